@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use benchmark::sha256_pad;
 use compiler::builder::{build_bytecode_s, Builder};
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use vm::emulator::Emulator;
@@ -41,6 +42,20 @@ fn compile_rc4_bytecode(key: &[u8], buf_len: usize) -> Vec<u8> {
     builder.dump().unwrap()
 }
 
+fn compile_sha256_bytecode(msg_len: usize) -> Vec<u8> {
+    let buf = sha256_pad(&vec![0u8; msg_len]);
+    let msg_src = generate_source("msg", &buf);
+    let s = format!(
+        "{}\n{msg_src}\n",
+        include_str!("../../compiler/src/test/sha256.S")
+            .replace("%NBLOCKS%", &format!("{}", buf.len() / 64))
+    );
+    let mut builder = Builder::new();
+    build_bytecode_s(s, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    builder.dump().unwrap()
+}
+
 fn test_fibonacci(bytecode: &[u8]) {
     let mut emulator = Emulator::with_bytecode(bytecode);
     emulator.execute().unwrap();
@@ -161,6 +176,108 @@ fn bm_rust_rc4(c: &mut Criterion) {
     group.finish();
 }
 
+fn test_sha256(bytecode: &[u8]) {
+    let mut emulator = Emulator::with_bytecode(bytecode);
+    emulator.execute().unwrap();
+    assert_ne!(emulator.ip() as usize, 0);
+}
+
+fn bm_sha256(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sha256");
+
+    let mut bytecode_map = HashMap::new();
+    for &n in &[256, 512, 1024, 2048, 4096] {
+        bytecode_map.insert(n, compile_sha256_bytecode(n));
+    }
+
+    for &n in &[256, 512, 1024, 2048, 4096] {
+        group.bench_function(format!("sha256_{}", n), |b| {
+            b.iter(|| test_sha256(black_box(bytecode_map.get(&n).unwrap())))
+        });
+    }
+
+    group.finish();
+}
+
+fn bm_rust_sha256(c: &mut Criterion) {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+
+    fn sha256_compress(state: &mut [u32; 8], block: &[u8]) {
+        let mut w = [0u32; 64];
+        for t in 0..16 {
+            w[t] = u32::from_be_bytes(block[t * 4..t * 4 + 4].try_into().unwrap());
+        }
+        for t in 16..64 {
+            let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+            let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+            w[t] = w[t - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[t - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for t in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[t])
+                .wrapping_add(w[t]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+
+    fn rust_sha256(msg_len: usize) -> [u32; 8] {
+        let mut state = [
+            0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+            0x5be0cd19,
+        ];
+        let buf = sha256_pad(&vec![0u8; msg_len]);
+        for block in buf.chunks(64) {
+            sha256_compress(&mut state, block);
+        }
+        state
+    }
+
+    let mut group = c.benchmark_group("rust_sha256");
+
+    for &n in &[256, 512, 1024, 2048, 4096] {
+        group.bench_function(format!("rust_sha256_{}", n), |b| {
+            b.iter(|| rust_sha256(black_box(n)))
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(fibonacci, bm_fibonacci, bm_rust_fibonacci);
 criterion_group!(rc4, bm_rc4, bm_rust_rc4);
-criterion_main!(fibonacci, rc4);
+criterion_group!(sha256, bm_sha256, bm_rust_sha256);
+criterion_main!(fibonacci, rc4, sha256);
@@ -1 +1,10 @@
-
+/// Pads `msg` to a multiple of the 64-byte block size as per FIPS 180-4.
+pub fn sha256_pad(msg: &[u8]) -> Vec<u8> {
+    let mut buf = msg.to_vec();
+    buf.push(0x80);
+    while buf.len() % 64 != 56 {
+        buf.push(0);
+    }
+    buf.extend_from_slice(&((msg.len() as u64) * 8).to_be_bytes());
+    buf
+}
@@ -1,7 +1,11 @@
 use std::collections::HashMap;
 
 use log::{error, trace};
-use vm::isa::{Instruction, Mnemonic, OpCode, Operand};
+use vm::{
+    custom::OperandKind,
+    emulator::Register,
+    isa::{Instruction, Mnemonic, OpCode, Operand, OperandSize},
+};
 
 use crate::parser::{Expr, LexInstruction, Parser};
 
@@ -30,6 +34,50 @@ pub enum CompileState {
         lexi: LexInstruction,
         instruction: Instruction,
     },
+    /// The object is pending IP-relative displacement resolution of the
+    /// memory operand `op`
+    UnresolvedRelative {
+        offset: usize,
+        label: String,
+        op: usize,
+        lexi: LexInstruction,
+        instruction: Instruction,
+    },
+}
+
+/// Returns whether `imm` fits `size`, either as an unsigned value or as a
+/// sign-extended negative one, e.g. `-1` for a byte
+fn fits_size(size: OperandSize, imm: u64) -> bool {
+    imm <= size.mask() || (imm as i64) >> (size.to_size() * 8 - 1) == -1
+}
+
+/// Checks that an immediate fits the size of the destination operand
+fn check_immediate_size(insn: &Instruction) -> Result<(), String> {
+    match (&insn.operands[0], &insn.operands[1], &insn.operands[2]) {
+        (Operand::Memory { size, .. }, Operand::Immediate64(imm), _) if !fits_size(*size, *imm) => {
+            Err(format!("Immediate overflows {size}: {imm}"))
+        }
+        (Operand::Register(reg), Operand::Immediate64(imm), _)
+        | (Operand::Register(reg), _, Operand::Immediate64(imm))
+            if !fits_size(reg.size(), *imm) =>
+        {
+            Err(format!("Immediate overflows {reg}: {imm}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Represents a user-defined mnemonic of a custom instruction
+///
+/// See [`vm::custom`] for the custom instructions of the emulator.
+#[derive(Debug, Clone)]
+pub struct CustomMnemonic {
+    /// The name of the mnemonic, matched case-insensitively
+    pub name: String,
+    /// The custom opcode the mnemonic is compiled to
+    pub opcode: u8,
+    /// The kinds of the operands
+    pub operands: Vec<OperandKind>,
 }
 
 /// Represents a builder for a input source
@@ -41,6 +89,8 @@ pub struct Builder {
     pub labels: HashMap<String, u64>,
     /// Current position in bytes
     pub cursor: usize,
+    /// User-defined mnemonics of custom instructions
+    pub mnemonics: Vec<CustomMnemonic>,
 }
 
 impl Builder {
@@ -50,6 +100,45 @@ impl Builder {
         Self::default()
     }
 
+    /// Registers the mnemonic `name` of the custom `opcode`, which takes
+    /// `operands`, replacing a mnemonic previously registered with the name.
+    ///
+    /// The operands must match those registered on the emulator with
+    /// `Emulator::register_custom`.
+    pub fn register_mnemonic(
+        &mut self,
+        name: &str,
+        opcode: u8,
+        operands: &[OperandKind],
+    ) -> Result<(), String> {
+        if Mnemonic::from_str(name).is_some() {
+            return Err(format!("Mnemonic already defined: {name}"));
+        }
+        if operands.len() > 3 {
+            return Err(format!("Too many operands ({}) for {name}", operands.len()));
+        }
+        if let Some(x) = self
+            .mnemonics
+            .iter()
+            .find(|x| x.opcode == opcode && !x.name.eq_ignore_ascii_case(name))
+        {
+            return Err(format!(
+                "Custom opcode {opcode} already mapped to {}",
+                x.name
+            ));
+        }
+
+        self.mnemonics
+            .retain(|x| !x.name.eq_ignore_ascii_case(name));
+        self.mnemonics.push(CustomMnemonic {
+            name: name.to_owned(),
+            opcode,
+            operands: operands.to_vec(),
+        });
+
+        Ok(())
+    }
+
     /// Finally dump the VM bytecode.
     pub fn dump(&mut self) -> Result<Vec<u8>, String> {
         let mut vec = Vec::new();
@@ -110,6 +199,7 @@ impl Builder {
                         );
 
                         instruction.set_immediate(*label_loc);
+                        check_immediate_size(instruction)?;
                         instruction.encode(&mut buf).unwrap();
 
                         final_state.push(CompileState::Compiled {
@@ -154,6 +244,45 @@ impl Builder {
                     }
                     None => return Err(format!("Unresolved label: {label}")),
                 },
+                CompileState::UnresolvedRelative {
+                    offset,
+                    label,
+                    op,
+                    lexi,
+                    instruction,
+                } => match self.labels.get(label) {
+                    Some(label_loc) => {
+                        let mut buf = Vec::new();
+
+                        // The IP points to the next instruction while executing
+                        instruction.encode(&mut buf).unwrap();
+                        let target = *label_loc as i64 - (*offset + buf.len()) as i64;
+                        buf.clear();
+
+                        if let Operand::Memory { displacement, .. } = &mut instruction.operands[*op]
+                        {
+                            *displacement = displacement
+                                .checked_add(target)
+                                .ok_or(format!("Displacement overflows: {label}"))?;
+                        }
+                        instruction.encode(&mut buf).unwrap();
+
+                        trace!(
+                            "{offset}: {} => ({}) {:02x?}",
+                            lexi.mnemonic,
+                            buf.len(),
+                            buf
+                        );
+
+                        final_state.push(CompileState::Compiled {
+                            offset: *offset,
+                            lexi: lexi.to_owned(),
+                            instruction: instruction.to_owned(),
+                            buf,
+                        });
+                    }
+                    None => return Err(format!("Unresolved label: {label}")),
+                },
             }
         }
 
@@ -164,6 +293,32 @@ impl Builder {
 
     /// Compile an intermediate [`LexInstruction`] into a compilation state
     pub fn compile_instruction(&mut self, lexi: &LexInstruction) -> Result<(), String> {
+        // mov r0, qword [rel table] is based on the IP, and its displacement
+        // is resolved along with the label
+        let mut relative = None;
+        let mut lexi = lexi.to_owned();
+        for (i, x) in lexi.operands.iter_mut().enumerate() {
+            if let Expr::RelMemoryOp {
+                size,
+                displacement,
+                label,
+            } = x
+            {
+                if relative.is_some() {
+                    return Err("Too many IP-relative operands".into());
+                }
+                relative = Some((i, label.to_owned()));
+                *x = Expr::MemoryOp {
+                    size: *size,
+                    displacement: *displacement,
+                    scale: 1,
+                    index_reg: None,
+                    base_reg: Some(Register::IP),
+                };
+            }
+        }
+        let lexi = &lexi;
+
         let op = &lexi.operands;
         let mnemonic = lexi.mnemonic;
 
@@ -174,14 +329,60 @@ impl Builder {
             return Err(format!("Too many operands ({}) for {mnemonic}", op.len()));
         }
 
+        // add r0, r0, r1 is just add r0, r1, so the shorter two-operand form is
+        // chosen whenever the destination is also a source. Custom instructions
+        // have a single form and are left as is.
+        if let [Expr::RegisterOp(reg0), Expr::RegisterOp(reg1), rhs] = &op[..] {
+            let commutative = matches!(
+                mnemonic,
+                Mnemonic::Add
+                    | Mnemonic::And
+                    | Mnemonic::Or
+                    | Mnemonic::Xor
+                    | Mnemonic::Imul
+                    | Mnemonic::Adc
+                    | Mnemonic::Mulh
+                    | Mnemonic::Imulh
+            );
+            let rhs = match rhs {
+                _ if matches!(mnemonic, Mnemonic::Custom(_)) => None,
+                _ if reg0 == reg1 => Some(rhs.to_owned()),
+                Expr::RegisterOp(reg2) if commutative && reg0 == reg2 => {
+                    Some(Expr::RegisterOp(*reg1))
+                }
+                _ => None,
+            };
+            if let Some(rhs) = rhs {
+                return self.compile_instruction(&LexInstruction {
+                    mnemonic,
+                    operands: vec![Expr::RegisterOp(*reg0), rhs],
+                });
+            }
+        }
+
         let mut insn = Instruction::new();
         let mut buf = Vec::<u8>::new();
 
         match mnemonic {
-            Mnemonic::Exit | Mnemonic::Ud => {
+            Mnemonic::Exit
+            | Mnemonic::Ud
+            | Mnemonic::Nop
+            | Mnemonic::Hlt
+            | Mnemonic::Cpuid
+            | Mnemonic::Ret
+            | Mnemonic::RepMovsb
+            | Mnemonic::RepStosb
+            | Mnemonic::RepCmpsb => {
                 match mnemonic {
                     Mnemonic::Exit => insn.set_opcode(OpCode::Exit),
                     Mnemonic::Ud => insn.set_opcode(OpCode::Ud),
+                    Mnemonic::Nop => insn.set_opcode(OpCode::Nop),
+                    Mnemonic::Hlt => insn.set_opcode(OpCode::Hlt),
+                    Mnemonic::Cpuid => insn.set_opcode(OpCode::Cpuid),
+                    Mnemonic::Ret => insn.set_opcode(OpCode::Ret),
+                    Mnemonic::RepMovsb => insn.set_opcode(OpCode::RepMovsb),
+                    Mnemonic::RepStosb => insn.set_opcode(OpCode::RepStosb),
+                    Mnemonic::RepCmpsb => insn.set_opcode(OpCode::RepCmpsb),
                     _ => unreachable!(),
                 };
 
@@ -193,6 +394,78 @@ impl Builder {
                     buf: buf.clone(),
                 });
             }
+            // add r0, r1, r2 or add r0, r1, 5
+            Mnemonic::Add
+            | Mnemonic::Adc
+            | Mnemonic::Sub
+            | Mnemonic::Sbb
+            | Mnemonic::And
+            | Mnemonic::Or
+            | Mnemonic::Xor
+            | Mnemonic::Imul
+            | Mnemonic::Mulh
+            | Mnemonic::Imulh
+            | Mnemonic::Div
+            | Mnemonic::Idiv
+            | Mnemonic::Rem
+            | Mnemonic::Irem
+            | Mnemonic::Shl
+            | Mnemonic::Shr
+            | Mnemonic::Sar
+            | Mnemonic::Rol
+            | Mnemonic::Ror
+            | Mnemonic::Rcl
+            | Mnemonic::Rcr
+                if op.len() == 3 =>
+            {
+                match (&op[0], &op[1], &op[2]) {
+                    (Expr::RegisterOp(reg0), Expr::RegisterOp(reg1), Expr::RegisterOp(reg2)) => {
+                        insn.set_op0_reg(*reg0);
+                        insn.set_op1_reg(*reg1);
+                        insn.set_op2_reg(*reg2);
+                    }
+                    (Expr::RegisterOp(reg0), Expr::RegisterOp(reg1), Expr::Immediate(imm)) => {
+                        insn.set_op0_reg(*reg0);
+                        insn.set_op1_reg(*reg1);
+                        insn.set_op2_immediate(*imm);
+                    }
+                    _ => return Err(format!("Unsupported operands for {mnemonic}")),
+                };
+                let opcode = match mnemonic {
+                    Mnemonic::Add => OpCode::AddRRRI,
+                    Mnemonic::Adc => OpCode::AdcRRRI,
+                    Mnemonic::Sub => OpCode::SubRRRI,
+                    Mnemonic::Sbb => OpCode::SbbRRRI,
+                    Mnemonic::And => OpCode::AndRRRI,
+                    Mnemonic::Or => OpCode::OrRRRI,
+                    Mnemonic::Xor => OpCode::XorRRRI,
+                    Mnemonic::Imul => OpCode::ImulRRRI,
+                    Mnemonic::Mulh => OpCode::MulhRRRI,
+                    Mnemonic::Imulh => OpCode::ImulhRRRI,
+                    Mnemonic::Div => OpCode::DivRRRI,
+                    Mnemonic::Idiv => OpCode::IdivRRRI,
+                    Mnemonic::Rem => OpCode::RemRRRI,
+                    Mnemonic::Irem => OpCode::IremRRRI,
+                    Mnemonic::Shl => OpCode::ShlRRRI,
+                    Mnemonic::Shr => OpCode::ShrRRRI,
+                    Mnemonic::Sar => OpCode::SarRRRI,
+                    Mnemonic::Rol => OpCode::RolRRRI,
+                    Mnemonic::Ror => OpCode::RorRRRI,
+                    Mnemonic::Rcl => OpCode::RclRRRI,
+                    Mnemonic::Rcr => OpCode::RcrRRRI,
+                    _ => unreachable!(),
+                };
+                insn.set_opcode(opcode);
+                check_immediate_size(&insn)?;
+
+                insn.encode(&mut buf).map_err(|e| e.to_string())?;
+                self.state.push(CompileState::Compiled {
+                    offset: self.cursor,
+                    lexi: lexi.to_owned(),
+                    instruction: insn.to_owned(),
+                    buf: buf.clone(),
+                });
+            }
             Mnemonic::Mov
             | Mnemonic::Add
             | Mnemonic::Sub
@@ -200,7 +473,48 @@ impl Builder {
             | Mnemonic::Or
             | Mnemonic::Xor
             | Mnemonic::Xchg
-            | Mnemonic::Imul => {
+            | Mnemonic::Imul
+            | Mnemonic::Adc
+            | Mnemonic::Sbb
+            | Mnemonic::Mul
+            | Mnemonic::Mulh
+            | Mnemonic::Imulh
+            | Mnemonic::Div
+            | Mnemonic::Idiv
+            | Mnemonic::Rem
+            | Mnemonic::Irem
+            | Mnemonic::Shl
+            | Mnemonic::Shr
+            | Mnemonic::Sar
+            | Mnemonic::Rol
+            | Mnemonic::Ror
+            | Mnemonic::Rcl
+            | Mnemonic::Rcr
+            | Mnemonic::Test
+            | Mnemonic::Cmp
+            | Mnemonic::Cmovz
+            | Mnemonic::Cmovnz
+            | Mnemonic::Cmovle
+            | Mnemonic::Cmovg
+            | Mnemonic::Cmovge
+            | Mnemonic::Cmovb
+            | Mnemonic::Cmovl
+            | Mnemonic::Cmova
+            | Mnemonic::Cmovae
+            | Mnemonic::Cmovbe
+            | Mnemonic::Cmovs
+            | Mnemonic::Cmovns
+            | Mnemonic::Cmovo
+            | Mnemonic::Cmovno
+            | Mnemonic::Cmovp
+            | Mnemonic::Cmovnp
+            | Mnemonic::Popcnt
+            | Mnemonic::Lzcnt
+            | Mnemonic::Tzcnt
+            | Mnemonic::Bt
+            | Mnemonic::Bts
+            | Mnemonic::Btr
+            | Mnemonic::Btc => {
                 let mut offsetof = None;
 
                 match &op[0] {
@@ -256,58 +570,161 @@ impl Builder {
                     x => return Err(format!("Unexpected operand: {x:?}")),
                 };
 
-                match (&op[0], &op[1]) {
+                // mul r0, r0
+                if let (Mnemonic::Mul, Expr::RegisterOp(lhs), Expr::RegisterOp(rhs)) =
+                    (mnemonic, &op[0], &op[1])
+                {
+                    if lhs.full() == rhs.full() {
+                        return Err(format!("{mnemonic} requires two distinct registers"));
+                    }
+                }
+
+                let opcode = match (&op[0], &op[1]) {
                     (Expr::RegisterOp(_), Expr::RegisterOp(_)) => match mnemonic {
-                        Mnemonic::Mov => insn.set_opcode(OpCode::MovRR),
-                        Mnemonic::Add => insn.set_opcode(OpCode::AddRR),
-                        Mnemonic::Sub => insn.set_opcode(OpCode::SubRR),
-                        Mnemonic::And => insn.set_opcode(OpCode::AndRR),
-                        Mnemonic::Or => insn.set_opcode(OpCode::OrRR),
-                        Mnemonic::Xor => insn.set_opcode(OpCode::XorRR),
-                        Mnemonic::Xchg => insn.set_opcode(OpCode::XchgRR),
-                        Mnemonic::Imul => insn.set_opcode(OpCode::ImulRR),
-                        _ => unreachable!(),
+                        Mnemonic::Mov => Some(OpCode::MovRR),
+                        Mnemonic::Add => Some(OpCode::AddRR),
+                        Mnemonic::Sub => Some(OpCode::SubRR),
+                        Mnemonic::And => Some(OpCode::AndRR),
+                        Mnemonic::Or => Some(OpCode::OrRR),
+                        Mnemonic::Xor => Some(OpCode::XorRR),
+                        Mnemonic::Xchg => Some(OpCode::XchgRR),
+                        Mnemonic::Imul => Some(OpCode::ImulRR),
+                        Mnemonic::Adc => Some(OpCode::AdcRR),
+                        Mnemonic::Sbb => Some(OpCode::SbbRR),
+                        Mnemonic::Mul => Some(OpCode::MulRR),
+                        Mnemonic::Mulh => Some(OpCode::MulhRR),
+                        Mnemonic::Imulh => Some(OpCode::ImulhRR),
+                        Mnemonic::Div => Some(OpCode::DivRR),
+                        Mnemonic::Idiv => Some(OpCode::IdivRR),
+                        Mnemonic::Rem => Some(OpCode::RemRR),
+                        Mnemonic::Irem => Some(OpCode::IremRR),
+                        Mnemonic::Shl => Some(OpCode::ShlRR),
+                        Mnemonic::Shr => Some(OpCode::ShrRR),
+                        Mnemonic::Sar => Some(OpCode::SarRR),
+                        Mnemonic::Rol => Some(OpCode::RolRR),
+                        Mnemonic::Ror => Some(OpCode::RorRR),
+                        Mnemonic::Rcl => Some(OpCode::RclRR),
+                        Mnemonic::Rcr => Some(OpCode::RcrRR),
+                        Mnemonic::Cmp => Some(OpCode::CmpRR),
+                        Mnemonic::Test => Some(OpCode::TestRR),
+                        Mnemonic::Cmovz => Some(OpCode::CmovzRR),
+                        Mnemonic::Cmovnz => Some(OpCode::CmovnzRR),
+                        Mnemonic::Cmovle => Some(OpCode::CmovleRR),
+                        Mnemonic::Cmovg => Some(OpCode::CmovgRR),
+                        Mnemonic::Cmovge => Some(OpCode::CmovgeRR),
+                        Mnemonic::Cmovb => Some(OpCode::CmovbRR),
+                        Mnemonic::Cmovl => Some(OpCode::CmovlRR),
+                        Mnemonic::Cmova => Some(OpCode::CmovaRR),
+                        Mnemonic::Cmovae => Some(OpCode::CmovaeRR),
+                        Mnemonic::Cmovbe => Some(OpCode::CmovbeRR),
+                        Mnemonic::Cmovs => Some(OpCode::CmovsRR),
+                        Mnemonic::Cmovns => Some(OpCode::CmovnsRR),
+                        Mnemonic::Cmovo => Some(OpCode::CmovoRR),
+                        Mnemonic::Cmovno => Some(OpCode::CmovnoRR),
+                        Mnemonic::Cmovp => Some(OpCode::CmovpRR),
+                        Mnemonic::Cmovnp => Some(OpCode::CmovnpRR),
+                        Mnemonic::Popcnt => Some(OpCode::PopcntRR),
+                        Mnemonic::Lzcnt => Some(OpCode::LzcntRR),
+                        Mnemonic::Tzcnt => Some(OpCode::TzcntRR),
+                        Mnemonic::Bt => Some(OpCode::BtRR),
+                        Mnemonic::Bts => Some(OpCode::BtsRR),
+                        Mnemonic::Btr => Some(OpCode::BtrRR),
+                        Mnemonic::Btc => Some(OpCode::BtcRR),
+                        _ => None,
                     },
                     (Expr::RegisterOp(_), Expr::Immediate(_))
                     | (Expr::RegisterOp(_), Expr::LabelRef(_)) => match mnemonic {
-                        Mnemonic::Mov => insn.set_opcode(OpCode::MovRIMM),
-                        Mnemonic::Add => insn.set_opcode(OpCode::AddRIMM),
-                        Mnemonic::Sub => insn.set_opcode(OpCode::SubRIMM),
-                        Mnemonic::And => insn.set_opcode(OpCode::AndRIMM),
-                        Mnemonic::Or => insn.set_opcode(OpCode::OrRIMM),
-                        Mnemonic::Xor => insn.set_opcode(OpCode::XorRIMM),
-                        Mnemonic::Xchg => unreachable!(),
-                        Mnemonic::Imul => insn.set_opcode(OpCode::ImulRIMM),
-                        _ => unreachable!(),
+                        Mnemonic::Mov => Some(OpCode::MovRIMM),
+                        Mnemonic::Add => Some(OpCode::AddRIMM),
+                        Mnemonic::Sub => Some(OpCode::SubRIMM),
+                        Mnemonic::And => Some(OpCode::AndRIMM),
+                        Mnemonic::Or => Some(OpCode::OrRIMM),
+                        Mnemonic::Xor => Some(OpCode::XorRIMM),
+                        Mnemonic::Imul => Some(OpCode::ImulRIMM),
+                        Mnemonic::Adc => Some(OpCode::AdcRIMM),
+                        Mnemonic::Sbb => Some(OpCode::SbbRIMM),
+                        Mnemonic::Div => Some(OpCode::DivRIMM),
+                        Mnemonic::Idiv => Some(OpCode::IdivRIMM),
+                        Mnemonic::Rem => Some(OpCode::RemRIMM),
+                        Mnemonic::Irem => Some(OpCode::IremRIMM),
+                        Mnemonic::Shl => Some(OpCode::ShlRIMM),
+                        Mnemonic::Shr => Some(OpCode::ShrRIMM),
+                        Mnemonic::Sar => Some(OpCode::SarRIMM),
+                        Mnemonic::Rol => Some(OpCode::RolRIMM),
+                        Mnemonic::Ror => Some(OpCode::RorRIMM),
+                        Mnemonic::Rcl => Some(OpCode::RclRIMM),
+                        Mnemonic::Rcr => Some(OpCode::RcrRIMM),
+                        Mnemonic::Cmp => Some(OpCode::CmpRIMM),
+                        Mnemonic::Test => Some(OpCode::TestRIMM),
+                        Mnemonic::Bt => Some(OpCode::BtRIMM),
+                        Mnemonic::Bts => Some(OpCode::BtsRIMM),
+                        Mnemonic::Btr => Some(OpCode::BtrRIMM),
+                        Mnemonic::Btc => Some(OpCode::BtcRIMM),
+                        _ => None,
                     },
-                    (
-                        Expr::RegisterOp(_),
-                        Expr::MemoryOp {
-                            size: _,
-                            displacement: _,
-                            scale: _,
-                            index_reg: _,
-                            base_reg: _,
-                        },
-                    ) => match mnemonic {
-                        Mnemonic::Mov => insn.set_opcode(OpCode::MovRRM),
-                        _ => unimplemented!(),
+                    (Expr::RegisterOp(_), Expr::MemoryOp { .. }) => match mnemonic {
+                        Mnemonic::Mov => Some(OpCode::MovRRM),
+                        Mnemonic::Add => Some(OpCode::AddRRM),
+                        Mnemonic::Sub => Some(OpCode::SubRRM),
+                        Mnemonic::And => Some(OpCode::AndRRM),
+                        Mnemonic::Or => Some(OpCode::OrRRM),
+                        Mnemonic::Xor => Some(OpCode::XorRRM),
+                        Mnemonic::Xchg => Some(OpCode::XchgRRM),
+                        Mnemonic::Imul => Some(OpCode::ImulRRM),
+                        Mnemonic::Adc => Some(OpCode::AdcRRM),
+                        Mnemonic::Sbb => Some(OpCode::SbbRRM),
+                        Mnemonic::Mulh => Some(OpCode::MulhRRM),
+                        Mnemonic::Imulh => Some(OpCode::ImulhRRM),
+                        Mnemonic::Cmp => Some(OpCode::CmpRRM),
+                        Mnemonic::Test => Some(OpCode::TestRRM),
+                        Mnemonic::Popcnt => Some(OpCode::PopcntRRM),
+                        Mnemonic::Lzcnt => Some(OpCode::LzcntRRM),
+                        Mnemonic::Tzcnt => Some(OpCode::TzcntRRM),
+                        _ => None,
                     },
-                    (
-                        Expr::MemoryOp {
-                            size: _,
-                            displacement: _,
-                            scale: _,
-                            index_reg: _,
-                            base_reg: _,
-                        },
-                        Expr::RegisterOp(_),
-                    ) => match mnemonic {
-                        Mnemonic::Mov => insn.set_opcode(OpCode::MovRMR),
-                        _ => unimplemented!(),
+                    (Expr::MemoryOp { .. }, Expr::RegisterOp(_)) => match mnemonic {
+                        Mnemonic::Mov => Some(OpCode::MovRMR),
+                        Mnemonic::Add => Some(OpCode::AddRMR),
+                        Mnemonic::Sub => Some(OpCode::SubRMR),
+                        Mnemonic::And => Some(OpCode::AndRMR),
+                        Mnemonic::Or => Some(OpCode::OrRMR),
+                        Mnemonic::Xor => Some(OpCode::XorRMR),
+                        Mnemonic::Adc => Some(OpCode::AdcRMR),
+                        Mnemonic::Sbb => Some(OpCode::SbbRMR),
+                        Mnemonic::Xchg => Some(OpCode::XchgRMR),
+                        Mnemonic::Cmp => Some(OpCode::CmpRMR),
+                        Mnemonic::Test => Some(OpCode::TestRMR),
+                        Mnemonic::Bt => Some(OpCode::BtRMR),
+                        Mnemonic::Bts => Some(OpCode::BtsRMR),
+                        Mnemonic::Btr => Some(OpCode::BtrRMR),
+                        Mnemonic::Btc => Some(OpCode::BtcRMR),
+                        _ => None,
                     },
-                    _ => todo!(),
-                }
+                    (Expr::MemoryOp { .. }, Expr::Immediate(_))
+                    | (Expr::MemoryOp { .. }, Expr::LabelRef(_)) => match mnemonic {
+                        Mnemonic::Mov => Some(OpCode::MovRMIMM),
+                        Mnemonic::Add => Some(OpCode::AddRMIMM),
+                        Mnemonic::Sub => Some(OpCode::SubRMIMM),
+                        Mnemonic::And => Some(OpCode::AndRMIMM),
+                        Mnemonic::Or => Some(OpCode::OrRMIMM),
+                        Mnemonic::Xor => Some(OpCode::XorRMIMM),
+                        Mnemonic::Adc => Some(OpCode::AdcRMIMM),
+                        Mnemonic::Sbb => Some(OpCode::SbbRMIMM),
+                        Mnemonic::Cmp => Some(OpCode::CmpRMIMM),
+                        Mnemonic::Test => Some(OpCode::TestRMIMM),
+                        Mnemonic::Bt => Some(OpCode::BtRMIMM),
+                        Mnemonic::Bts => Some(OpCode::BtsRMIMM),
+                        Mnemonic::Btr => Some(OpCode::BtrRMIMM),
+                        Mnemonic::Btc => Some(OpCode::BtcRMIMM),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match opcode {
+                    Some(opcode) => insn.set_opcode(opcode),
+                    None => return Err(format!("Unsupported operands for {mnemonic}")),
+                };
+                check_immediate_size(&insn)?;
 
                 insn.encode(&mut buf).map_err(|e| e.to_string())?;
                 if let Some(label) = offsetof {
@@ -327,7 +744,514 @@ impl Builder {
                     });
                 }
             }
-            Mnemonic::Inc | Mnemonic::Dec => {
+            Mnemonic::Lea => {
+                match (&op[0], &op[1]) {
+                    (
+                        Expr::RegisterOp(reg),
+                        Expr::MemoryOp {
+                            size,
+                            displacement,
+                            scale,
+                            index_reg,
+                            base_reg,
+                        },
+                    ) => {
+                        insn.set_op0_reg(*reg);
+                        insn.set_op1_mem(Operand::Memory {
+                            size: *size,
+                            displacement: *displacement,
+                            scale: *scale,
+                            index_reg: *index_reg,
+                            base_reg: *base_reg,
+                        });
+                        insn.set_opcode(OpCode::LeaRRM);
+                    }
+                    _ => return Err(format!("Unsupported operands for {mnemonic}")),
+                };
+
+                insn.encode(&mut buf).map_err(|e| e.to_string())?;
+                self.state.push(CompileState::Compiled {
+                    offset: self.cursor,
+                    lexi: lexi.to_owned(),
+                    instruction: insn.to_owned(),
+                    buf: buf.clone(),
+                });
+            }
+            Mnemonic::Movzx | Mnemonic::Movsx => {
+                match &op[0] {
+                    Expr::RegisterOp(reg) => {
+                        insn.set_op0_reg(*reg);
+                    }
+                    x => return Err(format!("Unexpected operand: {x:?}")),
+                };
+                let opcode = match (mnemonic, &op[1]) {
+                    (
+                        _,
+                        Expr::MemoryOp {
+                            size,
+                            displacement,
+                            scale,
+                            index_reg,
+                            base_reg,
+                        },
+                    ) => {
+                        insn.set_op1_mem(Operand::Memory {
+                            size: *size,
+                            displacement: *displacement,
+                            scale: *scale,
+                            index_reg: *index_reg,
+                            base_reg: *base_reg,
+                        });
+
+                        match mnemonic {
+                            Mnemonic::Movzx => OpCode::MovzxRRM,
+                            Mnemonic::Movsx => OpCode::MovsxRRM,
+                            _ => unreachable!(),
+                        }
+                    }
+                    // movzx r1, r0b
+                    (_, Expr::RegisterOp(reg)) if !matches!(reg.size(), OperandSize::QWord) => {
+                        insn.set_op1_reg(*reg);
+
+                        match (mnemonic, reg.size()) {
+                            (Mnemonic::Movzx, OperandSize::Byte) => OpCode::MovzxRR8,
+                            (Mnemonic::Movzx, OperandSize::Word) => OpCode::MovzxRR16,
+                            (Mnemonic::Movzx, OperandSize::DWord) => OpCode::MovzxRR32,
+                            (Mnemonic::Movsx, OperandSize::Byte) => OpCode::MovsxRR8,
+                            (Mnemonic::Movsx, OperandSize::Word) => OpCode::MovsxRR16,
+                            (Mnemonic::Movsx, OperandSize::DWord) => OpCode::MovsxRR32,
+                            _ => return Err(format!("Unsupported operands for {mnemonic}")),
+                        }
+                    }
+                    (_, x) => return Err(format!("Unexpected operand: {x:?}")),
+                };
+                insn.set_opcode(opcode);
+
+                insn.encode(&mut buf).map_err(|e| e.to_string())?;
+                self.state.push(CompileState::Compiled {
+                    offset: self.cursor,
+                    lexi: lexi.to_owned(),
+                    instruction: insn.to_owned(),
+                    buf: buf.clone(),
+                });
+            }
+            Mnemonic::Fmov
+            | Mnemonic::Fadd
+            | Mnemonic::Fsub
+            | Mnemonic::Fmul
+            | Mnemonic::Fdiv
+            | Mnemonic::Fsqrt
+            | Mnemonic::Fcmp
+            | Mnemonic::Cvtsi2f
+            | Mnemonic::Cvtf2si => {
+                let opcode = match (&op[0], &op[1]) {
+                    (Expr::FloatRegisterOp(freg0), Expr::FloatRegisterOp(freg1)) => {
+                        insn.set_op0_freg(*freg0);
+                        insn.set_op1_freg(*freg1);
+
+                        match mnemonic {
+                            Mnemonic::Fmov => Some(OpCode::FmovFF),
+                            Mnemonic::Fadd => Some(OpCode::FaddFF),
+                            Mnemonic::Fsub => Some(OpCode::FsubFF),
+                            Mnemonic::Fmul => Some(OpCode::FmulFF),
+                            Mnemonic::Fdiv => Some(OpCode::FdivFF),
+                            Mnemonic::Fsqrt => Some(OpCode::FsqrtFF),
+                            Mnemonic::Fcmp => Some(OpCode::FcmpFF),
+                            _ => None,
+                        }
+                    }
+                    (
+                        Expr::FloatRegisterOp(freg),
+                        Expr::MemoryOp {
+                            size,
+                            displacement,
+                            scale,
+                            index_reg,
+                            base_reg,
+                        },
+                    ) if matches!(size, OperandSize::DWord | OperandSize::QWord) => {
+                        insn.set_op0_freg(*freg);
+                        insn.set_op1_mem(Operand::Memory {
+                            size: *size,
+                            displacement: *displacement,
+                            scale: *scale,
+                            index_reg: *index_reg,
+                            base_reg: *base_reg,
+                        });
+
+                        match mnemonic {
+                            Mnemonic::Fmov => Some(OpCode::FmovFRM),
+                            _ => None,
+                        }
+                    }
+                    (
+                        Expr::MemoryOp {
+                            size,
+                            displacement,
+                            scale,
+                            index_reg,
+                            base_reg,
+                        },
+                        Expr::FloatRegisterOp(freg),
+                    ) if matches!(size, OperandSize::DWord | OperandSize::QWord) => {
+                        insn.set_op0_mem(Operand::Memory {
+                            size: *size,
+                            displacement: *displacement,
+                            scale: *scale,
+                            index_reg: *index_reg,
+                            base_reg: *base_reg,
+                        });
+                        insn.set_op1_freg(*freg);
+
+                        match mnemonic {
+                            Mnemonic::Fmov => Some(OpCode::FmovRMF),
+                            _ => None,
+                        }
+                    }
+                    (Expr::FloatRegisterOp(freg), Expr::FloatImmediate(num)) => {
+                        insn.set_op0_freg(*freg);
+                        insn.set_immediate(num.to_bits());
+
+                        match mnemonic {
+                            Mnemonic::Fmov => Some(OpCode::FmovFIMM),
+                            _ => None,
+                        }
+                    }
+                    // fmov f0, 2 takes the integer as its signed value, so that
+                    // fmov f0, -2 is -2.0
+                    (Expr::FloatRegisterOp(freg), Expr::Immediate(num)) => {
+                        insn.set_op0_freg(*freg);
+                        insn.set_immediate((*num as i64 as f64).to_bits());
+
+                        match mnemonic {
+                            Mnemonic::Fmov => Some(OpCode::FmovFIMM),
+                            _ => None,
+                        }
+                    }
+                    (Expr::FloatRegisterOp(freg), Expr::RegisterOp(reg)) => {
+                        insn.set_op0_freg(*freg);
+                        insn.set_op1_reg(*reg);
+
+                        match mnemonic {
+                            Mnemonic::Fmov => Some(OpCode::FmovFR),
+                            Mnemonic::Cvtsi2f => Some(OpCode::Cvtsi2fFR),
+                            _ => None,
+                        }
+                    }
+                    (Expr::RegisterOp(reg), Expr::FloatRegisterOp(freg)) => {
+                        insn.set_op0_reg(*reg);
+                        insn.set_op1_freg(*freg);
+
+                        match mnemonic {
+                            Mnemonic::Fmov => Some(OpCode::FmovRF),
+                            Mnemonic::Cvtf2si => Some(OpCode::Cvtf2siRF),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+                match opcode {
+                    Some(opcode) => insn.set_opcode(opcode),
+                    None => return Err(format!("Unsupported operands for {mnemonic}")),
+                };
+
+                insn.encode(&mut buf).map_err(|e| e.to_string())?;
+                self.state.push(CompileState::Compiled {
+                    offset: self.cursor,
+                    lexi: lexi.to_owned(),
+                    instruction: insn.to_owned(),
+                    buf: buf.clone(),
+                });
+            }
+            Mnemonic::Movdqu
+            | Mnemonic::Movq
+            | Mnemonic::Paddb
+            | Mnemonic::Paddw
+            | Mnemonic::Paddd
+            | Mnemonic::Paddq
+            | Mnemonic::Psubb
+            | Mnemonic::Psubw
+            | Mnemonic::Psubd
+            | Mnemonic::Psubq
+            | Mnemonic::Pand
+            | Mnemonic::Por
+            | Mnemonic::Pxor
+            | Mnemonic::Pcmpeqb
+            | Mnemonic::Pcmpeqw
+            | Mnemonic::Pcmpeqd
+            | Mnemonic::Pcmpeqq
+            | Mnemonic::Pshufb => {
+                let opcode = match (&op[0], &op[1]) {
+                    (Expr::VectorRegisterOp(vreg0), Expr::VectorRegisterOp(vreg1)) => {
+                        insn.set_op0_vreg(*vreg0);
+                        insn.set_op1_vreg(*vreg1);
+
+                        match mnemonic {
+                            Mnemonic::Movdqu => Some(OpCode::MovdquVV),
+                            Mnemonic::Paddb => Some(OpCode::PaddbVV),
+                            Mnemonic::Paddw => Some(OpCode::PaddwVV),
+                            Mnemonic::Paddd => Some(OpCode::PadddVV),
+                            Mnemonic::Paddq => Some(OpCode::PaddqVV),
+                            Mnemonic::Psubb => Some(OpCode::PsubbVV),
+                            Mnemonic::Psubw => Some(OpCode::PsubwVV),
+                            Mnemonic::Psubd => Some(OpCode::PsubdVV),
+                            Mnemonic::Psubq => Some(OpCode::PsubqVV),
+                            Mnemonic::Pand => Some(OpCode::PandVV),
+                            Mnemonic::Por => Some(OpCode::PorVV),
+                            Mnemonic::Pxor => Some(OpCode::PxorVV),
+                            Mnemonic::Pcmpeqb => Some(OpCode::PcmpeqbVV),
+                            Mnemonic::Pcmpeqw => Some(OpCode::PcmpeqwVV),
+                            Mnemonic::Pcmpeqd => Some(OpCode::PcmpeqdVV),
+                            Mnemonic::Pcmpeqq => Some(OpCode::PcmpeqqVV),
+                            Mnemonic::Pshufb => Some(OpCode::PshufbVV),
+                            _ => None,
+                        }
+                    }
+                    (
+                        Expr::VectorRegisterOp(vreg),
+                        Expr::MemoryOp {
+                            size: OperandSize::XmmWord,
+                            displacement,
+                            scale,
+                            index_reg,
+                            base_reg,
+                        },
+                    ) => {
+                        insn.set_op0_vreg(*vreg);
+                        insn.set_op1_mem(Operand::Memory {
+                            size: OperandSize::XmmWord,
+                            displacement: *displacement,
+                            scale: *scale,
+                            index_reg: *index_reg,
+                            base_reg: *base_reg,
+                        });
+
+                        match mnemonic {
+                            Mnemonic::Movdqu => Some(OpCode::MovdquVRM),
+                            _ => None,
+                        }
+                    }
+                    (
+                        Expr::MemoryOp {
+                            size: OperandSize::XmmWord,
+                            displacement,
+                            scale,
+                            index_reg,
+                            base_reg,
+                        },
+                        Expr::VectorRegisterOp(vreg),
+                    ) => {
+                        insn.set_op0_mem(Operand::Memory {
+                            size: OperandSize::XmmWord,
+                            displacement: *displacement,
+                            scale: *scale,
+                            index_reg: *index_reg,
+                            base_reg: *base_reg,
+                        });
+                        insn.set_op1_vreg(*vreg);
+
+                        match mnemonic {
+                            Mnemonic::Movdqu => Some(OpCode::MovdquRMV),
+                            _ => None,
+                        }
+                    }
+                    (Expr::VectorRegisterOp(vreg), Expr::RegisterOp(reg)) => {
+                        insn.set_op0_vreg(*vreg);
+                        insn.set_op1_reg(*reg);
+
+                        match mnemonic {
+                            Mnemonic::Movq => Some(OpCode::MovqVR),
+                            _ => None,
+                        }
+                    }
+                    (Expr::RegisterOp(reg), Expr::VectorRegisterOp(vreg)) => {
+                        insn.set_op0_reg(*reg);
+                        insn.set_op1_vreg(*vreg);
+
+                        match mnemonic {
+                            Mnemonic::Movq => Some(OpCode::MovqRV),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+                match opcode {
+                    Some(opcode) => insn.set_opcode(opcode),
+                    None => return Err(format!("Unsupported operands for {mnemonic}")),
+                };
+
+                insn.encode(&mut buf).map_err(|e| e.to_string())?;
+                self.state.push(CompileState::Compiled {
+                    offset: self.cursor,
+                    lexi: lexi.to_owned(),
+                    instruction: insn.to_owned(),
+                    buf: buf.clone(),
+                });
+            }
+            Mnemonic::In | Mnemonic::Out => {
+                // Ports are 16-bit
+                if let (Expr::Immediate(port), _) | (_, Expr::Immediate(port)) = (&op[0], &op[1]) {
+                    if *port > u16::MAX as u64 {
+                        return Err(format!("Port overflows: {port}"));
+                    }
+                }
+
+                let opcode = match (&op[0], &op[1]) {
+                    // in r0b, 60h
+                    (Expr::RegisterOp(reg), Expr::Immediate(port)) => {
+                        insn.set_op0_reg(*reg);
+                        insn.set_immediate(*port);
+
+                        match mnemonic {
+                            Mnemonic::In => Some(OpCode::InRIMM),
+                            _ => None,
+                        }
+                    }
+                    // out 60h, r0b
+                    (Expr::Immediate(port), Expr::RegisterOp(reg)) => {
+                        insn.set_op0_immediate(*port);
+                        insn.set_op1_reg(*reg);
+
+                        match mnemonic {
+                            Mnemonic::Out => Some(OpCode::OutIMMR),
+                            _ => None,
+                        }
+                    }
+                    // in r0b, r1 or out r1, r0b
+                    (Expr::RegisterOp(reg0), Expr::RegisterOp(reg1)) => {
+                        insn.set_op0_reg(*reg0);
+                        insn.set_op1_reg(*reg1);
+
+                        match mnemonic {
+                            Mnemonic::In => Some(OpCode::InRR),
+                            Mnemonic::Out => Some(OpCode::OutRR),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+                match opcode {
+                    Some(opcode) => insn.set_opcode(opcode),
+                    None => return Err(format!("Unsupported operands for {mnemonic}")),
+                };
+
+                insn.encode(&mut buf).map_err(|e| e.to_string())?;
+                self.state.push(CompileState::Compiled {
+                    offset: self.cursor,
+                    lexi: lexi.to_owned(),
+                    instruction: insn.to_owned(),
+                    buf: buf.clone(),
+                });
+            }
+            Mnemonic::Custom(opcode) => {
+                let custom = match self.mnemonics.iter().find(|x| x.opcode == opcode) {
+                    Some(custom) => custom,
+                    None => return Err(format!("Unregistered custom opcode: {opcode}")),
+                };
+                if op.len() != custom.operands.len() {
+                    return Err(format!(
+                        "Expected {} operands for {}, got {}",
+                        custom.operands.len(),
+                        custom.name,
+                        op.len()
+                    ));
+                }
+
+                insn.set_opcode(OpCode::Custom);
+                insn.custom = opcode;
+                for (i, (expr, kind)) in op.iter().zip(&custom.operands).enumerate() {
+                    insn.operands[i] = match (expr, kind) {
+                        (Expr::RegisterOp(reg), OperandKind::Register) => Operand::Register(*reg),
+                        (Expr::FloatRegisterOp(reg), OperandKind::FloatRegister) => {
+                            Operand::FloatRegister(*reg)
+                        }
+                        (Expr::VectorRegisterOp(reg), OperandKind::VectorRegister) => {
+                            Operand::VectorRegister(*reg)
+                        }
+                        (
+                            Expr::MemoryOp {
+                                size,
+                                displacement,
+                                scale,
+                                index_reg,
+                                base_reg,
+                            },
+                            OperandKind::Memory,
+                        ) => Operand::Memory {
+                            size: *size,
+                            displacement: *displacement,
+                            scale: *scale,
+                            index_reg: *index_reg,
+                            base_reg: *base_reg,
+                        },
+                        (Expr::Immediate(imm), OperandKind::Immediate) => {
+                            Operand::Immediate64(*imm)
+                        }
+                        _ => return Err(format!("Unsupported operands for {}", custom.name)),
+                    };
+                }
+
+                insn.encode(&mut buf).map_err(|e| e.to_string())?;
+                self.state.push(CompileState::Compiled {
+                    offset: self.cursor,
+                    lexi: lexi.to_owned(),
+                    instruction: insn.to_owned(),
+                    buf: buf.clone(),
+                });
+            }
+            Mnemonic::Setz
+            | Mnemonic::Setnz
+            | Mnemonic::Setle
+            | Mnemonic::Setg
+            | Mnemonic::Setge
+            | Mnemonic::Setb
+            | Mnemonic::Setl
+            | Mnemonic::Seta
+            | Mnemonic::Setae
+            | Mnemonic::Setbe
+            | Mnemonic::Sets
+            | Mnemonic::Setns
+            | Mnemonic::Seto
+            | Mnemonic::Setno
+            | Mnemonic::Setp
+            | Mnemonic::Setnp
+            | Mnemonic::Bswap => {
+                match &op[0] {
+                    Expr::RegisterOp(reg) => {
+                        insn.set_op0_reg(*reg);
+                    }
+                    x => return Err(format!("Unexpected operand: {x:?}")),
+                };
+                match mnemonic {
+                    Mnemonic::Setz => insn.set_opcode(OpCode::SetzR),
+                    Mnemonic::Setnz => insn.set_opcode(OpCode::SetnzR),
+                    Mnemonic::Setle => insn.set_opcode(OpCode::SetleR),
+                    Mnemonic::Setg => insn.set_opcode(OpCode::SetgR),
+                    Mnemonic::Setge => insn.set_opcode(OpCode::SetgeR),
+                    Mnemonic::Setb => insn.set_opcode(OpCode::SetbR),
+                    Mnemonic::Setl => insn.set_opcode(OpCode::SetlR),
+                    Mnemonic::Seta => insn.set_opcode(OpCode::SetaR),
+                    Mnemonic::Setae => insn.set_opcode(OpCode::SetaeR),
+                    Mnemonic::Setbe => insn.set_opcode(OpCode::SetbeR),
+                    Mnemonic::Sets => insn.set_opcode(OpCode::SetsR),
+                    Mnemonic::Setns => insn.set_opcode(OpCode::SetnsR),
+                    Mnemonic::Seto => insn.set_opcode(OpCode::SetoR),
+                    Mnemonic::Setno => insn.set_opcode(OpCode::SetnoR),
+                    Mnemonic::Setp => insn.set_opcode(OpCode::SetpR),
+                    Mnemonic::Setnp => insn.set_opcode(OpCode::SetnpR),
+                    Mnemonic::Bswap => insn.set_opcode(OpCode::BswapR),
+                    _ => unreachable!(),
+                };
+
+                insn.encode(&mut buf).map_err(|e| e.to_string())?;
+                self.state.push(CompileState::Compiled {
+                    offset: self.cursor,
+                    lexi: lexi.to_owned(),
+                    instruction: insn.to_owned(),
+                    buf: buf.clone(),
+                });
+            }
+            Mnemonic::Inc | Mnemonic::Dec | Mnemonic::Not | Mnemonic::Neg => {
                 match &op[0] {
                     Expr::RegisterOp(reg) => {
                         insn.set_op0_reg(*reg);
@@ -335,6 +1259,8 @@ impl Builder {
                         match mnemonic {
                             Mnemonic::Inc => insn.set_opcode(OpCode::IncR),
                             Mnemonic::Dec => insn.set_opcode(OpCode::DecR),
+                            Mnemonic::Not => insn.set_opcode(OpCode::NotR),
+                            Mnemonic::Neg => insn.set_opcode(OpCode::NegR),
                             _ => unreachable!(),
                         }
                     }
@@ -352,6 +1278,14 @@ impl Builder {
                             index_reg: *index_reg,
                             base_reg: *base_reg,
                         });
+
+                        match mnemonic {
+                            Mnemonic::Inc => insn.set_opcode(OpCode::IncRM),
+                            Mnemonic::Dec => insn.set_opcode(OpCode::DecRM),
+                            Mnemonic::Not => insn.set_opcode(OpCode::NotRM),
+                            Mnemonic::Neg => insn.set_opcode(OpCode::NegRM),
+                            _ => unreachable!(),
+                        }
                     }
                     Expr::Immediate(_) => {
                         return Err("Unexpected immediate at first operand".into())
@@ -367,51 +1301,41 @@ impl Builder {
                     buf: buf.clone(),
                 });
             }
-            Mnemonic::Test | Mnemonic::Cmp => {
+            Mnemonic::Jmp | Mnemonic::Call if !matches!(op[0], Expr::LabelRef(_)) => {
                 match &op[0] {
                     Expr::RegisterOp(reg) => {
                         insn.set_op0_reg(*reg);
+
+                        match mnemonic {
+                            Mnemonic::Jmp => insn.set_opcode(OpCode::JmpR),
+                            Mnemonic::Call => insn.set_opcode(OpCode::CallR),
+                            _ => unreachable!(),
+                        }
                     }
                     Expr::MemoryOp {
-                        size: _,
-                        displacement: _,
-                        scale: _,
-                        index_reg: _,
-                        base_reg: _,
-                    } => todo!(),
-                    x => return Err(format!("Unexpected operand: {x:?}")),
-                };
-                match &op[1] {
-                    Expr::RegisterOp(reg) => {
-                        insn.set_op1_reg(*reg);
-                    }
-                    Expr::MemoryOp {
-                        size: _,
-                        displacement: _,
-                        scale: _,
-                        index_reg: _,
-                        base_reg: _,
-                    } => todo!(),
-                    Expr::Immediate(imm) => {
-                        insn.set_immediate(*imm);
+                        size,
+                        displacement,
+                        scale,
+                        index_reg,
+                        base_reg,
+                    } => {
+                        insn.set_op0_mem(Operand::Memory {
+                            size: *size,
+                            displacement: *displacement,
+                            scale: *scale,
+                            index_reg: *index_reg,
+                            base_reg: *base_reg,
+                        });
+
+                        match mnemonic {
+                            Mnemonic::Jmp => insn.set_opcode(OpCode::JmpRM),
+                            Mnemonic::Call => insn.set_opcode(OpCode::CallRM),
+                            _ => unreachable!(),
+                        }
                     }
                     x => return Err(format!("Unexpected operand: {x:?}")),
                 };
 
-                match (&op[0], &op[1]) {
-                    (Expr::RegisterOp(_), Expr::RegisterOp(_)) => match mnemonic {
-                        Mnemonic::Test => insn.set_opcode(OpCode::TestRR),
-                        Mnemonic::Cmp => insn.set_opcode(OpCode::CmpRR),
-                        _ => unreachable!(),
-                    },
-                    (Expr::RegisterOp(_), Expr::Immediate(_)) => match mnemonic {
-                        Mnemonic::Test => insn.set_opcode(OpCode::TestRIMM),
-                        Mnemonic::Cmp => insn.set_opcode(OpCode::CmpRIMM),
-                        _ => unreachable!(),
-                    },
-                    _ => todo!(),
-                }
-
                 insn.encode(&mut buf).map_err(|e| e.to_string())?;
                 self.state.push(CompileState::Compiled {
                     offset: self.cursor,
@@ -426,7 +1350,18 @@ impl Builder {
             | Mnemonic::Jle
             | Mnemonic::Jg
             | Mnemonic::Jge
-            | Mnemonic::Jb => {
+            | Mnemonic::Jb
+            | Mnemonic::Jl
+            | Mnemonic::Ja
+            | Mnemonic::Jae
+            | Mnemonic::Jbe
+            | Mnemonic::Js
+            | Mnemonic::Jns
+            | Mnemonic::Jo
+            | Mnemonic::Jno
+            | Mnemonic::Jp
+            | Mnemonic::Jnp
+            | Mnemonic::Call => {
                 match mnemonic {
                     Mnemonic::Jmp => insn.set_opcode(OpCode::Jmp),
                     Mnemonic::Jz => insn.set_opcode(OpCode::Jz),
@@ -434,7 +1369,18 @@ impl Builder {
                     Mnemonic::Jle => insn.set_opcode(OpCode::Jle),
                     Mnemonic::Jg => insn.set_opcode(OpCode::Jg),
                     Mnemonic::Jge => insn.set_opcode(OpCode::Jge),
-                    Mnemonic::Jb => insn.set_opcode(OpCode::Jge),
+                    Mnemonic::Jb => insn.set_opcode(OpCode::Jb),
+                    Mnemonic::Jl => insn.set_opcode(OpCode::Jl),
+                    Mnemonic::Ja => insn.set_opcode(OpCode::Ja),
+                    Mnemonic::Jae => insn.set_opcode(OpCode::Jae),
+                    Mnemonic::Jbe => insn.set_opcode(OpCode::Jbe),
+                    Mnemonic::Js => insn.set_opcode(OpCode::Js),
+                    Mnemonic::Jns => insn.set_opcode(OpCode::Jns),
+                    Mnemonic::Jo => insn.set_opcode(OpCode::Jo),
+                    Mnemonic::Jno => insn.set_opcode(OpCode::Jno),
+                    Mnemonic::Jp => insn.set_opcode(OpCode::Jp),
+                    Mnemonic::Jnp => insn.set_opcode(OpCode::Jnp),
+                    Mnemonic::Call => insn.set_opcode(OpCode::Call),
                     _ => unreachable!(),
                 }
 
@@ -456,6 +1402,32 @@ impl Builder {
                     instruction: insn.to_owned(),
                 });
             }
+            Mnemonic::Push | Mnemonic::Pop => {
+                match (mnemonic, &op[0]) {
+                    (_, Expr::RegisterOp(reg)) => {
+                        insn.set_op0_reg(*reg);
+
+                        match mnemonic {
+                            Mnemonic::Push => insn.set_opcode(OpCode::PushR),
+                            Mnemonic::Pop => insn.set_opcode(OpCode::PopR),
+                            _ => unreachable!(),
+                        }
+                    }
+                    (Mnemonic::Push, Expr::Immediate(imm)) => {
+                        insn.set_op0_immediate(*imm);
+                        insn.set_opcode(OpCode::PushIMM);
+                    }
+                    (_, x) => return Err(format!("Unexpected operand: {x:?}")),
+                };
+
+                insn.encode(&mut buf).map_err(|e| e.to_string())?;
+                self.state.push(CompileState::Compiled {
+                    offset: self.cursor,
+                    lexi: lexi.to_owned(),
+                    instruction: insn.to_owned(),
+                    buf: buf.clone(),
+                });
+            }
             Mnemonic::Db | Mnemonic::Dw | Mnemonic::Dd | Mnemonic::Dq => {
                 match &op[0] {
                     Expr::Immediate(imm) => match mnemonic {
@@ -501,6 +1473,25 @@ impl Builder {
             }
         };
 
+        if let Some((op, label)) = relative {
+            match self.state.pop() {
+                Some(CompileState::Compiled {
+                    offset,
+                    lexi,
+                    instruction,
+                    buf: _,
+                }) => self.state.push(CompileState::UnresolvedRelative {
+                    offset,
+                    label,
+                    op,
+                    lexi,
+                    instruction,
+                }),
+                // Another label is pending, e.g. `offsetof`
+                _ => return Err(format!("Unsupported operands for {mnemonic}")),
+            }
+        }
+
         assert_ne!(buf.len(), 0);
         self.cursor += buf.len();
 
@@ -521,7 +1512,7 @@ pub fn build_bytecode_s<I: AsRef<str>>(
 
 /// Compile single line of source
 pub fn build_bytecode<I: AsRef<str>>(input: I, builder: &mut Builder) -> crate::error::Result<()> {
-    let mut parser = Parser::new(input.as_ref());
+    let mut parser = Parser::with_mnemonics(input.as_ref(), &builder.mnemonics);
 
     match parser.parse_label() {
         Ok(Some(label)) => {
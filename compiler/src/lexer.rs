@@ -15,6 +15,9 @@ pub enum Token {
     OffsetOf,
     /// Indicates an integer or hexdecimal number literal
     Number(u64),
+    /// Indicates a decimal floating-point number literal (e.g., `1.5`,
+    /// `2.0e-3`)
+    Float(f64),
     /// Indicates a comma (`,`)
     Comma,
     /// Indicates a comment anchor (`;`)
@@ -114,8 +117,11 @@ impl<'a> Lexer<'a> {
                     };
 
                     if ch == 'h' || !ch.is_ascii_hexdigit() {
+                        // Consume the suffix so that it is not left as an identifier
                         if ch == 'h' {
                             is_hex = true;
+                            chars.next();
+                            pos += 1;
                         }
                         break;
                     }
@@ -124,6 +130,73 @@ impl<'a> Lexer<'a> {
                     pos += 1;
                 }
 
+                // 1e3 and 2e-5 have their exponent taken for hex digits above
+                let exponent = src[start..pos]
+                    .find(['e', 'E'])
+                    .map(|i| start + i)
+                    .filter(|&e| {
+                        src[start..e].bytes().all(|b| b.is_ascii_digit())
+                            && src[e + 1..pos].bytes().all(|b| b.is_ascii_digit())
+                    });
+
+                if let (false, Some(e)) = (is_hex, exponent) {
+                    if e + 1 == pos && matches!(chars.peek(), Some('+' | '-')) {
+                        chars.next();
+                        pos += 1;
+
+                        while chars.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+                            chars.next();
+                            pos += 1;
+                        }
+                    }
+
+                    self.pos = pos;
+                    return Ok(Token::Float(
+                        src[start..pos]
+                            .parse()
+                            .map_err(|e| format!("{e}: {}", &src[start..pos]))?,
+                    ));
+                }
+
+                // 1.5, 2.0e-3
+                if !is_hex && chars.peek() == Some(&'.') {
+                    chars.next();
+                    pos += 1;
+
+                    let mut in_exponent = false;
+                    loop {
+                        let ch = match chars.peek() {
+                            Some(ch) => *ch,
+                            None => return Ok(Token::EOF),
+                        };
+
+                        if !in_exponent && (ch == 'e' || ch == 'E') {
+                            in_exponent = true;
+                            chars.next();
+                            pos += 1;
+
+                            if matches!(chars.peek(), Some('+' | '-')) {
+                                chars.next();
+                                pos += 1;
+                            }
+                            continue;
+                        }
+                        if !ch.is_ascii_digit() {
+                            break;
+                        }
+
+                        chars.next();
+                        pos += 1;
+                    }
+
+                    self.pos = pos;
+                    return Ok(Token::Float(
+                        src[start..pos]
+                            .parse()
+                            .map_err(|e| format!("{e}: {}", &src[start..pos]))?,
+                    ));
+                }
+
                 Ok(Token::Number(
                     if is_hex {
                         u64::from_str_radix(&src[start..pos].trim_end_matches("h"), 16)
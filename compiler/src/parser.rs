@@ -1,9 +1,12 @@
 use vm::{
-    emulator::Register,
+    emulator::{FloatRegister, Register, VectorRegister},
     isa::{Mnemonic, OperandSize},
 };
 
-use crate::lexer::{Lexer, Token};
+use crate::{
+    builder::CustomMnemonic,
+    lexer::{Lexer, Token},
+};
 
 /// Represents an expression
 #[derive(Debug, Clone)]
@@ -13,13 +16,25 @@ pub enum Expr {
     /// Indicates a memory operand
     MemoryOp {
         size: OperandSize,
-        displacement: u64,
+        displacement: i64,
         scale: u8,
         index_reg: Option<Register>,
         base_reg: Option<Register>,
     },
+    /// Indicates a memory operand relative to the next instruction
+    RelMemoryOp {
+        size: OperandSize,
+        displacement: i64,
+        label: String,
+    },
     /// Indicates a 64-bit immediate
     Immediate(u64),
+    /// Indicates a floating-point register operand
+    FloatRegisterOp(FloatRegister),
+    /// Indicates a double precision immediate
+    FloatImmediate(f64),
+    /// Indicates a vector register operand
+    VectorRegisterOp(VectorRegister),
     /// Indicates a label definition
     Label(String),
     /// Indicates a label reference (in an operand)
@@ -37,19 +52,28 @@ pub struct LexInstruction {
 
 /// Represents a parser
 #[derive(Debug)]
-pub struct Parser {
+pub struct Parser<'a> {
     /// Tokens analyzed by [`Lexer`]
     tokens: Vec<Token>,
+    /// User-defined mnemonics recognized in addition to the built-in ones
+    mnemonics: &'a [CustomMnemonic],
 }
 
-impl<'a> Parser {
+impl<'a> Parser<'a> {
     /// Make an new instance of [`Parser`] with the input source
     #[must_use]
-    pub fn new(input: &'a str) -> Self {
+    pub fn new(input: &str) -> Self {
+        Self::with_mnemonics(input, &[])
+    }
+
+    /// Make an new instance of [`Parser`] with the input source, recognizing
+    /// the user-defined `mnemonics` as well
+    #[must_use]
+    pub fn with_mnemonics(input: &str, mnemonics: &'a [CustomMnemonic]) -> Self {
         let mut lexer = Lexer::new(input);
         let tokens = lexer.by_ref().collect();
 
-        Parser { tokens }
+        Parser { tokens, mnemonics }
     }
 
     /// Parses a label definition
@@ -60,6 +84,10 @@ impl<'a> Parser {
         }
 
         match &self.tokens[0] {
+            // `jmp sp` could not reach a label named `sp`
+            Token::Label(id) if Register::from_str(id.trim_end_matches(':')).is_some() => {
+                Err(format!("Label named after a register: {id}"))
+            }
             Token::Label(id) => return Ok(Some(id.to_owned())),
             _ => return Ok(None),
         }
@@ -72,11 +100,26 @@ impl<'a> Parser {
             return Ok(None);
         }
 
+        // Fold a prefix into the mnemonic, e.g. `rep movsb`
+        if let [Token::Ident(prefix), Token::Ident(id), ..] = &self.tokens[..] {
+            if matches!(&*prefix.to_lowercase(), "rep" | "repe" | "repz") {
+                self.tokens[0] = Token::Ident(format!("{prefix} {id}"));
+                self.tokens.remove(1);
+            }
+        }
+
         let mut operands = Vec::new();
         let mnemonic = match &self.tokens[0] {
             Token::Ident(id) => match Mnemonic::from_str(id) {
                 Some(id) => Ok(id),
-                None => Err(format!("Unrecognized mnemonic: {id}")),
+                None => match self
+                    .mnemonics
+                    .iter()
+                    .find(|x| x.name.eq_ignore_ascii_case(id))
+                {
+                    Some(custom) => Ok(Mnemonic::Custom(custom.opcode)),
+                    None => Err(format!("Unrecognized mnemonic: {id}")),
+                },
             },
             _ => Err("Expected an identifier".into()),
         }?
@@ -92,19 +135,21 @@ impl<'a> Parser {
                 Token::Ident(id) => Ok(Expr::LabelRef(id.to_owned())),
                 _ => return Err("Expected an identifier after `offsetof`".into()),
             },
-            Token::SizeClass(_) => {
-                match &self.tokens[i + 1] {
-                    Token::LParen => {}
-                    _ => return Err("Expected `[` after size class".into()),
+            Token::SizeClass(_) | Token::LParen => {
+                // mov r1, byte [r0]
+                let (size, i) = match &self.tokens[i] {
+                    Token::SizeClass(id) => (*id, i + 1),
+                    // lea r1, [r0]
+                    _ if mnemonic == Mnemonic::Lea => (OperandSize::QWord, i),
+                    _ => return Err("Expected size class before `[`".into()),
                 };
 
-                // mov r1, byte [r0]
-                let size = match &self.tokens[i] {
-                    Token::SizeClass(id) => Ok::<OperandSize, String>(*id),
-                    _ => Err("Expected size class".into()),
-                }?;
+                match self.tokens.get(i) {
+                    Some(Token::LParen) => {}
+                    _ => return Err("Expected `[` after size class".into()),
+                };
 
-                // Collect to `size [LParen, .., RParen]`
+                // Collect to `[LParen, .., RParen]`
                 let tok = self.tokens[i..]
                     .iter()
                     .take_while(|x| !matches!(x, Token::RParen))
@@ -116,88 +161,140 @@ impl<'a> Parser {
                     )
                     .collect::<Vec<_>>();
 
-                if tok.is_empty() {
+                if !matches!(tok.last(), Some(Token::RParen)) {
                     return Err(format!("Expected `]`"));
                 }
-                if tok.len() < 4 {
+                if tok.len() < 3 {
                     return Err(format!("Expected an identifier after `[`"));
                 }
 
+                // mov r1, qword [rel table]
+                let rel = match (&tok[1], &tok[2]) {
+                    (Token::Ident(id), Token::Ident(label)) if id.eq_ignore_ascii_case("rel") => {
+                        Some(label.to_owned())
+                    }
+                    _ => None,
+                };
+
                 // mov r1, [r0]
-                let base_reg = match &tok[2] {
+                let base_reg = match &tok[1] {
+                    _ if rel.is_some() => Ok(None),
                     Token::Ident(id) => match Register::from_str(id) {
                         Some(id) => Ok(Some(id)),
                         _ => Err(format!("Unrecognized register: {id}")),
                     },
                     _ => Err("Expected identifier".into()),
                 }?;
-                // mov r3, [r0+r1]
-                let index_reg = if tok.len() > 5 {
-                    match &tok[4] {
-                        Token::Ident(id) => match Register::from_str(id) {
-                            Some(id) => Ok(Some(id)),
-                            _ => Err(format!("Unrecognized register: {id}")),
-                        },
-                        _ => Err(format!("Expected register: {:?}", &tok[4])),
-                    }?
-                } else {
-                    None
-                };
-                // mov r3, [r0+r1*2]
-                let scale = if tok.len() > 7 {
-                    match &tok[6] {
-                        Token::Number(num) => match num {
-                            1 | 2 | 4 | 8 => Ok(*num as u8),
-                            _ => Err(format!("Unrecognized scale: {num}")),
-                        },
-                        _ => Err(format!(
-                            "Expected scale number 1 | 2 | 4 | 8: {:?}",
-                            &tok[6]
-                        )),
-                    }?
-                } else {
-                    1
-                };
+
+                let mut index_reg = None;
+                let mut scale = 1;
+                let mut displacement = 0i64;
+
+                let mut j = if rel.is_some() { 3 } else { 2 };
+                while j < tok.len() - 1 {
+                    let negative = match &tok[j] {
+                        Token::Op('+') => false,
+                        Token::Op('-') => true,
+                        x => return Err(format!("Expected `+` or `-`: {x:?}")),
+                    };
+                    match &tok[j + 1] {
+                        // mov r3, [r0-8]
+                        Token::Number(num) => {
+                            let num = if negative {
+                                -(*num as i128)
+                            } else {
+                                *num as i128
+                            };
+                            displacement = i64::try_from(displacement as i128 + num)
+                                .map_err(|_| format!("Displacement overflows: {num}"))?;
+                            j += 2;
+                        }
+                        // mov r3, [r0+r1]
+                        Token::Ident(id) if !negative && index_reg.is_none() && rel.is_none() => {
+                            index_reg = match Register::from_str(id) {
+                                Some(id) => Some(id),
+                                _ => return Err(format!("Unrecognized register: {id}")),
+                            };
+                            j += 2;
+
+                            // mov r3, [r0+r1*2]
+                            if matches!(tok[j], Token::Op('*')) {
+                                scale = match tok.get(j + 1) {
+                                    Some(Token::Number(num)) => match num {
+                                        1 | 2 | 4 | 8 => Ok(*num as u8),
+                                        _ => Err(format!("Unrecognized scale: {num}")),
+                                    },
+                                    x => Err(format!("Expected scale number 1 | 2 | 4 | 8: {x:?}")),
+                                }?;
+                                j += 2;
+                            }
+                        }
+                        x => return Err(format!("Expected register or displacement: {x:?}")),
+                    }
+                }
+
+                if let Some(label) = rel {
+                    return Ok(Expr::RelMemoryOp {
+                        size,
+                        displacement,
+                        label,
+                    });
+                }
 
                 Ok(Expr::MemoryOp {
                     size,
-                    displacement: 0,
+                    displacement,
                     scale,
                     index_reg,
                     base_reg,
                 })
             }
-            Token::Ident(id) => match mnemonic {
-                Mnemonic::Jmp => Ok(Expr::LabelRef(id.to_owned())),
-                Mnemonic::Jz => Ok(Expr::LabelRef(id.to_owned())),
-                Mnemonic::Jnz => Ok(Expr::LabelRef(id.to_owned())),
-                Mnemonic::Jle => Ok(Expr::LabelRef(id.to_owned())),
-                Mnemonic::Jg => Ok(Expr::LabelRef(id.to_owned())),
-                Mnemonic::Jge => Ok(Expr::LabelRef(id.to_owned())),
-                Mnemonic::Jb => Ok(Expr::LabelRef(id.to_owned())),
-                _ => match Register::from_str(id) {
-                    Some(id) => Ok(Expr::RegisterOp(id)),
-                    _ => Err(format!("Unrecognized register: {id}")),
-                },
-            },
+            // jmp mylabel, whereas `jmp r0` falls through to a register
+            Token::Ident(id) if mnemonic.is_branch() && Register::from_str(id).is_none() => {
+                Ok(Expr::LabelRef(id.to_owned()))
+            }
+            Token::Ident(id) => {
+                if let Some(id) = Register::from_str(id) {
+                    Ok(Expr::RegisterOp(id))
+                } else if let Some(id) = FloatRegister::from_str(id) {
+                    Ok(Expr::FloatRegisterOp(id))
+                } else if let Some(id) = VectorRegister::from_str(id) {
+                    Ok(Expr::VectorRegisterOp(id))
+                } else {
+                    Err(format!("Unrecognized register: {id}"))
+                }
+            }
             Token::Label(id) => Ok(Expr::Label(id.to_owned())),
             Token::Number(num) => Ok(Expr::Immediate(*num)),
+            Token::Float(num) => Ok(Expr::FloatImmediate(*num)),
+            // fmov f0, -1.5 or mov r0, -1
+            Token::Op('-') => match self.tokens.get(i + 1) {
+                Some(Token::Float(num)) => Ok(Expr::FloatImmediate(-num)),
+                Some(Token::Number(num)) if *num > 1 << 63 => {
+                    Err(format!("Negative immediate out of range: -{num}"))
+                }
+                Some(Token::Number(num)) => Ok(Expr::Immediate(num.wrapping_neg())),
+                _ => Err("Expected a number after `-`".into()),
+            },
             x => Err(format!("Unexpected token: {x:?}")),
         };
 
         let op0 = parse_op(1)?;
         operands.push(op0);
 
-        if let Some(pos) = self.tokens[1..]
+        // add r0, r1 or add r0, r1, r2
+        for (pos, _) in self
+            .tokens
             .iter()
-            .position(|t| matches!(t, Token::Comma))
+            .enumerate()
+            .filter(|(_, t)| matches!(t, Token::Comma))
         {
-            if pos + 2 > self.tokens.len() {
+            if pos + 1 >= self.tokens.len() {
                 return Err("Expected an operand after `,`".into());
             }
 
-            let op1 = parse_op(pos + 2)?;
-            operands.push(op1);
+            let op = parse_op(pos + 1)?;
+            operands.push(op);
         }
 
         Ok(Some(LexInstruction { mnemonic, operands }))
@@ -0,0 +1,149 @@
+use vm::emulator::Register;
+
+use super::build;
+
+fn test_count(mnemonic: &str, input: u64, output: u64, cf: u64, zf: u64) {
+    let s = format!(
+        "
+mov r1, {input}
+{mnemonic} r0, r1
+exit
+"
+    );
+    let mut emulator = build(&s);
+    emulator.execute().unwrap();
+    assert_eq!(
+        emulator.regs.read(Register::R0),
+        output,
+        "{mnemonic} {input:#x}"
+    );
+    let rf = emulator.regs.read_rf();
+    assert_eq!(rf.read_cf(), cf, "{mnemonic} {input:#x}: CF");
+    assert_eq!(rf.read_zf(), zf, "{mnemonic} {input:#x}: ZF");
+    assert_eq!(rf.read_sf(), 0);
+    assert_eq!(rf.read_of(), 0);
+}
+
+#[test]
+fn popcnt() {
+    test_count("popcnt", 0, 0, 0, 1);
+    test_count("popcnt", 0xF0F0, 8, 0, 0);
+    test_count("popcnt", u64::MAX, 64, 0, 0);
+}
+
+#[test]
+fn lzcnt() {
+    test_count("lzcnt", 0, 64, 1, 0);
+    test_count("lzcnt", 1, 63, 0, 0);
+    test_count("lzcnt", 0x8000000000000000, 0, 0, 1);
+}
+
+#[test]
+fn tzcnt() {
+    test_count("tzcnt", 0, 64, 1, 0);
+    test_count("tzcnt", 0x100, 8, 0, 0);
+    test_count("tzcnt", 1, 0, 0, 1);
+}
+
+#[test]
+fn count_rm() {
+    // The count is computed at the width of the memory operand
+    const S: &str = "
+mov r6, offsetof data
+popcnt r0, word [r6]
+lzcnt r1, dword [r6]
+tzcnt r2, byte [r6]
+lzcnt r3, byte [r6]
+exit
+
+data:
+    dq 0FF0010h
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 1);
+    assert_eq!(emulator.regs.read(Register::R1), 8);
+    assert_eq!(emulator.regs.read(Register::R2), 4);
+    assert_eq!(emulator.regs.read(Register::R3), 3);
+}
+
+#[test]
+fn bswap() {
+    const S: &str = "
+mov r0, 0102030405060708h
+bswap r0
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 0x0807060504030201);
+}
+
+#[test]
+fn bt() {
+    const S: &str = "
+mov r0, 0F0h
+mov r1, 4
+mov r2, 0
+mov r3, 0
+bt r0, r1
+setc r2
+bt r0, 67
+setc r3
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 0xF0);
+    assert_eq!(emulator.regs.read(Register::R2), 1);
+    assert_eq!(emulator.regs.read(Register::R3), 0);
+}
+
+#[test]
+fn bts_btr_btc() {
+    const S: &str = "
+mov r0, 0
+mov r1, 63
+bts r0, r1
+setc r2
+bts r0, 0
+btr r0, 63
+setc r3
+btc r0, 1
+btc r0, 0
+setc r4
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 2);
+    assert_eq!(emulator.regs.read(Register::R2), 0);
+    assert_eq!(emulator.regs.read(Register::R3), 1);
+    assert_eq!(emulator.regs.read(Register::R4), 1);
+}
+
+#[test]
+fn bitset() {
+    // Offsets wrap around the width of the memory operand
+    const S: &str = "
+mov r6, offsetof set
+mov r1, 9
+bts word [r6], r1
+bts byte [r6], 3
+btc byte [r6], 11
+setc r2
+btr word [r6], 1
+bt qword [r6], r1
+setc r3
+exit
+
+set:
+    dq 2
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    let set = emulator.regs.read(Register::R6) as usize;
+    assert_eq!(emulator.dram.read_u64le(set).unwrap(), 0x200);
+    assert_eq!(emulator.regs.read(Register::R2), 1);
+    assert_eq!(emulator.regs.read(Register::R3), 1);
+}
@@ -0,0 +1,164 @@
+use vm::{emulator::Register, exception::Exception};
+
+use super::build;
+
+#[test]
+fn movsb() {
+    const S: &str = "
+mov r0, offsetof dst
+mov r1, offsetof src
+mov r2, 12
+rep movsb
+exit
+
+src:
+    dq 0706050403020100h
+    dq 0F0E0D0C0B0A0908h
+dst:
+    dq 0
+    dq 0
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    let dst = emulator.regs.read(Register::R0) as usize - 12;
+    assert_eq!(emulator.dram.read_u64le(dst).unwrap(), 0x0706050403020100);
+    assert_eq!(emulator.dram.read_u64le(dst + 8).unwrap(), 0x0B0A0908);
+    assert_eq!(emulator.regs.read(Register::R1) as usize, dst - 4);
+    assert_eq!(emulator.regs.read(Register::R2), 0);
+}
+
+#[test]
+fn movsb_overlap() {
+    const S: &str = "
+mov r0, offsetof buf
+mov r1, r0
+inc r0
+mov r2, 4
+rep movsb
+exit
+
+buf:
+    dq 0000000504030201h
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    let buf = emulator.regs.read(Register::R1) as usize - 4;
+    assert_eq!(emulator.dram.read_u64le(buf).unwrap(), 0x0000000403020101);
+}
+
+#[test]
+fn stosb() {
+    const S: &str = "
+mov r0, offsetof buf
+mov r1, 1AAh
+mov r2, 5
+cmp r2, 5
+rep stosb
+exit
+
+buf:
+    dq 0
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    let buf = emulator.regs.read(Register::R0) as usize - 5;
+    assert_eq!(emulator.dram.read_u64le(buf).unwrap(), 0xAAAAAAAAAA);
+    assert_eq!(emulator.regs.read(Register::R1), 0x1AA);
+    assert_eq!(emulator.regs.read(Register::R2), 0);
+    // Flags are left untouched
+    assert_eq!(emulator.regs.read_rf().read_zf(), 1);
+}
+
+fn test_cmpsb(lhs: u64, rhs: u64, count: u64, remaining: u64, zf: u64, cf: u64) {
+    let s = format!(
+        "
+mov r6, offsetof a
+mov r7, offsetof b
+mov r0, r6
+mov r1, r7
+mov r2, {count}
+repe cmpsb
+exit
+
+a:
+    dq {lhs}
+b:
+    dq {rhs}
+"
+    );
+    let mut emulator = build(&s);
+    emulator.execute().unwrap();
+    let compared = count - remaining;
+    assert_eq!(
+        emulator.regs.read(Register::R0),
+        emulator.regs.read(Register::R6) + compared
+    );
+    assert_eq!(
+        emulator.regs.read(Register::R1),
+        emulator.regs.read(Register::R7) + compared
+    );
+    assert_eq!(emulator.regs.read(Register::R2), remaining);
+    let rf = emulator.regs.read_rf();
+    assert_eq!(rf.read_zf(), zf, "{lhs:#x} {rhs:#x} {count}: ZF");
+    assert_eq!(rf.read_cf(), cf, "{lhs:#x} {rhs:#x} {count}: CF");
+}
+
+#[test]
+fn cmpsb() {
+    // Equal
+    test_cmpsb(0x0102030405060708, 0x0102030405060708, 8, 0, 1, 0);
+    // Differs at the third byte, below
+    test_cmpsb(0x0102030405010708, 0x0102030405060708, 8, 5, 0, 1);
+    // Differs at the third byte, above
+    test_cmpsb(0x0102030405FF0708, 0x0102030405060708, 8, 5, 0, 0);
+    // The difference lies beyond the count
+    test_cmpsb(0xFF02030405060708, 0x0102030405060708, 7, 0, 1, 0);
+}
+
+#[test]
+fn zero_count() {
+    const S: &str = "
+mov r0, 0FFFFFFFFFFFFFFFFh
+mov r1, 0FFFFFFFFFFFFFFFFh
+mov r2, 0
+rep movsb
+rep stosb
+cmp r2, 1
+rep cmpsb
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), u64::MAX);
+    // Flags of the `cmp` are left untouched
+    assert_eq!(emulator.regs.read_rf().read_cf(), 1);
+}
+
+#[test]
+fn out_of_bounds() {
+    for insn in ["rep movsb", "rep stosb", "rep cmpsb"] {
+        let s = format!(
+            "
+mov r0, offsetof buf
+mov r1, r0
+mov r2, 100000000h
+{insn}
+exit
+
+buf:
+    dq 0
+"
+        );
+        let mut emulator = build(&s);
+        assert!(
+            matches!(
+                emulator.execute().map_err(|e| e.kind),
+                Err(Exception::AccessViolation(_))
+            ),
+            "{insn}"
+        );
+        // Nothing has been written
+        let buf = emulator.regs.read(Register::R0) as usize;
+        assert_eq!(emulator.dram.read_u64le(buf).unwrap(), 0);
+    }
+}
@@ -0,0 +1,119 @@
+use vm::{
+    emulator::{Emulator, Register},
+    exception::Exception,
+};
+
+use super::run;
+use crate::builder::{build_bytecode_s, Builder};
+
+#[test]
+fn call_ret() {
+    const S: &str = "
+mov r0, 3
+call add_four
+call add_four
+exit
+
+add_four:
+    add r0, 4
+    ret
+";
+    let mut builder = Builder::new();
+    build_bytecode_s(S, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let bytecode_len = dump.len();
+    assert_ne!(bytecode_len, 0);
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 11u64);
+    assert_eq!(emulator.regs.read(Register::SP), emulator.stack_base);
+    assert_ne!(emulator.ip() as usize, 0);
+}
+
+#[test]
+fn call_next() {
+    // The branch offset of a call to the next instruction is 0
+    const S: &str = "
+call next
+next:
+    pop r0
+    exit
+";
+    let emulator = run(S);
+    // The return address is the end of the 9-byte call
+    assert_eq!(emulator.regs.read(Register::R0), 9);
+}
+
+fn test_factorial(input: u64, output: u64) {
+    let s = format!(
+        "
+mov r0, {input}
+call factorial
+exit
+
+; input = r0
+; output = r1
+factorial:
+    cmp r0, 1
+    jle factorial_base
+    push r0
+    dec r0
+    call factorial
+    pop r0
+    imul r1, r0
+    ret
+factorial_base:
+    mov r1, 1
+    ret
+"
+    );
+    let mut builder = Builder::new();
+    build_bytecode_s(s, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R1), output);
+    assert_eq!(emulator.regs.read(Register::SP), emulator.stack_base);
+}
+
+#[test]
+fn call_recursive() {
+    test_factorial(0, 1);
+    test_factorial(1, 1);
+    test_factorial(5, 120);
+    test_factorial(10, 3628800);
+    test_factorial(20, 2432902008176640000);
+}
+
+#[test]
+fn call_overflow() {
+    const S: &str = "
+recurse:
+    call recurse
+";
+    let mut builder = Builder::new();
+    build_bytecode_s(S, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let mut emulator = Emulator::with_stack(dump, 64);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::StackOverflow)
+    ));
+    assert_eq!(emulator.cycle, 8);
+}
+
+#[test]
+fn ret_underflow() {
+    let mut builder = Builder::new();
+    build_bytecode_s("ret\n", &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let mut emulator = Emulator::with_bytecode(dump);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::StackUnderflow)
+    ));
+}
@@ -0,0 +1,154 @@
+use vm::emulator::Register;
+
+use super::build;
+use crate::builder::{build_bytecode_s, Builder};
+
+/// Evaluates `cc` for `cmp lhs, rhs` on the host.
+fn expected(cc: &str, lhs: u64, rhs: u64) -> bool {
+    let value = lhs.wrapping_sub(rhs);
+    let of = (lhs as i64).overflowing_sub(rhs as i64).1;
+    let pf = (value as u8).count_ones() % 2 == 0;
+    match cc {
+        "z" => lhs == rhs,
+        "nz" => lhs != rhs,
+        "le" => (lhs as i64) <= (rhs as i64),
+        "g" => (lhs as i64) > (rhs as i64),
+        "ge" => (lhs as i64) >= (rhs as i64),
+        "b" => lhs < rhs,
+        "l" => (lhs as i64) < (rhs as i64),
+        "a" => lhs > rhs,
+        "ae" => lhs >= rhs,
+        "be" => lhs <= rhs,
+        "s" => (value as i64) < 0,
+        "ns" => (value as i64) >= 0,
+        "o" => of,
+        "no" => !of,
+        "p" => pf,
+        "np" => !pf,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn conditions() {
+    const CCS: [&str; 16] = [
+        "z", "nz", "le", "g", "ge", "b", "l", "a", "ae", "be", "s", "ns", "o", "no", "p", "np",
+    ];
+    const PAIRS: [(u64, u64); 7] = [
+        (0, 0),
+        (1, 2),
+        (2, 1),
+        (3, 0xFFFFFFFFFFFFFFFF),
+        (0xFFFFFFFFFFFFFFFF, 3),
+        (0x8000000000000000, 1),
+        (0x7FFFFFFFFFFFFFFF, 0xFFFFFFFFFFFFFFFF),
+    ];
+
+    for cc in CCS {
+        for (lhs, rhs) in PAIRS {
+            let s = format!(
+                "
+mov r1, {lhs}
+mov r2, {rhs}
+mov r3, 1111h
+mov r4, 2222h
+mov r0, 0FFh
+cmp r1, r2
+set{cc} r0
+cmov{cc} r4, r3
+exit
+"
+            );
+            let mut emulator = build(&s);
+            emulator.execute().unwrap();
+            let taken = expected(cc, lhs, rhs);
+            assert_eq!(
+                emulator.regs.read(Register::R0),
+                taken as u64,
+                "set{cc} {lhs:#x}, {rhs:#x}"
+            );
+            assert_eq!(
+                emulator.regs.read(Register::R4),
+                if taken { 0x1111 } else { 0x2222 },
+                "cmov{cc} {lhs:#x}, {rhs:#x}"
+            );
+        }
+    }
+}
+
+#[test]
+fn aliases() {
+    const S: &str = "
+mov r1, 5
+mov r2, 5
+cmp r1, r2
+sete r0
+setc r3
+setnae r4
+setpe r5
+mov r6, 7
+cmovne r6, r1
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 1);
+    assert_eq!(emulator.regs.read(Register::R3), 0);
+    assert_eq!(emulator.regs.read(Register::R4), 0);
+    assert_eq!(emulator.regs.read(Register::R5), 1);
+    assert_eq!(emulator.regs.read(Register::R6), 7);
+}
+
+#[test]
+fn preserves_flags() {
+    const S: &str = "
+mov r1, 1
+mov r2, 2
+cmp r1, r2
+setb r0
+cmovb r1, r2
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    let rf = emulator.regs.read_rf();
+    assert_eq!(rf.read_cf(), 1);
+    assert_eq!(rf.read_zf(), 0);
+    assert_eq!(emulator.regs.read(Register::R1), 2);
+}
+
+#[test]
+fn branchless() {
+    // min, max and abs without a single branch
+    const S: &str = "
+mov r1, 0FFFFFFFFFFFFFFF9h
+mov r2, 3
+mov r3, r1
+cmp r1, r2
+cmovg r3, r2
+mov r4, r1
+cmp r1, r2
+cmovl r4, r2
+mov r5, r1
+mov r6, 0
+sub r6, r1
+test r1, r1
+cmovs r5, r6
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R3), -7i64 as u64);
+    assert_eq!(emulator.regs.read(Register::R4), 3);
+    assert_eq!(emulator.regs.read(Register::R5), 7);
+}
+
+#[test]
+fn operands() {
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("sete [r0]", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("cmovz r0, 1", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("cmovz r0, [r1]", &mut builder).is_err());
+}
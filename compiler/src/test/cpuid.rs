@@ -0,0 +1,80 @@
+use vm::{
+    emulator::{Emulator, Register},
+    exception::Exception,
+    isa::{Extension, Extensions, ESCAPE, ISA_VERSION},
+};
+
+use super::build;
+use crate::builder::{build_bytecode_s, Builder};
+
+#[test]
+fn cpuid() {
+    let mut builder = Builder::new();
+    build_bytecode_s("cpuid\nexit", &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let bytecode = builder.dump().unwrap();
+    // cpuid is on the second page
+    assert_eq!(bytecode[..2], [ESCAPE, 0x00]);
+
+    let mut emulator = Emulator::with_bytecode(bytecode);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), ISA_VERSION);
+    assert_eq!(emulator.regs.read(Register::R1), Extensions::all().0);
+    assert_ne!(emulator.regs.read(Register::R1) & Extension::Fpu.bit(), 0);
+    assert_ne!(emulator.regs.read(Register::R1) & Extension::Simd.bit(), 0);
+    assert_ne!(
+        emulator.regs.read(Register::R1) & Extension::PortIo.bit(),
+        0
+    );
+}
+
+#[test]
+fn disabled() {
+    const S: &str = "
+cpuid
+add r2, 1
+fmov f0, 1.0
+exit
+";
+    let mut emulator = build(S);
+    emulator.extensions.remove(Extension::Fpu);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::IllegalInstruction)
+    ));
+    assert_eq!(
+        emulator.regs.read(Register::R1),
+        Extensions::all().0 & !Extension::Fpu.bit()
+    );
+    assert_eq!(emulator.regs.read(Register::R2), 1);
+
+    let mut emulator = build(S);
+    emulator.extensions.remove(Extension::Simd);
+    emulator.extensions.remove(Extension::PortIo);
+    emulator.execute().unwrap();
+    assert_eq!(
+        emulator.regs.read(Register::R1),
+        Extension::Fpu.bit() | Extension::Custom.bit()
+    );
+
+    let mut emulator = build("in r0, 60h\nexit");
+    emulator.extensions = Extensions::none();
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::IllegalInstruction)
+    ));
+
+    let mut emulator = build("pxor v0, v0\nexit");
+    emulator.extensions = Extensions::none();
+    emulator.extensions.insert(Extension::Simd);
+    emulator.execute().unwrap();
+}
+
+#[test]
+fn undefined() {
+    // Undefined opcodes of both pages
+    for bytecode in [vec![0xFE], vec![ESCAPE, 0xFF]] {
+        let mut emulator = Emulator::with_bytecode(bytecode);
+        assert!(emulator.execute().is_err());
+    }
+}
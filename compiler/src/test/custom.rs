@@ -0,0 +1,236 @@
+use vm::{
+    custom::OperandKind,
+    emulator::{Emulator, FloatRegister, Register},
+    exception::Exception,
+    isa::{Extension, Instruction, ESCAPE},
+};
+
+use crate::builder::{build_bytecode_s, Builder};
+
+/// Registers the mnemonics of the custom instructions of [`with_custom`]
+fn with_mnemonics() -> Builder {
+    let mut builder = Builder::new();
+    builder
+        .register_mnemonic("weight", 0, &[OperandKind::Register, OperandKind::Register])
+        .unwrap();
+    builder
+        .register_mnemonic("madd", 1, &[
+            OperandKind::Register,
+            OperandKind::Register,
+            OperandKind::Immediate,
+        ])
+        .unwrap();
+    builder
+        .register_mnemonic("incm", 2, &[OperandKind::Memory])
+        .unwrap();
+    builder
+        .register_mnemonic("fhalf", 3, &[
+            OperandKind::FloatRegister,
+            OperandKind::FloatRegister,
+        ])
+        .unwrap();
+    builder.register_mnemonic("fault", 4, &[]).unwrap();
+    builder
+}
+
+fn assemble(s: &str) -> Vec<u8> {
+    let mut builder = with_mnemonics();
+    build_bytecode_s(s, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    builder.dump().unwrap()
+}
+
+/// Makes an emulator with the custom instructions of [`with_mnemonics`]
+fn with_custom(bytecode: Vec<u8>) -> Emulator {
+    let mut emulator = Emulator::with_bytecode(bytecode);
+    emulator
+        .register_custom(
+            0,
+            &[OperandKind::Register, OperandKind::Register],
+            |emu: &mut Emulator, insn: &Instruction| {
+                let value = emu.regs.read(insn.op1_reg()).count_ones();
+                emu.regs.write(insn.op0_reg(), value as u64);
+                Ok(())
+            },
+        )
+        .unwrap();
+    emulator
+        .register_custom(
+            1,
+            &[
+                OperandKind::Register,
+                OperandKind::Register,
+                OperandKind::Immediate,
+            ],
+            |emu: &mut Emulator, insn: &Instruction| {
+                let value = emu
+                    .regs
+                    .read(insn.op1_reg())
+                    .wrapping_mul(insn.op2_immediate());
+                let dest = emu.regs.read(insn.op0_reg());
+                emu.regs.write(insn.op0_reg(), dest.wrapping_add(value));
+                Ok(())
+            },
+        )
+        .unwrap();
+    emulator
+        .register_custom(
+            2,
+            &[OperandKind::Memory],
+            |emu: &mut Emulator, insn: &Instruction| {
+                let value = emu.read_memop(insn, 0)?;
+                emu.write_memop(insn, 0, value + 1)
+            },
+        )
+        .unwrap();
+    emulator
+        .register_custom(
+            3,
+            &[OperandKind::FloatRegister, OperandKind::FloatRegister],
+            |emu: &mut Emulator, insn: &Instruction| {
+                let value = emu.fregs.read(insn.op1_freg());
+                emu.fregs.write(insn.op0_freg(), value / 2.0);
+                Ok(())
+            },
+        )
+        .unwrap();
+    emulator
+        .register_custom(4, &[], |_: &mut Emulator, _: &Instruction| {
+            Err(Exception::DivideByZero)
+        })
+        .unwrap();
+    emulator
+}
+
+#[test]
+fn custom() {
+    const S: &str = "
+    mov r1, 0F0Fh
+    weight r0, r1
+    mov r2, 1
+    mov r3, 7
+    madd r2, r3, 3
+    MADD r2, r2, 2
+    mov r4, offsetof counter
+    incm qword [r4]
+    incm byte [rel counter]
+    fmov f1, 5.0
+    fhalf f0, f1
+    exit
+
+counter:
+    dq 0FFh
+";
+    let bytecode = assemble(S);
+    let mut emulator = with_custom(bytecode);
+    emulator.execute().unwrap();
+
+    assert_eq!(emulator.regs.read(Register::R0), 8);
+    // (1 + 7 * 3) + 22 * 2
+    assert_eq!(emulator.regs.read(Register::R2), 66);
+    let counter = emulator.regs.read(Register::R4) as usize;
+    assert_eq!(emulator.dram.read_u64le(counter).unwrap(), 0x101);
+    assert_eq!(emulator.fregs.read(FloatRegister::F0), 2.5);
+}
+
+#[test]
+fn encoding() {
+    let bytecode = assemble("weight r0, r1");
+    assert_eq!(bytecode, [
+        ESCAPE,
+        0x01,
+        0x00,
+        Register::R0 as u8,
+        Register::R1 as u8
+    ]);
+}
+
+#[test]
+fn handler_error() {
+    let mut emulator = with_custom(assemble("fault\nexit"));
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::DivideByZero)
+    ));
+}
+
+#[test]
+fn unregistered() {
+    // Without a handler, or without the extension
+    let mut emulator = Emulator::with_bytecode(assemble("weight r0, r1\nexit"));
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::IllegalInstruction)
+    ));
+
+    let mut emulator = with_custom(vec![ESCAPE, 0x01, 0x05]);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::IllegalInstruction)
+    ));
+
+    let mut emulator = with_custom(assemble("weight r0, r1\nexit"));
+    emulator.extensions.remove(Extension::Custom);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::IllegalInstruction)
+    ));
+
+    let mut emulator = with_custom(assemble("weight r0, r1\nexit"));
+    emulator.custom.unregister(0);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::IllegalInstruction)
+    ));
+}
+
+#[test]
+fn too_many_operands() {
+    let mut emulator = Emulator::new();
+    let handler = |_: &mut Emulator, _: &Instruction| Ok(());
+    assert!(
+        emulator
+            .register_custom(0, &[OperandKind::Register; 4], handler)
+            .is_err()
+    );
+    assert!(emulator.custom.get(0).is_none());
+}
+
+#[test]
+fn invalid_mnemonics() {
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("weight r0, r1", &mut builder).is_err());
+
+    let mut builder = with_mnemonics();
+    assert!(builder.register_mnemonic("mov", 5, &[]).is_err());
+    // The opcode is already mapped to `weight`
+    assert!(builder.register_mnemonic("hamming", 0, &[]).is_err());
+    assert!(
+        builder
+            .register_mnemonic("wide", 5, &[OperandKind::Register; 4])
+            .is_err()
+    );
+    // Re-registering the same name replaces it
+    assert!(
+        builder
+            .register_mnemonic("weight", 0, &[OperandKind::Register])
+            .is_ok()
+    );
+    assert!(build_bytecode_s("weight r0", &mut builder).is_ok());
+}
+
+#[test]
+fn invalid_operands() {
+    for s in [
+        "weight r0",
+        "weight r0, r1, r2",
+        "weight r0, 1",
+        "madd r0, r1, r2",
+        "incm r0",
+        "fhalf f0, r0",
+        "fault r0",
+    ] {
+        let mut builder = with_mnemonics();
+        assert!(build_bytecode_s(s, &mut builder).is_err(), "{s}");
+    }
+}
@@ -0,0 +1,78 @@
+use vm::{emulator::Register, exception::Exception};
+
+use super::{build, run};
+use crate::builder::{build_bytecode_s, Builder};
+
+#[test]
+fn displacement() {
+    const S: &str = "
+mov r1, offsetof data
+mov r2, 1
+mov r0, qword [r1 + 8]
+mov r3, qword [r1 + r2*8 + 8]
+mov r4, 16
+add r1, r4
+mov r5, qword [r1 - 8]
+mov r6, qword [r1 + r2*8 - 24]
+mov qword [r1 - 16], 7
+mov r7, qword [r1 + 8 - 24]
+lea r8, [r1 - 10h]
+sub r8, r1
+exit
+
+data:
+    dq 1
+    dq 2
+    dq 3
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R0), 2);
+    assert_eq!(emulator.regs.read(Register::R3), 3);
+    assert_eq!(emulator.regs.read(Register::R5), 2);
+    assert_eq!(emulator.regs.read(Register::R6), 1);
+    assert_eq!(emulator.regs.read(Register::R7), 7);
+    assert_eq!(emulator.regs.read(Register::R8), -16i64 as u64);
+}
+
+#[test]
+fn displacement_overflow() {
+    // Wraps around instead of panicking and lands outside the DRAM
+    const S: &str = "
+mov r1, 7FFFFFFFFFFFFFFFh
+mov r0, qword [r1 + 7FFFFFFFFFFFFFFFh]
+exit
+";
+    let mut emulator = build(S);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::AccessViolation(_))
+    ));
+
+    const T: &str = "
+mov r1, 0
+mov r0, qword [r1 - 8]
+exit
+";
+    let mut emulator = build(T);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::AccessViolation(_))
+    ));
+}
+
+#[test]
+fn displacement_syntax() {
+    build("mov r0, qword [r1 - 8000000000000000h]");
+    for s in [
+        "mov r0, qword [r1 + 8000000000000000h]",
+        "mov r0, qword [r1 - r2]",
+        "mov r0, qword [r1 + r2 + r3]",
+        "mov r0, qword [r1 * 2]",
+        "mov r0, qword [r1 + r2*3]",
+        "mov r0, qword [r1 -]",
+        "mov r0, qword [r1 + 8",
+    ] {
+        let mut builder = Builder::new();
+        assert!(build_bytecode_s(s, &mut builder).is_err(), "{s}");
+    }
+}
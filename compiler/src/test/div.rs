@@ -0,0 +1,63 @@
+use vm::{
+    emulator::{Emulator, Register},
+    exception::Exception,
+};
+
+use crate::builder::{build_bytecode_s, Builder};
+
+fn divide(insn: &str, lhs: u64, rhs: u64) -> Result<u64, Exception> {
+    let s = format!("mov r0, {lhs}\nmov r1, {rhs}\n{insn}\nexit\n");
+    let mut builder = Builder::new();
+    build_bytecode_s(s, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.execute().map_err(|e| e.kind)?;
+    Ok(emulator.regs.read(Register::R0))
+}
+
+#[test]
+fn div() {
+    assert_eq!(divide("div r0, r1", 100, 7).unwrap(), 14u64);
+    assert_eq!(divide("div r0, 7", 100, 0).unwrap(), 14u64);
+    assert_eq!(divide("div r0, r1", u64::MAX, 2).unwrap(), u64::MAX / 2);
+    assert_eq!(divide("rem r0, r1", 100, 7).unwrap(), 2u64);
+    assert_eq!(divide("rem r0, 0FFh", 0x1000, 0).unwrap(), 0x1000u64 % 0xFF);
+}
+
+#[test]
+fn idiv() {
+    let minus = |x: i64| x as u64;
+    assert_eq!(divide("idiv r0, r1", minus(-100), 7).unwrap(), minus(-14));
+    assert_eq!(divide("idiv r0, r1", 100, minus(-7)).unwrap(), minus(-14));
+    assert_eq!(divide("irem r0, r1", minus(-100), 7).unwrap(), minus(-2));
+    assert_eq!(divide("irem r0, r1", 100, minus(-7)).unwrap(), 2u64);
+    assert_eq!(divide("idiv r0, 2", minus(-9), 0).unwrap(), minus(-4));
+    assert_eq!(
+        divide("idiv r0, r1", minus(i64::MIN), minus(-1)).unwrap(),
+        minus(i64::MIN)
+    );
+    assert_eq!(
+        divide("irem r0, r1", minus(i64::MIN), minus(-1)).unwrap(),
+        0u64
+    );
+}
+
+#[test]
+fn divide_by_zero() {
+    for insn in [
+        "div r0, r1",
+        "div r0, 0",
+        "idiv r0, r1",
+        "idiv r0, 0",
+        "rem r0, r1",
+        "rem r0, 0",
+        "irem r0, r1",
+        "irem r0, 0",
+    ] {
+        assert!(
+            matches!(divide(insn, 1, 0), Err(Exception::DivideByZero)),
+            "{insn}"
+        );
+    }
+}
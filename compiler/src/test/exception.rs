@@ -0,0 +1,125 @@
+use vm::{
+    emulator::Emulator,
+    exception::{AccessKind, Exception, MemoryAccess},
+    isa::{OpCode, ESCAPE},
+};
+
+use super::build;
+use crate::builder::{build_bytecode_s, Builder};
+
+/// Returns the offset of the last instruction of `s`, which must fault
+fn faulting_ip(s: &str) -> u64 {
+    let (prologue, _) = s.trim_end().rsplit_once('\n').unwrap();
+    let mut builder = Builder::new();
+    build_bytecode_s(prologue, &mut builder).unwrap();
+    builder.cursor as u64
+}
+
+#[test]
+fn read() {
+    const S: &str = "
+mov r0, 100000h
+mov r1, 1
+mov r2, word [r0 + 8]";
+    let e = build(S).execute().unwrap_err();
+    assert_eq!(e.ip, faulting_ip(S));
+    assert_eq!(e.opcode, Some(OpCode::MovRRM as u16));
+    assert_eq!(
+        e.access(),
+        Some(&MemoryAccess {
+            address: 0x100008,
+            kind: AccessKind::Read,
+            size: 2,
+        })
+    );
+}
+
+#[test]
+fn write() {
+    const S: &str = "
+mov r0, 100000h
+mov qword [r0], r1";
+    let e = build(S).execute().unwrap_err();
+    assert_eq!(e.ip, faulting_ip(S));
+    assert_eq!(e.opcode, Some(OpCode::MovRMR as u16));
+    assert_eq!(
+        e.access(),
+        Some(&MemoryAccess {
+            address: 0x100000,
+            kind: AccessKind::Write,
+            size: 8,
+        })
+    );
+    assert_eq!(
+        e.to_string(),
+        format!(
+            "AccessViolation on a write of 8 bytes to 0x100000 at IP {:#x} (opcode {:#04x})",
+            e.ip,
+            OpCode::MovRMR as u16
+        )
+    );
+
+    // At the top of the address space
+    let e = build("mov r0, 0FFFFFFFFFFFFFFFFh\nmov byte [r0], r1")
+        .execute()
+        .unwrap_err();
+    assert_eq!(
+        e.access(),
+        Some(&MemoryAccess {
+            address: u64::MAX,
+            kind: AccessKind::Write,
+            size: 1,
+        })
+    );
+}
+
+#[test]
+fn fetch() {
+    let e = build("mov r0, 100000h\njmp r0").execute().unwrap_err();
+    assert_eq!(e.ip, 0x100000);
+    assert_eq!(e.opcode, None);
+    assert_eq!(
+        e.access(),
+        Some(&MemoryAccess {
+            address: 0x100000,
+            kind: AccessKind::Fetch,
+            size: 1,
+        })
+    );
+
+    // The operands run past the end of the DRAM
+    let mut emulator = Emulator::with_stack(vec![OpCode::MovRIMM as u8, 0x02], 0);
+    let e = emulator.execute().unwrap_err();
+    assert_eq!(e.ip, 0);
+    assert_eq!(e.opcode, Some(OpCode::MovRIMM as u16));
+    assert_eq!(e.access().unwrap().kind, AccessKind::Fetch);
+    assert_eq!(e.access().unwrap().address, 2);
+}
+
+#[test]
+fn illegal_instruction() {
+    let mut emulator = Emulator::with_bytecode(vec![OpCode::Nop as u8, ESCAPE, 0xFF]);
+    let e = emulator.execute().unwrap_err();
+    assert!(matches!(e.kind, Exception::IllegalInstruction));
+    assert_eq!(e.ip, 1);
+    assert_eq!(e.opcode, Some(0xFFFF));
+    assert_eq!(e.access(), None);
+    assert_eq!(
+        e.to_string(),
+        "IllegalInstruction at IP 0x1 (opcode 0xffff)"
+    );
+}
+
+#[test]
+fn single_step() {
+    let mut emulator = build("hlt\nexit");
+    let e = emulator.single_step().unwrap_err();
+    assert!(matches!(e.kind, Exception::Halt));
+    assert_eq!(e.ip, 0);
+
+    // Exit is reported by single_step, but not by execute
+    let e = emulator.single_step().unwrap_err();
+    assert!(matches!(e.kind, Exception::Exit));
+    assert_eq!(e.ip, 1);
+    assert_eq!(e.opcode, Some(OpCode::Exit as u16));
+}
@@ -0,0 +1,255 @@
+use vm::emulator::{FloatRegister, Register};
+
+use super::run;
+use crate::builder::{build_bytecode_s, Builder};
+
+#[test]
+fn arithmetic() {
+    const S: &str = "
+fmov f0, 1.5
+fmov f1, 2.25
+fmov f2, f0
+fadd f2, f1
+fmov f3, f0
+fsub f3, f1
+fmov f4, f0
+fmul f4, f1
+fmov f5, f1
+fdiv f5, f0
+fmov f6, 2
+fsqrt f7, f6
+fmov f8, -0.5
+fmov f9, 1.0e3
+fmov f10, 2.5E-1
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.fregs.read(FloatRegister::F2), 3.75);
+    assert_eq!(emulator.fregs.read(FloatRegister::F3), -0.75);
+    assert_eq!(emulator.fregs.read(FloatRegister::F4), 3.375);
+    assert_eq!(emulator.fregs.read(FloatRegister::F5), 1.5);
+    assert_eq!(emulator.fregs.read(FloatRegister::F6), 2.0);
+    assert_eq!(emulator.fregs.read(FloatRegister::F7), 2f64.sqrt());
+    assert_eq!(emulator.fregs.read(FloatRegister::F8), -0.5);
+    assert_eq!(emulator.fregs.read(FloatRegister::F9), 1000.0);
+    assert_eq!(emulator.fregs.read(FloatRegister::F10), 0.25);
+    // No exception occurred
+    let rf = emulator.regs.read_rf();
+    assert_eq!(rf.read_ie(), 0);
+    assert_eq!(rf.read_ze(), 0);
+    assert_eq!(rf.read_oe(), 0);
+}
+
+#[test]
+fn exponent() {
+    const S: &str = "
+fmov f0, 1e3
+fmov f1, 2E5
+fmov f2, 5e-1
+fmov f3, -4e+2
+mov r0, 1e3h
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.fregs.read(FloatRegister::F0), 1000.0);
+    assert_eq!(emulator.fregs.read(FloatRegister::F1), 200000.0);
+    assert_eq!(emulator.fregs.read(FloatRegister::F2), 0.5);
+    assert_eq!(emulator.fregs.read(FloatRegister::F3), -400.0);
+    assert_eq!(emulator.regs.read(Register::R0), 0x1E3);
+
+    for s in ["fmov f0, 1e", "fmov f0, 1e-", "mov r0, 1e3"] {
+        let mut builder = Builder::new();
+        assert!(build_bytecode_s(s, &mut builder).is_err(), "{s}");
+    }
+}
+
+#[test]
+fn compare() {
+    const S: &str = "
+fmov f0, 1.0
+fmov f1, 2.0
+fcmp f0, f1
+setb r0
+fcmp f1, f0
+seta r1
+fcmp f0, f0
+setz r2
+setp r3
+fmov f2, 0.0
+fdiv f2, f2
+fcmp f2, f0
+setp r4
+setz r5
+setb r6
+jbe unordered
+mov r7, 1
+unordered:
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R0), 1);
+    assert_eq!(emulator.regs.read(Register::R1), 1);
+    assert_eq!(emulator.regs.read(Register::R2), 1);
+    assert_eq!(emulator.regs.read(Register::R3), 0);
+    assert_eq!(emulator.regs.read(Register::R4), 1);
+    assert_eq!(emulator.regs.read(Register::R5), 1);
+    assert_eq!(emulator.regs.read(Register::R6), 1);
+    assert_eq!(emulator.regs.read(Register::R7), 0);
+}
+
+#[test]
+fn exceptions() {
+    const S: &str = "
+fmov f0, 1.0
+fmov f1, 0.0
+fdiv f0, f1
+exit
+";
+    let rf = run(S).regs.read_rf();
+    assert_eq!((rf.read_ie(), rf.read_ze(), rf.read_oe()), (0, 1, 0));
+
+    const T: &str = "
+fmov f0, 1.0e300
+fmul f0, f0
+fmov f1, 1.0
+fadd f1, f1
+exit
+";
+    let emulator = run(T);
+    assert_eq!(emulator.fregs.read(FloatRegister::F0), f64::INFINITY);
+    // Sticky across the following exact operation
+    let rf = emulator.regs.read_rf();
+    assert_eq!((rf.read_ie(), rf.read_ze(), rf.read_oe()), (0, 0, 1));
+
+    const U: &str = "
+fmov f0, -1.0
+fsqrt f0, f0
+exit
+";
+    let emulator = run(U);
+    assert!(emulator.fregs.read(FloatRegister::F0).is_nan());
+    let rf = emulator.regs.read_rf();
+    assert_eq!((rf.read_ie(), rf.read_ze(), rf.read_oe()), (1, 0, 0));
+
+    // NaN operands propagate quietly
+    const V: &str = "
+fmov f0, 0.0
+fdiv f0, f0
+mov r0, rf
+and r0, 100000000h
+not r0
+and rf, r0
+fmov f1, 1.0
+fadd f1, f0
+exit
+";
+    let emulator = run(V);
+    assert!(emulator.fregs.read(FloatRegister::F1).is_nan());
+    assert_eq!(emulator.regs.read_rf().read_ie(), 0);
+}
+
+#[test]
+fn conversion() {
+    const S: &str = "
+mov r0, 0
+sub r0, 7
+cvtsi2f f0, r0
+fmov f1, -2.75
+cvtf2si r1, f1
+fmov f2, 1.0e19
+cvtf2si r2, f2
+fmov r3, f1
+fmov f3, r3
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.fregs.read(FloatRegister::F0), -7.0);
+    assert_eq!(emulator.regs.read(Register::R1), -2i64 as u64);
+    assert_eq!(emulator.regs.read(Register::R2), 0x8000000000000000);
+    assert_eq!(emulator.regs.read(Register::R3), (-2.75f64).to_bits());
+    assert_eq!(emulator.fregs.read(FloatRegister::F3), -2.75);
+    assert_eq!(emulator.regs.read_rf().read_ie(), 1);
+}
+
+#[test]
+fn negative_immediate() {
+    // A negative integer stays an integer for the integer mnemonics
+    const S: &str = "
+mov r0, -1
+cmp r0, -1
+setz r1
+mov r2, offsetof data
+mov byte [r2], -1
+mov r3, -123456789ABCDEFh
+mov r4, 10
+add r4, -3
+mov r5, -8000000000000000h
+fmov f0, -2
+exit
+
+data:
+    dq 0
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R0), u64::MAX);
+    assert_eq!(emulator.regs.read(Register::R1), 1);
+    let data = emulator.regs.read(Register::R2) as usize;
+    assert_eq!(emulator.dram.read_u64le(data).unwrap(), 0xFF);
+    assert_eq!(
+        emulator.regs.read(Register::R3),
+        -0x123456789ABCDEFi64 as u64
+    );
+    assert_eq!(emulator.regs.read(Register::R4), 7);
+    assert_eq!(emulator.regs.read(Register::R5), i64::MIN as u64);
+    assert_eq!(emulator.fregs.read(FloatRegister::F0), -2.0);
+
+    for s in [
+        "mov byte [r0], -129",
+        "mov word [r0], -8001h",
+        "mov r0, -8000000000000001h",
+        "mov r0, -0FFFFFFFFFFFFFFFFh",
+    ] {
+        let mut builder = Builder::new();
+        assert!(build_bytecode_s(s, &mut builder).is_err(), "{s}");
+    }
+}
+
+#[test]
+fn memory() {
+    const S: &str = "
+mov r0, offsetof data
+fmov f0, qword [r0]
+mov r1, 8
+fmov f1, dword [r0 + r1]
+fadd f0, f1
+mov r1, 16
+fmov qword [r0 + r1], f0
+mov r1, 24
+fmov dword [r0 + r1], f0
+mov r2, qword [r0 + r1]
+exit
+
+data:
+    dq 3FF8000000000000h
+    dq 40200000h
+    dq 0
+    dq 0
+";
+    let emulator = run(S);
+    assert_eq!(emulator.fregs.read(FloatRegister::F0), 4.0);
+    assert_eq!(emulator.fregs.read(FloatRegister::F1), 2.5);
+    assert_eq!(emulator.regs.read(Register::R2), 4f32.to_bits() as u64);
+}
+
+#[test]
+fn unsupported() {
+    for s in [
+        "fadd f0, 1.0",
+        "fmov f0, byte [r0]",
+        "fsqrt r0, f0",
+        "cvtsi2f r0, f0",
+    ] {
+        let mut builder = Builder::new();
+        assert!(build_bytecode_s(s, &mut builder).is_err(), "{s}");
+    }
+}
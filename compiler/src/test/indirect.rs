@@ -0,0 +1,139 @@
+use vm::{emulator::Register, exception::Exception};
+
+use super::build;
+use crate::builder::{build_bytecode_s, Builder};
+
+#[test]
+fn jmp_r() {
+    const S: &str = "
+mov r1, offsetof target
+jmp r1
+mov r0, 1
+exit
+target:
+mov r0, 2
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 2);
+}
+
+#[test]
+fn call_r() {
+    const S: &str = "
+mov r0, 3
+mov r1, offsetof add_four
+call r1
+call r1
+exit
+
+add_four:
+    add r0, 4
+    ret
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 11);
+    assert_eq!(emulator.regs.read(Register::SP), emulator.stack_base);
+}
+
+fn test_switch(selector: u64, output: u64) {
+    let s = format!(
+        "
+mov r6, offsetof table
+mov r5, 0
+mov r1, offsetof case0
+mov qword [r6 + r5*8], r1
+inc r5
+mov r1, offsetof case1
+mov qword [r6 + r5*8], r1
+inc r5
+mov r1, offsetof case2
+mov qword [r6 + r5*8], r1
+
+mov r2, {selector}
+jmp qword [r6 + r2*8]
+
+case0:
+    mov r0, 10
+    exit
+case1:
+    mov r0, 20
+    exit
+case2:
+    mov r0, 30
+    exit
+
+table:
+    dq 0
+    dq 0
+    dq 0
+"
+    );
+    let mut emulator = build(&s);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), output);
+}
+
+#[test]
+fn switch() {
+    test_switch(0, 10);
+    test_switch(1, 20);
+    test_switch(2, 30);
+}
+
+#[test]
+fn call_rm() {
+    // The target is read before the return address is pushed
+    const S: &str = "
+mov r0, 3
+mov r1, offsetof add_four
+push r1
+call qword [sp]
+pop r1
+exit
+
+add_four:
+    add r0, 4
+    ret
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 7);
+    assert_eq!(emulator.regs.read(Register::SP), emulator.stack_base);
+}
+
+#[test]
+fn out_of_bounds() {
+    const S: &str = "
+mov r1, 0FFFFFFFFFFFFFFF0h
+jmp r1
+";
+    let mut emulator = build(S);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::AccessViolation(_))
+    ));
+}
+
+#[test]
+fn operands() {
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("jz r0", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("jnz qword [r0]", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("call 10h", &mut builder).is_err());
+}
+
+#[test]
+fn register_label() {
+    // `jmp sp` jumps to the register, so no label may shadow it
+    for s in ["jmp sp\nsp:\nexit", "call r0d\nR0D:\nret"] {
+        let mut builder = Builder::new();
+        assert!(build_bytecode_s(s, &mut builder).is_err(), "{s}");
+    }
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("jmp spin\nspin:\nexit", &mut builder).is_ok());
+}
@@ -0,0 +1,105 @@
+use vm::emulator::{Emulator, Register};
+
+use super::run;
+use crate::builder::{build_bytecode_s, Builder};
+
+/// Returns whether `branch` is taken after `cmp lhs, rhs`.
+fn taken(branch: &str, lhs: u64, rhs: u64) -> bool {
+    let s = format!(
+        "
+mov r0, {lhs}
+mov r1, {rhs}
+cmp r0, r1
+{branch} taken
+mov r2, 3
+exit
+taken:
+mov r2, 7
+exit
+"
+    );
+    let mut builder = Builder::new();
+    build_bytecode_s(s, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.execute().unwrap();
+    match emulator.regs.read(Register::R2) {
+        3 => false,
+        7 => true,
+        x => unreachable!("{x}"),
+    }
+}
+
+#[test]
+fn jcc() {
+    type Cond = fn(u64, u64) -> bool;
+
+    const PAIRS: &[(u64, u64)] = &[
+        (0, 0),
+        (1, 3),
+        (3, 1),
+        (u64::MAX, 1),
+        (1, u64::MAX),
+        (1 << 63, 1),
+        (i64::MAX as u64, u64::MAX),
+        (0x10, 0x0D),
+    ];
+
+    let sub = |lhs: u64, rhs: u64| lhs.wrapping_sub(rhs);
+    let overflows = |lhs: u64, rhs: u64| (lhs as i64).overflowing_sub(rhs as i64).1;
+    let parity = |lhs: u64, rhs: u64| (sub(lhs, rhs) as u8).count_ones() % 2 == 0;
+
+    let table: &[(&[&str], Cond)] = &[
+        (&["jz", "je"], |l, r| l == r),
+        (&["jnz", "jne"], |l, r| l != r),
+        (&["jl", "jnge"], |l, r| (l as i64) < (r as i64)),
+        (&["jle", "jng"], |l, r| (l as i64) <= (r as i64)),
+        (&["jg", "jnle"], |l, r| (l as i64) > (r as i64)),
+        (&["jge", "jnl"], |l, r| (l as i64) >= (r as i64)),
+        (&["jb", "jc", "jnae"], |l, r| l < r),
+        (&["jbe", "jna"], |l, r| l <= r),
+        (&["ja", "jnbe"], |l, r| l > r),
+        (&["jae", "jnb", "jnc"], |l, r| l >= r),
+        (&["js"], |l, r| (l.wrapping_sub(r) as i64) < 0),
+        (&["jns"], |l, r| (l.wrapping_sub(r) as i64) >= 0),
+    ];
+    for (branches, cond) in table {
+        for &(lhs, rhs) in PAIRS {
+            for branch in *branches {
+                assert_eq!(
+                    taken(branch, lhs, rhs),
+                    cond(lhs, rhs),
+                    "{branch} with {lhs:#x}, {rhs:#x}"
+                );
+            }
+        }
+    }
+
+    for &(lhs, rhs) in PAIRS {
+        assert_eq!(taken("jo", lhs, rhs), overflows(lhs, rhs));
+        assert_eq!(taken("jno", lhs, rhs), !overflows(lhs, rhs));
+        assert_eq!(taken("jp", lhs, rhs), parity(lhs, rhs));
+        assert_eq!(taken("jpe", lhs, rhs), parity(lhs, rhs));
+        assert_eq!(taken("jnp", lhs, rhs), !parity(lhs, rhs));
+        assert_eq!(taken("jpo", lhs, rhs), !parity(lhs, rhs));
+    }
+}
+
+#[test]
+fn branch_to_next() {
+    // A branch to the next instruction has an offset of 0, taken or not
+    const S: &str = "
+mov r0, 1
+cmp r0, r0
+jz next
+next:
+jnz skip
+skip:
+jmp done
+done:
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R0), 1);
+}
@@ -0,0 +1,68 @@
+use vm::{emulator::Register, exception::Exception};
+
+use super::build;
+use crate::builder::{build_bytecode_s, Builder};
+
+#[test]
+fn lea() {
+    const S: &str = "
+mov r1, 1000h
+mov r2, 3
+lea r0, [r1]
+lea r3, [r1 + r2]
+lea r4, [r1 + r2*8]
+lea r5, qword [r2 + r2*4]
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 0x1000);
+    assert_eq!(emulator.regs.read(Register::R3), 0x1003);
+    assert_eq!(emulator.regs.read(Register::R4), 0x1018);
+    assert_eq!(emulator.regs.read(Register::R5), 15);
+}
+
+#[test]
+fn lea_wrap() {
+    // Neither overflows nor touches memory
+    const S: &str = "
+mov r1, 0FFFFFFFFFFFFFFFFh
+mov r2, 8000000000000001h
+lea r0, [r1 + r2*2]
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 1);
+}
+
+#[test]
+fn memop_wrap() {
+    const S: &str = "
+mov r1, 0FFFFFFFFFFFFFFFCh
+mov r0, qword [r1]
+exit
+";
+    let mut emulator = build(S);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::AccessViolation(_))
+    ));
+}
+
+#[test]
+fn lea_unsupported() {
+    for s in ["lea r0, r1", "lea r0, 5", "lea [r0], r1"] {
+        let mut builder = Builder::new();
+        assert!(build_bytecode_s(s, &mut builder).is_err(), "{s}");
+    }
+}
+
+#[test]
+fn size_class_required() {
+    // Only `lea` defaults a bracket without a size class
+    for s in ["mov [r0], 5", "add r0, [r1]", "movzx r0, [r1]"] {
+        let mut builder = Builder::new();
+        assert!(build_bytecode_s(s, &mut builder).is_err(), "{s}");
+    }
+}
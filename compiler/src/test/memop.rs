@@ -0,0 +1,226 @@
+use vm::emulator::{Emulator, Register};
+
+use crate::builder::{build_bytecode_s, Builder};
+
+fn run_with_data(s: &str) -> (Emulator, u64) {
+    let mut builder = Builder::new();
+    build_bytecode_s(s, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let data_loc = *builder.labels.get("data").unwrap();
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.execute().unwrap();
+    (emulator, data_loc)
+}
+
+#[test]
+fn binop_r_rm() {
+    const S: &str = "
+mov r1, offsetof data
+mov r0, 100
+add r0, byte [r1]
+mov r2, 100
+sub r2, qword [r1]
+mov r3, 0FFh
+and r3, word [r1]
+mov r4, 1
+or r4, dword [r1]
+mov r5, 0FFh
+xor r5, byte [r1]
+mov r6, 3
+imul r6, qword [r1]
+exit
+
+data:
+    dq 0F0F0h
+";
+    let (emulator, _) = run_with_data(S);
+    assert_eq!(emulator.regs.read(Register::R0), 100 + 0xF0);
+    assert_eq!(
+        emulator.regs.read(Register::R2),
+        100u64.wrapping_sub(0xF0F0)
+    );
+    assert_eq!(emulator.regs.read(Register::R3), 0xF0);
+    assert_eq!(emulator.regs.read(Register::R4), 0xF0F1);
+    assert_eq!(emulator.regs.read(Register::R5), 0x0F);
+    assert_eq!(emulator.regs.read(Register::R6), 3 * 0xF0F0);
+    assert_eq!(emulator.regs.read_rf().read_sf(), 0);
+}
+
+#[test]
+fn binop_rm_r() {
+    const S: &str = "
+mov r1, offsetof data
+mov r0, 10h
+add qword [r1], r0
+mov r0, 1
+sub dword [r1], r0
+mov r2, 8
+mov r0, 0FFh
+and byte [r1 + r2], r0
+or byte [r1 + r2], r2
+xor qword [r1], r0
+exit
+
+data:
+    dq 1000h
+    dq 1234h
+";
+    let (emulator, data_loc) = run_with_data(S);
+    assert_eq!(
+        emulator.dram.read_u64le(data_loc as usize).unwrap(),
+        (0x1000 + 0x10 - 1) ^ 0xFF
+    );
+    assert_eq!(
+        emulator.dram.read_u64le(data_loc as usize + 8).unwrap(),
+        0x123C
+    );
+}
+
+#[test]
+fn binop_rm_imm() {
+    const S: &str = "
+mov r1, offsetof data
+add qword [r1], 5
+sub qword [r1], 2
+or qword [r1], 100h
+and qword [r1], 0FF0Fh
+xor qword [r1], 1
+exit
+
+data:
+    dq 10h
+";
+    let (emulator, data_loc) = run_with_data(S);
+    assert_eq!(
+        emulator.dram.read_u64le(data_loc as usize).unwrap(),
+        (((0x10 + 5 - 2) | 0x100) & 0xFF0F) ^ 1
+    );
+}
+
+#[test]
+fn rm_width() {
+    const S: &str = "
+mov r1, offsetof data
+add byte [r1], 1
+exit
+
+data:
+    dq 12FFh
+";
+    let (emulator, data_loc) = run_with_data(S);
+    assert_eq!(emulator.dram.read_u64le(data_loc as usize).unwrap(), 0x1200);
+    let rf = emulator.regs.read_rf();
+    assert_eq!(rf.read_cf(), 1);
+    assert_eq!(rf.read_zf(), 1);
+    assert_eq!(rf.read_sf(), 0);
+}
+
+#[test]
+fn rm_width_overflow() {
+    const S: &str = "
+mov r1, offsetof data
+mov r0, 1
+add word [r1], r0
+exit
+
+data:
+    dq 7FFFh
+";
+    let (emulator, data_loc) = run_with_data(S);
+    assert_eq!(emulator.dram.read_u64le(data_loc as usize).unwrap(), 0x8000);
+    let rf = emulator.regs.read_rf();
+    assert_eq!(rf.read_cf(), 0);
+    assert_eq!(rf.read_sf(), 1);
+    assert_eq!(rf.read_of(), 1);
+}
+
+#[test]
+fn inc_dec_rm() {
+    const S: &str = "
+mov r1, offsetof data
+mov r2, 8
+inc qword [r1]
+dec byte [r1 + r2]
+exit
+
+data:
+    dq 41h
+    dq 100h
+";
+    let (emulator, data_loc) = run_with_data(S);
+    assert_eq!(emulator.dram.read_u64le(data_loc as usize).unwrap(), 0x42);
+    assert_eq!(
+        emulator.dram.read_u64le(data_loc as usize + 8).unwrap(),
+        0x1FF
+    );
+    assert_eq!(emulator.regs.read_rf().read_sf(), 1);
+}
+
+#[test]
+fn cmp_test_rm() {
+    const S: &str = "
+mov r1, offsetof data
+xor r0, r0
+cmp byte [r1], 0FFh
+jnz fail
+inc r0
+mov r2, 0FFh
+cmp r2, byte [r1]
+jnz fail
+inc r0
+cmp word [r1], r2
+jbe fail
+inc r0
+test qword [r1], 100h
+jz fail
+inc r0
+test r2, byte [r1]
+jz fail
+inc r0
+mov r2, 1
+test byte [r1], r2
+jz fail
+inc r0
+fail:
+exit
+
+data:
+    dq 1FFh
+";
+    let (emulator, data_loc) = run_with_data(S);
+    assert_eq!(emulator.regs.read(Register::R0), 6);
+    assert_eq!(emulator.dram.read_u64le(data_loc as usize).unwrap(), 0x1FF);
+}
+
+#[test]
+fn xchg_rm() {
+    const S: &str = "
+mov r1, offsetof data
+mov r0, 5
+xchg r0, qword [r1]
+mov r2, 0ABCDh
+xchg byte [r1], r2
+exit
+
+data:
+    dq 7
+";
+    let (emulator, data_loc) = run_with_data(S);
+    assert_eq!(emulator.regs.read(Register::R0), 7);
+    assert_eq!(emulator.regs.read(Register::R2), 5);
+    assert_eq!(emulator.dram.read_u64le(data_loc as usize).unwrap(), 0xCD);
+}
+
+#[test]
+fn unsupported_operands() {
+    for s in [
+        "xchg r0, 5",
+        "shl byte [r0], 1",
+        "imul qword [r0], r1",
+        "div r0, byte [r1]",
+    ] {
+        let mut builder = Builder::new();
+        assert!(build_bytecode_s(s, &mut builder).is_err(), "{s}");
+    }
+}
@@ -1,15 +1,61 @@
+use vm::emulator::Emulator;
+
+use crate::builder::{build_bytecode_s, Builder};
+
 mod and;
 mod array;
+mod bits;
+mod block;
+mod call;
+mod cmov;
 mod cmp;
+mod cpuid;
+mod custom;
+mod displacement;
+mod div;
+mod exception;
 mod fibonacci;
+mod float;
 mod imul;
+mod indirect;
+mod jcc;
 mod jg;
 mod jle;
 mod jz;
+mod lea;
 mod lexer;
+mod memop;
 mod mov;
+mod movx;
+mod mul;
 mod offsetof;
 mod or;
+mod port;
+mod push;
 mod rc4;
+mod relative;
+mod rflags;
+mod sha256;
+mod shift;
+mod simd;
+mod subreg;
+mod ternary;
 mod test;
+mod unary;
 mod xor;
+
+/// Builds `s` into an [`Emulator`] loaded with its bytecode
+pub(crate) fn build(s: &str) -> Emulator {
+    let mut builder = Builder::new();
+    build_bytecode_s(s, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    Emulator::with_bytecode(dump)
+}
+
+/// Builds `s` and executes it until it exits
+pub(crate) fn run(s: &str) -> Emulator {
+    let mut emulator = build(s);
+    emulator.execute().unwrap();
+    emulator
+}
@@ -77,3 +77,69 @@ exit
     assert_eq!(emulator.dram.read_u64le(0).unwrap(), 0x5F621844867E03A6u64);
     assert_ne!(emulator.ip() as usize, 0);
 }
+
+#[test]
+fn mov_rm_imm() {
+    const S: &str = "
+mov r0, offsetof data
+mov r1, 8
+mov byte [r0], 0ABh
+mov word [r0 + r1], 0BEEFh
+mov r1, 2
+mov dword [r0 + r1*8], 0DEADBEEFh
+mov r1, 3
+mov qword [r0 + r1*8], 0FEDCBA9876543210h
+exit
+
+data:
+    dq 0FFFFFFFFFFFFFFFFh
+    dq 0FFFFFFFFFFFFFFFFh
+    dq 0FFFFFFFFFFFFFFFFh
+    dq 0
+";
+    let mut builder = Builder::new();
+    build_bytecode_s(S, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let data_loc = *builder.labels.get("data").unwrap() as usize;
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.execute().unwrap();
+    assert_eq!(
+        emulator.dram.read_u64le(data_loc).unwrap(),
+        0xFFFFFFFFFFFFFFABu64
+    );
+    assert_eq!(
+        emulator.dram.read_u64le(data_loc + 8).unwrap(),
+        0xFFFFFFFFFFFFBEEFu64
+    );
+    assert_eq!(
+        emulator.dram.read_u64le(data_loc + 16).unwrap(),
+        0xFFFFFFFFDEADBEEFu64
+    );
+    assert_eq!(
+        emulator.dram.read_u64le(data_loc + 24).unwrap(),
+        0xFEDCBA9876543210u64
+    );
+}
+
+#[test]
+fn mov_rm_imm_overflow() {
+    for s in [
+        "mov byte [r0], 100h",
+        "mov word [r0], 10000h",
+        "mov dword [r0], 100000000h",
+        "add byte [r0], 1FFh",
+    ] {
+        let mut builder = Builder::new();
+        assert!(build_bytecode_s(s, &mut builder).is_err(), "{s}");
+    }
+
+    // Labels are only known once the source is finalized
+    let s = format!(
+        "mov byte [r0], offsetof data\nexit\n{}data:\ndb 0\n",
+        "dq 0\n".repeat(32)
+    );
+    let mut builder = Builder::new();
+    build_bytecode_s(s, &mut builder).unwrap();
+    assert!(builder.finalize().is_err());
+}
@@ -0,0 +1,94 @@
+use vm::emulator::Register;
+
+use super::run;
+use crate::builder::{build_bytecode_s, Builder};
+
+#[test]
+fn movx_r_rm() {
+    const S: &str = "
+mov r0, offsetof data
+movzx r1, byte [r0]
+movsx r2, byte [r0]
+movzx r3, word [r0]
+movsx r4, word [r0]
+movzx r5, dword [r0]
+movsxd r6, dword [r0]
+movsx r7, qword [r0]
+exit
+
+data:
+    dq 0FFFF8080h
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R1), 0x80);
+    assert_eq!(emulator.regs.read(Register::R2), -0x80i64 as u64);
+    assert_eq!(emulator.regs.read(Register::R3), 0x8080);
+    assert_eq!(emulator.regs.read(Register::R4), -0x7F80i64 as u64);
+    assert_eq!(emulator.regs.read(Register::R5), 0xFFFF8080);
+    assert_eq!(emulator.regs.read(Register::R6), -0x7F80i64 as u64);
+    assert_eq!(emulator.regs.read(Register::R7), 0xFFFF8080);
+}
+
+#[test]
+fn movx_r_r() {
+    const S: &str = "
+mov r0, 12345678FEDC7F80h
+movzx r1, r0b
+movsx r2, r0b
+movzx r3, r0w
+movsx r4, r0w
+movzx r5, r0d
+movsx r6, r0d
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R1), 0x80);
+    assert_eq!(emulator.regs.read(Register::R2), 0xFFFFFFFFFFFFFF80);
+    assert_eq!(emulator.regs.read(Register::R3), 0x7F80);
+    assert_eq!(emulator.regs.read(Register::R4), 0x7F80);
+    assert_eq!(emulator.regs.read(Register::R5), 0xFEDC7F80);
+    assert_eq!(emulator.regs.read(Register::R6), 0xFFFFFFFFFEDC7F80);
+}
+
+#[test]
+fn movsx_sum() {
+    // Sums a table of signed bytes
+    const S: &str = "
+mov r0, offsetof table
+xor r1, r1
+xor r2, r2
+loop:
+    movsx r3, byte [r0 + r1]
+    add r2, r3
+    inc r1
+    cmp r1, 5
+    jnz loop
+exit
+
+table:
+    db 10
+    db 0FEh
+    db 80h
+    db 7Fh
+    db 0FFh
+";
+    let emulator = run(S);
+    assert_eq!(
+        emulator.regs.read(Register::R2) as i64,
+        10 - 2 - 128 + 127 - 1
+    );
+}
+
+#[test]
+fn movx_unsupported() {
+    for s in [
+        "movzx r0, r1",
+        "movsx r0, 5",
+        "movsx r0, byte r1",
+        "movzx byte [r0], r1",
+        "mov r0, byte r1",
+    ] {
+        let mut builder = Builder::new();
+        assert!(build_bytecode_s(s, &mut builder).is_err(), "{s}");
+    }
+}
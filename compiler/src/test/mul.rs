@@ -0,0 +1,243 @@
+use vm::{
+    emulator::{Emulator, Register},
+    exception::Exception,
+    isa::{Instruction, OpCode},
+};
+
+use super::build;
+use crate::builder::{build_bytecode_s, Builder};
+
+const VALUES: [u64; 8] = [
+    0,
+    1,
+    2,
+    0x7FFFFFFFFFFFFFFF,
+    0x8000000000000000,
+    0xFFFFFFFFFFFFFFFF,
+    0x0123456789ABCDEF,
+    0xFEDCBA9876543210,
+];
+
+#[test]
+fn mul() {
+    for lhs in VALUES {
+        for rhs in VALUES {
+            let s = format!(
+                "
+mov r0, {lhs}
+mov r1, {rhs}
+mov r2, {lhs}
+mulh r2, r1
+mov r3, {lhs}
+imulh r3, r1
+mul r0, r1
+exit
+"
+            );
+            let mut emulator = build(&s);
+            emulator.execute().unwrap();
+            let product = lhs as u128 * rhs as u128;
+            let signed = lhs as i64 as i128 * rhs as i64 as i128;
+            assert_eq!(emulator.regs.read(Register::R0), product as u64);
+            assert_eq!(emulator.regs.read(Register::R1), (product >> 64) as u64);
+            assert_eq!(emulator.regs.read(Register::R2), (product >> 64) as u64);
+            assert_eq!(emulator.regs.read(Register::R3), (signed >> 64) as u64);
+            let rf = emulator.regs.read_rf();
+            assert_eq!(rf.read_cf(), ((product >> 64) != 0) as u64);
+            assert_eq!(rf.read_of(), ((product >> 64) != 0) as u64);
+        }
+    }
+}
+
+#[test]
+fn imulh_rm() {
+    const S: &str = "
+mov r6, offsetof data
+mov r0, 0FFFFFFFFFFFFFFFFh
+imulh r0, qword [r6]
+mov r1, 0FFFFFFFFFFFFFFFFh
+mulh r1, qword [r6]
+exit
+
+data:
+    dq 0FFFFFFFFFFFFFFFFh
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 0);
+    assert_eq!(emulator.regs.read(Register::R1), 0xFFFFFFFFFFFFFFFE);
+    let rf = emulator.regs.read_rf();
+    assert_eq!(rf.read_cf(), 1);
+}
+
+#[test]
+fn mulh_rm_narrow() {
+    // The memory operand is extended from its own size
+    const S: &str = "
+mov r6, offsetof data
+mov r0, 2
+imulh r0, byte [r6]
+mov r1, 2
+mulh r1, byte [r6]
+mov r2, 2
+imulh r2, word [r6]
+exit
+
+data:
+    dq 0FFh
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 0xFFFFFFFFFFFFFFFF);
+    assert_eq!(emulator.regs.read(Register::R1), 0);
+    assert_eq!(emulator.regs.read(Register::R2), 0);
+}
+
+#[test]
+fn mul_aliased() {
+    // Both halves would go to the same register
+    for s in ["mul r0, r0", "mul r1d, r1"] {
+        let mut builder = Builder::new();
+        assert!(build_bytecode_s(s, &mut builder).is_err(), "{s}");
+    }
+
+    let mut insn = Instruction::new();
+    insn.set_opcode(OpCode::MulRR);
+    insn.set_op0_reg(Register::R0);
+    insn.set_op1_reg(Register::R0);
+    let mut bytecode = Vec::new();
+    insn.encode(&mut bytecode).unwrap();
+    let mut emulator = Emulator::with_bytecode(bytecode);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::IllegalInstruction)
+    ));
+}
+
+#[test]
+fn adc_sbb() {
+    for lhs in VALUES {
+        for rhs in VALUES {
+            let lhs = ((lhs as u128) << 64) | rhs.rotate_left(7) as u128;
+            let rhs = ((rhs as u128) << 64) | lhs.rotate_right(3) as u64 as u128;
+            let s = format!(
+                "
+mov r0, {}
+mov r1, {}
+mov r2, {}
+mov r3, {}
+mov r4, r0
+mov r5, r1
+add r0, r2
+adc r1, r3
+setc r6
+sub r4, r2
+sbb r5, r3
+setc r7
+exit
+",
+                lhs as u64,
+                (lhs >> 64) as u64,
+                rhs as u64,
+                (rhs >> 64) as u64,
+            );
+            let mut emulator = build(&s);
+            emulator.execute().unwrap();
+            let (sum, carry) = lhs.overflowing_add(rhs);
+            let (diff, borrow) = lhs.overflowing_sub(rhs);
+            assert_eq!(emulator.regs.read(Register::R0), sum as u64);
+            assert_eq!(emulator.regs.read(Register::R1), (sum >> 64) as u64);
+            assert_eq!(emulator.regs.read(Register::R6), carry as u64);
+            assert_eq!(emulator.regs.read(Register::R4), diff as u64);
+            assert_eq!(emulator.regs.read(Register::R5), (diff >> 64) as u64);
+            assert_eq!(emulator.regs.read(Register::R7), borrow as u64);
+        }
+    }
+}
+
+#[test]
+fn adc_carry_in() {
+    // A carry-in on a maximal operand still produces a carry-out
+    const S: &str = "
+mov r0, 1
+mov r1, 0FFFFFFFFFFFFFFFFh
+cmp r0, 2
+adc r0, r1
+setc r2
+mov r3, 0
+cmp r3, 1
+sbb r3, r1
+setc r4
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 1);
+    assert_eq!(emulator.regs.read(Register::R2), 1);
+    assert_eq!(emulator.regs.read(Register::R3), 0);
+    assert_eq!(emulator.regs.read(Register::R4), 1);
+}
+
+#[test]
+fn bignum_add() {
+    // 256-bit a += b, limbs in little-endian order
+    const S: &str = "
+mov r6, offsetof a
+mov r7, offsetof b
+mov r5, 0
+mov r4, 4
+xor r0, r0
+loop:
+    mov r1, qword [r7 + r5*8]
+    adc qword [r6 + r5*8], r1
+    inc r5
+    dec r4
+    jnz loop
+setc r0
+exit
+
+a:
+    dq 0FFFFFFFFFFFFFFFFh
+    dq 0FFFFFFFFFFFFFFFFh
+    dq 0
+    dq 8000000000000000h
+b:
+    dq 1
+    dq 0
+    dq 0FFFFFFFFFFFFFFFFh
+    dq 8000000000000000h
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    let a = emulator.regs.read(Register::R6) as usize;
+    let limbs = (0..4)
+        .map(|i| emulator.dram.read_u64le(a + i * 8).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(limbs, [0, 0, 0, 1]);
+    assert_eq!(emulator.regs.read(Register::R0), 1);
+}
+
+#[test]
+fn adc_rm_byte() {
+    const S: &str = "
+mov r6, offsetof data
+mov r0, 0
+cmp r0, 1
+adc byte [r6], 0
+setc r1
+setz r2
+sbb byte [r6], 1
+setc r3
+exit
+
+data:
+    dq 0FFh
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    let data = emulator.regs.read(Register::R6) as usize;
+    assert_eq!(emulator.dram.read_u64le(data).unwrap(), 0xFE);
+    assert_eq!(emulator.regs.read(Register::R1), 1);
+    assert_eq!(emulator.regs.read(Register::R2), 1);
+    assert_eq!(emulator.regs.read(Register::R3), 1);
+}
@@ -0,0 +1,146 @@
+use std::sync::{Arc, Mutex};
+
+use vm::{emulator::Register, exception::Exception, isa::OperandSize, port::PortDevice};
+
+use super::build;
+use crate::builder::{build_bytecode_s, Builder};
+
+/// Records the bytes written to it, and cannot be read
+#[derive(Default)]
+struct Console(Vec<(u16, u64)>);
+
+impl PortDevice for Console {
+    fn read(&mut self, _port: u16, _size: OperandSize) -> Result<u64, Exception> {
+        Err(Exception::IllegalInstruction)
+    }
+
+    fn write(&mut self, port: u16, _size: OperandSize, value: u64) -> Result<(), Exception> {
+        self.0.push((port, value));
+        Ok(())
+    }
+}
+
+/// Returns the next value of a counter on each read
+#[derive(Default)]
+struct Counter(u64);
+
+impl PortDevice for Counter {
+    fn read(&mut self, _port: u16, _size: OperandSize) -> Result<u64, Exception> {
+        self.0 += 0x101;
+        Ok(self.0)
+    }
+
+    fn write(&mut self, _port: u16, _size: OperandSize, value: u64) -> Result<(), Exception> {
+        self.0 = value;
+        Ok(())
+    }
+}
+
+#[test]
+fn output() {
+    const S: &str = "
+    mov r0, offsetof message
+print:
+    mov r1, byte [r0]
+    test r1, r1
+    jz done
+    out 0E9h, r1b
+    inc r0
+    jmp print
+done:
+    mov r2, 0E9h
+    mov r3, 0ABCDh
+    out r2, r3w
+    exit
+
+message:
+    db 48h
+    db 69h
+    db 0
+";
+    let console = Arc::new(Mutex::new(Console::default()));
+    let mut emulator = build(S);
+    emulator.ports.map(0xE9, console.clone());
+    emulator.execute().unwrap();
+
+    let console = console.lock().unwrap();
+    assert_eq!(console.0, [(0xE9, 0x48), (0xE9, 0x69), (0xE9, 0xABCD)]);
+}
+
+#[test]
+fn input() {
+    const S: &str = "
+mov r0, 1111111111111111h
+mov r1, 1111111111111111h
+mov r2, 1111111111111111h
+in r0, 60h
+in r1b, 60h
+mov r3, 60h
+in r2w, r3
+in r4, 61h
+in r5b, 61h
+exit
+";
+    let counter = Arc::new(Mutex::new(Counter::default()));
+    let mut emulator = build(S);
+    emulator.ports.map(0x60, counter.clone());
+    emulator.execute().unwrap();
+
+    assert_eq!(emulator.regs.read(Register::R0), 0x101);
+    assert_eq!(emulator.regs.read(Register::R1), 0x1111111111111102);
+    assert_eq!(emulator.regs.read(Register::R2), 0x1111111111110303);
+    // Unmapped ports read all ones
+    assert_eq!(emulator.regs.read(Register::R4), u64::MAX);
+    assert_eq!(emulator.regs.read(Register::R5), 0xFF);
+    assert_eq!(counter.lock().unwrap().0, 0x303);
+}
+
+#[test]
+fn shared() {
+    const S: &str = "
+out 60h, r0
+out 61h, r1
+in r2, 61h
+out 62h, r2
+exit
+";
+    let counter = Arc::new(Mutex::new(Counter::default()));
+    let mut emulator = build(S);
+    emulator.regs.write(Register::R0, 7);
+    emulator.regs.write(Register::R1, 0x1000);
+    emulator.ports.map(0x60, counter.clone());
+    emulator.ports.map(0x61, counter.clone());
+    emulator.execute().unwrap();
+
+    assert_eq!(emulator.regs.read(Register::R2), 0x1101);
+    assert_eq!(counter.lock().unwrap().0, 0x1101);
+
+    emulator.ports.unmap(0x61);
+    emulator.regs.write(Register::IP, 0);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R2), u64::MAX);
+}
+
+#[test]
+fn device_error() {
+    let mut emulator = build("in r0, 0E9h\nexit");
+    emulator
+        .ports
+        .map(0xE9, Arc::new(Mutex::new(Console::default())));
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::IllegalInstruction)
+    ));
+}
+
+#[test]
+fn invalid_operands() {
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("in r0, 10000h", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("out 60h, 1", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("in 60h, r0", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("out r0, [r1]", &mut builder).is_err());
+}
@@ -0,0 +1,68 @@
+use vm::{
+    emulator::{Emulator, Register},
+    exception::Exception,
+};
+
+use crate::builder::{build_bytecode_s, Builder};
+
+#[test]
+fn push_pop() {
+    const S: &str = "
+mov r0, 1234h
+push r0
+push 5678h
+pop r1
+pop r2
+exit
+";
+    let mut builder = Builder::new();
+    build_bytecode_s(S, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let bytecode_len = dump.len();
+    assert_ne!(bytecode_len, 0);
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R1), 0x5678u64);
+    assert_eq!(emulator.regs.read(Register::R2), 0x1234u64);
+    assert_eq!(emulator.regs.read(Register::SP), emulator.stack_base);
+    assert_ne!(emulator.ip() as usize, 0);
+}
+
+#[test]
+fn push_overflow() {
+    const S: &str = "
+push 1
+push 2
+exit
+";
+    let mut builder = Builder::new();
+    build_bytecode_s(S, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let mut emulator = Emulator::with_stack(dump, 8);
+    emulator.single_step().unwrap();
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::StackOverflow)
+    ));
+    assert_eq!(emulator.regs.read(Register::SP), emulator.stack_limit);
+}
+
+#[test]
+fn pop_underflow() {
+    const S: &str = "
+pop r0
+exit
+";
+    let mut builder = Builder::new();
+    build_bytecode_s(S, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let mut emulator = Emulator::with_bytecode(dump);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::StackUnderflow)
+    ));
+    assert_eq!(emulator.regs.read(Register::SP), emulator.stack_base);
+}
@@ -0,0 +1,75 @@
+use vm::emulator::{Emulator, Register};
+
+use crate::builder::{build_bytecode_s, Builder};
+
+fn assemble(s: &str) -> Result<Vec<u8>, String> {
+    let mut builder = Builder::new();
+    build_bytecode_s(s, &mut builder).map_err(|e| e.to_string())?;
+    builder.finalize()?;
+    builder.dump()
+}
+
+/// Loads `bytecode` at `base` and runs it from there
+fn run_at(bytecode: &[u8], base: usize) -> Emulator {
+    let mut image = vec![0; base];
+    image.extend_from_slice(bytecode);
+    let mut emulator = Emulator::with_bytecode(image);
+    emulator.regs.write(Register::IP, base as u64);
+    emulator.execute().unwrap();
+    emulator
+}
+
+#[test]
+fn relative() {
+    const S: &str = "
+mov r0, qword [rel table]
+mov r1, dword [rel table + 8]
+mov r2, qword [rel table - 8]
+lea r3, [rel table]
+mov qword [rel result], r0
+mov r4, qword [rel result]
+exit
+
+    dq 1
+table:
+    dq 2
+    dq 3
+result:
+    dq 0
+";
+    let bytecode = assemble(S).unwrap();
+    for base in [0, 3, 0x1000] {
+        let emulator = run_at(&bytecode, base);
+        assert_eq!(emulator.regs.read(Register::R0), 2, "{base}");
+        assert_eq!(emulator.regs.read(Register::R1), 3, "{base}");
+        assert_eq!(emulator.regs.read(Register::R2), 1, "{base}");
+        assert_eq!(emulator.regs.read(Register::R4), 2, "{base}");
+        // Points to the table wherever it is loaded
+        let table = emulator.regs.read(Register::R3) as usize;
+        assert_eq!(emulator.dram.read_u64le(table).unwrap(), 2, "{base}");
+    }
+}
+
+#[test]
+fn relative_branch() {
+    const S: &str = "
+mov r0, 0
+lea r1, [rel inc]
+call r1
+exit
+
+inc:
+    inc r0
+    ret
+";
+    let bytecode = assemble(S).unwrap();
+    let emulator = run_at(&bytecode, 0x100);
+    assert_eq!(emulator.regs.read(Register::R0), 1);
+}
+
+#[test]
+fn relative_unsupported() {
+    assert!(assemble("mov r0, qword [rel missing]").is_err());
+    assert!(assemble("mov r0, qword [rel table + r1]\ntable:").is_err());
+    assert!(assemble("mov qword [rel table], offsetof table\ntable:").is_err());
+}
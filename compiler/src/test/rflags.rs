@@ -0,0 +1,131 @@
+use vm::emulator::{Emulator, RFlags, Register};
+
+use crate::builder::{build_bytecode_s, Builder};
+
+/// Runs `insn` with `r0 = lhs` and `r1 = rhs` and returns the resulting flags.
+///
+/// All flags are set before `insn` executes so that preserved flags can be
+/// told apart from cleared ones.
+fn run_insn(insn: &str, lhs: u64, rhs: u64) -> RFlags {
+    let s = format!("mov r0, {lhs}\nmov r1, {rhs}\n{insn}\nexit\n");
+    let mut builder = Builder::new();
+    build_bytecode_s(s, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.single_step().unwrap();
+    emulator.single_step().unwrap();
+    let mut rf = emulator.regs.read_rf();
+    rf.write_cf(1);
+    rf.write_zf(1);
+    rf.write_sf(1);
+    rf.write_of(1);
+    rf.write_pf(1);
+    rf.write_af(1);
+    emulator.regs.write_rf(rf);
+    emulator.execute().unwrap();
+    emulator.regs.read_rf()
+}
+
+#[test]
+fn rflags() {
+    const MAX: u64 = u64::MAX;
+    const MIN_SIGNED: u64 = 1 << 63;
+    const MAX_SIGNED: u64 = MIN_SIGNED - 1;
+
+    #[rustfmt::skip]
+    const TABLE: &[(&str, u64, u64, [u64; 6])] = &[
+        // insn             lhs         rhs          CF ZF SF OF PF AF
+        ("add r0, r1",      1,          2,          [0, 0, 0, 0, 1, 0]),
+        ("add r0, r1",      MAX,        1,          [1, 1, 0, 0, 1, 1]),
+        ("add r0, r1",      MAX_SIGNED, 1,          [0, 0, 1, 1, 1, 1]),
+        ("add r0, r1",      MIN_SIGNED, MIN_SIGNED, [1, 1, 0, 1, 1, 0]),
+        ("add r0, 0Fh",     1,          0,          [0, 0, 0, 0, 0, 1]),
+        ("sub r0, r1",      5,          5,          [0, 1, 0, 0, 1, 0]),
+        ("sub r0, r1",      0,          1,          [1, 0, 1, 0, 1, 1]),
+        ("sub r0, r1",      MIN_SIGNED, 1,          [0, 0, 0, 1, 1, 1]),
+        ("sub r0, 3",       10,         0,          [0, 0, 0, 0, 0, 0]),
+        ("cmp r0, r1",      1,          3,          [1, 0, 1, 0, 0, 1]),
+        ("cmp r0, r1",      3,          1,          [0, 0, 0, 0, 0, 0]),
+        ("cmp r0, 7",       7,          0,          [0, 1, 0, 0, 1, 0]),
+        ("and r0, r1",      0xF0,       0x0F,       [0, 1, 0, 0, 1, 0]),
+        ("and r0, r1",      MAX,        MIN_SIGNED, [0, 0, 1, 0, 1, 0]),
+        ("and r0, 7",       3,          0,          [0, 0, 0, 0, 1, 0]),
+        ("or r0, r1",       1,          2,          [0, 0, 0, 0, 1, 0]),
+        ("or r0, 1",        0,          0,          [0, 0, 0, 0, 0, 0]),
+        ("xor r0, r1",      MAX,        MAX,        [0, 1, 0, 0, 1, 0]),
+        ("xor r0, 1",       MIN_SIGNED, 0,          [0, 0, 1, 0, 0, 0]),
+        ("test r0, r1",     0x0C,       4,          [0, 0, 0, 0, 0, 0]),
+        ("test r0, 1",      2,          0,          [0, 1, 0, 0, 1, 0]),
+        ("inc r0",          MAX,        0,          [1, 1, 0, 0, 1, 1]),
+        ("inc r0",          MAX_SIGNED, 0,          [1, 0, 1, 1, 1, 1]),
+        ("dec r0",          1,          0,          [1, 1, 0, 0, 1, 0]),
+        ("dec r0",          MIN_SIGNED, 0,          [1, 0, 0, 1, 1, 1]),
+        ("imul r0, r1",     3,          MAX,        [0, 0, 1, 0, 0, 0]),
+        ("imul r0, r1",     MIN_SIGNED, MAX,        [1, 0, 1, 1, 1, 0]),
+        ("imul r0, 2",      MAX_SIGNED, 0,          [1, 0, 1, 1, 0, 0]),
+        ("mov r0, r1",      0,          0,          [1, 1, 1, 1, 1, 1]),
+        ("xchg r0, r1",     1,          2,          [1, 1, 1, 1, 1, 1]),
+    ];
+
+    for (insn, lhs, rhs, [cf, zf, sf, of, pf, af]) in TABLE {
+        let rf = run_insn(insn, *lhs, *rhs);
+        let actual = [
+            rf.read_cf(),
+            rf.read_zf(),
+            rf.read_sf(),
+            rf.read_of(),
+            rf.read_pf(),
+            rf.read_af(),
+        ];
+        assert_eq!(
+            actual,
+            [*cf, *zf, *sf, *of, *pf, *af],
+            "{insn} with r0={lhs:#x}, r1={rhs:#x}"
+        );
+    }
+}
+
+#[test]
+fn dec_jnz_loop() {
+    const S: &str = "
+mov r0, 10
+xor r1, r1
+loop:
+    add r1, 3
+    dec r0
+    jnz loop
+exit
+";
+    let mut builder = Builder::new();
+    build_bytecode_s(S, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R1), 30u64);
+    assert_eq!(emulator.regs.read_rf().read_zf(), 1);
+}
+
+#[test]
+fn add_jb() {
+    const S: &str = "
+mov r0, 0FFFFFFFFFFFFFFFFh
+add r0, 2
+jb carry
+mov r1, 3
+exit
+carry:
+mov r1, 7
+exit
+";
+    let mut builder = Builder::new();
+    build_bytecode_s(S, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 1u64);
+    assert_eq!(emulator.regs.read(Register::R1), 7u64);
+    assert_eq!(emulator.regs.read_rf().read_cf(), 1);
+}
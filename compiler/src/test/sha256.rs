@@ -0,0 +1,93 @@
+use vm::emulator::Emulator;
+
+use crate::builder::{build_bytecode_s, Builder};
+
+/// Pads `msg` to a multiple of the 64-byte block size as per FIPS 180-4.
+fn sha256_pad(msg: &[u8]) -> Vec<u8> {
+    let mut buf = msg.to_vec();
+    buf.push(0x80);
+    while buf.len() % 64 != 56 {
+        buf.push(0);
+    }
+    buf.extend_from_slice(&((msg.len() as u64) * 8).to_be_bytes());
+    buf
+}
+
+fn generate_msg_source(msg: &[u8]) -> String {
+    let mut s = String::from("msg:\n");
+
+    for c in msg {
+        s += &format!("db {c}\n");
+    }
+
+    s
+}
+
+fn test_sha256(msg: &[u8], expect: &[u8; 32]) {
+    let buf = sha256_pad(msg);
+    let msg_src = generate_msg_source(&buf);
+    let s = format!(
+        "{}\n{msg_src}\n",
+        include_str!("sha256.S").replace("%NBLOCKS%", &format!("{}", buf.len() / 64))
+    );
+
+    let mut builder = Builder::new();
+    build_bytecode_s(s, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+
+    let state_loc = *builder.labels.get("state").unwrap() as usize;
+    assert_ne!(state_loc, 0);
+
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.execute().unwrap();
+
+    let state = &emulator.dram.0[state_loc..state_loc + 32];
+    let digest = state
+        .chunks(4)
+        .flat_map(|word| [word[3], word[2], word[1], word[0]])
+        .collect::<Vec<_>>();
+    assert_eq!(digest, expect);
+}
+
+#[test]
+fn empty() {
+    test_sha256(b"", &[
+        0xe3, 0xb0, 0xc4, 0x42, 0x98, 0xfc, 0x1c, 0x14, 0x9a, 0xfb, 0xf4, 0xc8, 0x99, 0x6f, 0xb9,
+        0x24, 0x27, 0xae, 0x41, 0xe4, 0x64, 0x9b, 0x93, 0x4c, 0xa4, 0x95, 0x99, 0x1b, 0x78, 0x52,
+        0xb8, 0x55,
+    ]);
+}
+
+#[test]
+fn abc() {
+    test_sha256(b"abc", &[
+        0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22,
+        0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00,
+        0x15, 0xad,
+    ]);
+}
+
+#[test]
+fn two_blocks() {
+    test_sha256(
+        b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+        &[
+            0x24, 0x8d, 0x6a, 0x61, 0xd2, 0x06, 0x38, 0xb8, 0xe5, 0xc0, 0x26, 0x93, 0x0c, 0x3e,
+            0x60, 0x39, 0xa3, 0x3c, 0xe4, 0x59, 0x64, 0xff, 0x21, 0x67, 0xf6, 0xec, 0xed, 0xd4,
+            0x19, 0xdb, 0x06, 0xc1,
+        ],
+    );
+}
+
+#[test]
+fn long() {
+    test_sha256(
+        b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+        &[
+            0xcf, 0x5b, 0x16, 0xa7, 0x78, 0xaf, 0x83, 0x80, 0x03, 0x6c, 0xe5, 0x9e, 0x7b, 0x04,
+            0x92, 0x37, 0x0b, 0x24, 0x9b, 0x11, 0xe8, 0xf0, 0x7a, 0x51, 0xaf, 0xac, 0x45, 0x03,
+            0x7a, 0xfe, 0xe9, 0xd1,
+        ],
+    );
+}
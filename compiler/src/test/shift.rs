@@ -0,0 +1,80 @@
+use vm::emulator::{Emulator, Register};
+
+use crate::builder::{build_bytecode_s, Builder};
+
+/// `(insn, lhs, rhs, CF, result, [CF, ZF, SF, OF, PF, AF])`
+type Case = (&'static str, u64, u64, u64, u64, [u64; 6]);
+
+/// Runs `insn` with `r0 = lhs`, `r1 = rhs` and CF set to `cf`, returning `r0`
+/// and the resulting flags.
+///
+/// All other flags are set before `insn` executes so that preserved flags can
+/// be told apart from cleared ones.
+fn run_insn(insn: &str, lhs: u64, rhs: u64, cf: u64) -> (u64, [u64; 6]) {
+    let s = format!("mov r0, {lhs}\nmov r1, {rhs}\n{insn}\nexit\n");
+    let mut builder = Builder::new();
+    build_bytecode_s(s, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let dump = builder.dump().unwrap();
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.single_step().unwrap();
+    emulator.single_step().unwrap();
+    let mut rf = emulator.regs.read_rf();
+    rf.write_cf(cf);
+    rf.write_zf(1);
+    rf.write_sf(1);
+    rf.write_of(1);
+    rf.write_pf(1);
+    rf.write_af(1);
+    emulator.regs.write_rf(rf);
+    emulator.execute().unwrap();
+    let rf = emulator.regs.read_rf();
+    (emulator.regs.read(Register::R0), [
+        rf.read_cf(),
+        rf.read_zf(),
+        rf.read_sf(),
+        rf.read_of(),
+        rf.read_pf(),
+        rf.read_af(),
+    ])
+}
+
+#[test]
+fn shift() {
+    const MAX: u64 = u64::MAX;
+    const MIN_SIGNED: u64 = 1 << 63;
+
+    #[rustfmt::skip]
+    const TABLE: &[Case] = &[
+        // insn         lhs                     rhs CF  result                  CF ZF SF OF PF AF
+        ("shl r0, 1",   MIN_SIGNED | 1,         0,  0,  2,                      [1, 0, 0, 1, 0, 0]),
+        ("shl r0, 4",   0x0F,                   0,  1,  0xF0,                   [0, 0, 0, 0, 1, 0]),
+        ("shl r0, 63",  3,                      0,  0,  MIN_SIGNED,             [1, 0, 1, 0, 1, 0]),
+        ("shl r0, r1",  5,                      64, 1,  5,                      [1, 1, 1, 1, 1, 1]),
+        ("sal r0, r1",  1,                      3,  1,  8,                      [0, 0, 0, 0, 0, 0]),
+        ("shr r0, 1",   MIN_SIGNED | 1,         0,  0,  MIN_SIGNED >> 1,        [1, 0, 0, 1, 1, 0]),
+        ("shr r0, r1",  0x10,                   5,  0,  0,                      [1, 1, 0, 0, 1, 0]),
+        ("sar r0, 1",   MAX,                    0,  0,  MAX,                    [1, 0, 1, 0, 1, 0]),
+        ("sar r0, 4",   MIN_SIGNED | 0x10,      0,  1,  0xF800000000000001,     [0, 0, 1, 0, 0, 0]),
+        ("sar r0, r1",  0x7F,                   65, 0,  0x3F,                   [1, 0, 0, 0, 1, 0]),
+        ("rol r0, 1",   MIN_SIGNED,             0,  0,  1,                      [1, 1, 1, 1, 1, 1]),
+        ("rol r0, 4",   0x0123456789ABCDEF,     0,  1,  0x123456789ABCDEF0,     [0, 1, 1, 0, 1, 1]),
+        ("ror r0, 1",   1,                      0,  0,  MIN_SIGNED,             [1, 1, 1, 1, 1, 1]),
+        ("ror r0, r1",  0x0123456789ABCDEF,     4,  0,  0xF0123456789ABCDE,     [1, 1, 1, 0, 1, 1]),
+        ("rcl r0, 1",   MIN_SIGNED,             0,  0,  0,                      [1, 1, 1, 1, 1, 1]),
+        ("rcl r0, 1",   0,                      0,  1,  1,                      [0, 1, 1, 0, 1, 1]),
+        ("rcl r0, r1",  0x0F,                   63, 1,  0xC000000000000003,     [1, 1, 1, 0, 1, 1]),
+        ("rcr r0, 1",   1,                      0,  0,  0,                      [1, 1, 1, 0, 1, 1]),
+        ("rcr r0, 1",   0,                      0,  1,  MIN_SIGNED,             [0, 1, 1, 1, 1, 1]),
+        ("rcr r0, r1",  3,                      0,  0,  3,                      [0, 1, 1, 1, 1, 1]),
+    ];
+
+    for (insn, lhs, rhs, cf, result, flags) in TABLE {
+        let actual = run_insn(insn, *lhs, *rhs, *cf);
+        assert_eq!(
+            actual,
+            (*result, *flags),
+            "{insn} with r0={lhs:#x}, r1={rhs:#x}, CF={cf}"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use vm::emulator::{Emulator, Register, VectorRegister};
+
+use super::run;
+use crate::builder::{build_bytecode_s, Builder};
+
+const LHS: u128 = 0xFFFFFFFFFFFFFFFF_00000000FFFF00FF;
+const RHS: u128 = 0xFFFFFFFF00000001_00000000FFFF0101;
+
+fn test_packed(mnemonic: &str, expected: u128) {
+    let s = format!(
+        "
+mov r0, offsetof data
+movdqu v0, xmmword [r0]
+mov r1, 16
+movdqu v1, xmmword [r0 + r1]
+{mnemonic} v0, v1
+exit
+
+data:
+    dq 0{:X}h
+    dq 0{:X}h
+    dq 0{:X}h
+    dq 0{:X}h
+",
+        LHS as u64,
+        (LHS >> 64) as u64,
+        RHS as u64,
+        (RHS >> 64) as u64
+    );
+    let emulator = run(&s);
+    assert_eq!(
+        emulator.vregs.read(VectorRegister::V0),
+        expected,
+        "{mnemonic}: {:032x}",
+        emulator.vregs.read(VectorRegister::V0)
+    );
+    // The source operand is left untouched
+    assert_eq!(emulator.vregs.read(VectorRegister::V1), RHS);
+}
+
+#[test]
+fn arithmetic() {
+    test_packed("paddb", 0xFEFEFEFEFFFFFF00_00000000FEFE0100);
+    test_packed("paddw", 0xFFFEFFFEFFFF0000_00000000FFFE0200);
+    test_packed("paddd", 0xFFFFFFFE00000000_00000000FFFE0200);
+    test_packed("paddq", 0xFFFFFFFF00000000_00000001FFFE0200);
+    test_packed("psubb", 0x00000000FFFFFFFE_000000000000FFFE);
+    test_packed("psubw", 0x00000000FFFFFFFE_000000000000FFFE);
+    test_packed("psubd", 0x00000000FFFFFFFE_00000000FFFFFFFE);
+    test_packed("psubq", 0x00000000FFFFFFFE_FFFFFFFFFFFFFFFE);
+}
+
+#[test]
+fn logic() {
+    test_packed("pand", LHS & RHS);
+    test_packed("por", LHS | RHS);
+    test_packed("pxor", LHS ^ RHS);
+}
+
+#[test]
+fn compare() {
+    test_packed("pcmpeqb", 0xFFFFFFFF00000000_FFFFFFFFFFFF0000);
+    test_packed("pcmpeqw", 0xFFFFFFFF00000000_FFFFFFFFFFFF0000);
+    test_packed("pcmpeqd", 0xFFFFFFFF00000000_FFFFFFFF00000000);
+    test_packed("pcmpeqq", 0);
+}
+
+#[test]
+fn shuffle() {
+    const S: &str = "
+mov r0, offsetof data
+movdqu v0, xmmword [r0]
+mov r1, 16
+movdqu v1, xmmword [r0 + r1]
+pshufb v0, v1
+mov r1, 32
+movdqu xmmword [r0 + r1], v0
+mov r2, qword [r0 + r1]
+exit
+
+data:
+    dq 0706050403020100h
+    dq 0F0E0D0C0B0A0908h
+    ; Reverse the low half and zero the high half
+    dq 08090A0B0C0D0E0Fh
+    dq 8080808080808080h
+    dq 0
+    dq 0
+";
+    let emulator = run(S);
+    assert_eq!(emulator.vregs.read(VectorRegister::V0), 0x08090A0B0C0D0E0F);
+    assert_eq!(emulator.regs.read(Register::R2), 0x08090A0B0C0D0E0F);
+}
+
+#[test]
+fn movq() {
+    const S: &str = "
+mov r0, 1234h
+movq v0, r0
+pcmpeqb v1, v1
+paddq v1, v0
+movdqu v2, v1
+movq r1, v2
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.vregs.read(VectorRegister::V0), 0x1234);
+    assert_eq!(
+        emulator.vregs.read(VectorRegister::V2),
+        0xFFFFFFFFFFFFFFFF_0000000000001233
+    );
+    assert_eq!(emulator.regs.read(Register::R1), 0x1233);
+}
+
+#[test]
+fn unsupported() {
+    for s in [
+        "movdqu v0, qword [r0]",
+        "movdqu v0, r0",
+        "paddb v0, xmmword [r0]",
+        "movq v0, v1",
+        "add r0, xmmword [r1]",
+    ] {
+        let mut builder = Builder::new();
+        let ok = build_bytecode_s(s, &mut builder).is_ok();
+        // Integer operators reject an `xmmword` operand at runtime
+        if s.starts_with("add") {
+            assert!(ok, "{s}");
+            builder.finalize().unwrap();
+            let mut emulator = Emulator::with_bytecode(builder.dump().unwrap());
+            assert!(emulator.execute().is_err(), "{s}");
+        } else {
+            assert!(!ok, "{s}");
+        }
+    }
+}
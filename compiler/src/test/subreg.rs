@@ -0,0 +1,253 @@
+use vm::emulator::{Emulator, FloatRegister, Register};
+
+use super::run;
+use crate::builder::{build_bytecode_s, Builder};
+
+#[test]
+fn mov() {
+    const S: &str = "
+mov r0, 1122334455667788h
+mov r1, r0
+mov r2, r0
+mov r3, r0
+mov r0b, 0AAh
+mov r1w, 0BBBBh
+mov r2d, 0CCCCCCCCh
+mov r4, r3d
+mov r5, r3w
+xor r6, r6
+mov r6b, r3w
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R0), 0x11223344556677AA);
+    assert_eq!(emulator.regs.read(Register::R1), 0x112233445566BBBB);
+    assert_eq!(emulator.regs.read(Register::R2), 0xCCCCCCCC);
+    assert_eq!(emulator.regs.read(Register::R4), 0x55667788);
+    assert_eq!(emulator.regs.read(Register::R5), 0x7788);
+    assert_eq!(emulator.regs.read(Register::R6), 0x88);
+    assert_eq!(emulator.regs.read(Register::R0B), 0xAA);
+    assert_eq!(emulator.regs.read(Register::R1W), 0xBBBB);
+}
+
+#[test]
+fn add() {
+    const S: &str = "
+mov r0, 1FFh
+add r0b, 1
+mov r1, 7Fh
+add r1b, 1
+mov r2, 0FFFFFFFFFFFFFFFFh
+add r2d, 1
+mov r3, 0FF00h
+mov r4, 0F0h
+mov r5, 20h
+add r4b, r5b
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R0), 0x100);
+    assert_eq!(emulator.regs.read(Register::R1), 0x80);
+    assert_eq!(emulator.regs.read(Register::R2), 0);
+    assert_eq!(emulator.regs.read(Register::R4), 0x10);
+    assert_eq!(emulator.regs.read_rf().read_cf(), 1);
+    assert_eq!(emulator.regs.read_rf().read_zf(), 0);
+
+    let emulator = run("mov r0, 1FFh\nadd r0b, 1\nexit");
+    assert_eq!(emulator.regs.read_rf().read_cf(), 1);
+    assert_eq!(emulator.regs.read_rf().read_zf(), 1);
+
+    let emulator = run("mov r1, 7Fh\nadd r1b, 1\nexit");
+    assert_eq!(emulator.regs.read_rf().read_of(), 1);
+    assert_eq!(emulator.regs.read_rf().read_sf(), 1);
+}
+
+#[test]
+fn shift() {
+    const S: &str = "
+mov r0, 0FF80h
+sar r0b, 4
+mov r1, 1
+shl r1d, 31
+mov r2, r1
+shl r2d, 1
+mov r3, 81h
+rol r3b, 1
+mov r4, 1
+rcr r4w, 1
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R0), 0xFFF8);
+    assert_eq!(emulator.regs.read(Register::R1), 0x80000000);
+    assert_eq!(emulator.regs.read(Register::R2), 0);
+    assert_eq!(emulator.regs.read(Register::R3), 0x03);
+    // CF from `rol` is rotated into the top of the word
+    assert_eq!(emulator.regs.read(Register::R4), 0x8000);
+    assert_eq!(emulator.regs.read_rf().read_cf(), 1);
+}
+
+#[test]
+fn mul_div() {
+    const S: &str = "
+mov r0, 100
+imul r0b, 2
+mov r1, 0F0h
+mov r2, 10h
+mul r1b, r2b
+mov r3, 0F9h
+mov r4, 2
+idiv r3b, r4b
+mov r5, 0F9h
+irem r5b, r4b
+mov r6, 0FFFFFFFFFFFF0007h
+div r6w, r4w
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R0), 0xC8);
+    assert_eq!(emulator.regs.read(Register::R1), 0x00);
+    assert_eq!(emulator.regs.read(Register::R2), 0x0F);
+    assert_eq!(emulator.regs.read(Register::R3), 0xFD);
+    assert_eq!(emulator.regs.read(Register::R5), 0xFF);
+    assert_eq!(emulator.regs.read(Register::R6), 0xFFFFFFFFFFFF0003);
+
+    let emulator = run("mov r0, 100\nimul r0b, 2\nexit");
+    assert_eq!(emulator.regs.read_rf().read_cf(), 1);
+    assert_eq!(emulator.regs.read_rf().read_of(), 1);
+
+    let emulator = run("mov r0, 0FFFFh\nimul r0w, 0FFFFh\nexit");
+    assert_eq!(emulator.regs.read(Register::R0), 1);
+    assert_eq!(emulator.regs.read_rf().read_cf(), 0);
+}
+
+#[test]
+fn bswap() {
+    const S: &str = "
+mov r0, 1122334455667788h
+mov r1, r0
+bswap r0d
+bswap r1w
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R0), 0x88776655);
+    assert_eq!(emulator.regs.read(Register::R1), 0x1122334455668877);
+}
+
+#[test]
+fn cmov() {
+    const S: &str = "
+mov r0, -1
+mov r1, r0
+mov r2, r0
+cmp r3, r3
+cmovnz r0d, r3d
+cmovz r1d, r3d
+cmovnz r2w, r3w
+exit
+";
+    let emulator = run(S);
+    // A 32-bit destination is zero-extended even if the condition fails
+    assert_eq!(emulator.regs.read(Register::R0), 0xFFFFFFFF);
+    assert_eq!(emulator.regs.read(Register::R1), 0);
+    assert_eq!(emulator.regs.read(Register::R2), 0xFFFFFFFFFFFFFFFF);
+}
+
+#[test]
+fn movx() {
+    const S: &str = "
+mov r0, 12345678FEDC7F80h
+movzx r1, r0b
+movsx r2, r0b
+movsx r3, r0w
+movsx r4, r0d
+movzx r5d, r0w
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R1), 0x80);
+    assert_eq!(emulator.regs.read(Register::R2), 0xFFFFFFFFFFFFFF80);
+    assert_eq!(emulator.regs.read(Register::R3), 0x7F80);
+    assert_eq!(emulator.regs.read(Register::R4), 0xFFFFFFFFFEDC7F80);
+    assert_eq!(emulator.regs.read(Register::R5), 0x7F80);
+}
+
+#[test]
+fn float() {
+    const S: &str = "
+mov r0, 0FFFFFFFFh
+cvtsi2f f0, r0d
+fmov f1, 3.0e9
+cvtf2si r1d, f1
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.fregs.read(FloatRegister::F0), -1.0);
+    assert_eq!(emulator.regs.read(Register::R1), 0x80000000);
+    assert_eq!(emulator.regs.read_rf().read_ie(), 1);
+}
+
+#[test]
+fn ksa() {
+    // The key scheduling of RC4, with the index arithmetic done on bytes
+    const S: &str = "
+    mov r0, offsetof key
+    mov r2, offsetof sbox
+    xor r3, r3
+init:
+    mov byte [r2 + r3], r3
+    inc r3
+    cmp r3, 256
+    jnz init
+
+    xor r3, r3
+    xor r5, r5
+loop:
+    mov r4, byte [r2 + r3]
+    add r5b, r4b
+    mov r6, r3
+    and r6, 3
+    mov r7, byte [r0 + r6]
+    add r5b, r7b
+    mov r7, byte [r2 + r5]
+    mov byte [r2 + r3], r7
+    mov byte [r2 + r5], r4
+    inc r3b
+    jnz loop
+    exit
+
+key:
+    dq 04030201h
+sbox:
+";
+    let mut builder = Builder::new();
+    build_bytecode_s(S, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    let mut dump = builder.dump().unwrap();
+    let sbox = dump.len();
+    dump.resize(sbox + 256, 0);
+    let mut emulator = Emulator::with_bytecode(dump);
+    emulator.execute().unwrap();
+
+    let mut expected: Vec<u8> = (0..=255).collect();
+    let key = [1u8, 2, 3, 4];
+    let mut j = 0u8;
+    for i in 0..256 {
+        j = j.wrapping_add(expected[i]).wrapping_add(key[i % 4]);
+        expected.swap(i, j as usize);
+    }
+    for (i, x) in expected.iter().enumerate() {
+        assert_eq!(emulator.dram.read_u8(sbox + i).unwrap(), *x);
+    }
+}
+
+#[test]
+fn immediate_overflow() {
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("mov r0b, 100h", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("add r0w, 10000h", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("mov r0d, 0FFFFFFFFh", &mut builder).is_ok());
+}
@@ -0,0 +1,205 @@
+use vm::{emulator::Register, exception::Exception};
+
+use super::{build, run};
+use crate::builder::{build_bytecode_s, Builder};
+
+fn len(s: &str) -> usize {
+    let mut builder = Builder::new();
+    build_bytecode_s(s, &mut builder).unwrap();
+    builder.finalize().unwrap();
+    builder.dump().unwrap().len()
+}
+
+#[test]
+fn r_r_r() {
+    const S: &str = "
+mov r14, 0F0h
+mov r15, 3Ch
+add r0, r14, r15
+sub r1, r15, r14
+and r2, r14, r15
+or r3, r14, r15
+xor r4, r14, r15
+imul r5, r14, r15
+mov r13, 4
+shl r6, r14, r13
+shr r7, r14, r13
+sub r8, r13, r14
+sar r8, r8, r13
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R0), 0x12C);
+    assert_eq!(emulator.regs.read(Register::R1), -0xB4i64 as u64);
+    assert_eq!(emulator.regs.read(Register::R2), 0x30);
+    assert_eq!(emulator.regs.read(Register::R3), 0xFC);
+    assert_eq!(emulator.regs.read(Register::R4), 0xCC);
+    assert_eq!(emulator.regs.read(Register::R5), 0x3840);
+    assert_eq!(emulator.regs.read(Register::R6), 0xF00);
+    assert_eq!(emulator.regs.read(Register::R7), 0x0F);
+    assert_eq!(emulator.regs.read(Register::R8), -0xFi64 as u64);
+    // Sources are left untouched
+    assert_eq!(emulator.regs.read(Register::R14), 0xF0);
+    assert_eq!(emulator.regs.read(Register::R15), 0x3C);
+}
+
+#[test]
+fn r_r_imm() {
+    const S: &str = "
+mov r15, 0F0h
+add r0, r15, 3Ch
+sub r1, r15, 0F1h
+and r2, r15, 3Ch
+or r3, r15, 3Ch
+xor r4, r15, 3Ch
+imul r5, r15, 3
+shl r6, r15, 4
+shr r7, r15, 4
+sar r8, r1, 63
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R0), 0x12C);
+    assert_eq!(emulator.regs.read(Register::R1), u64::MAX);
+    assert_eq!(emulator.regs.read(Register::R2), 0x30);
+    assert_eq!(emulator.regs.read(Register::R3), 0xFC);
+    assert_eq!(emulator.regs.read(Register::R4), 0xCC);
+    assert_eq!(emulator.regs.read(Register::R5), 0x2D0);
+    assert_eq!(emulator.regs.read(Register::R6), 0xF00);
+    assert_eq!(emulator.regs.read(Register::R7), 0x0F);
+    assert_eq!(emulator.regs.read(Register::R8), u64::MAX);
+}
+
+#[test]
+fn carry_mul_div_rotate() {
+    const S: &str = "
+mov r14, 0F0h
+mov r15, 7
+cmp r12, 1
+adc r0, r14, r15
+cmp r12, 1
+sbb r1, r14, r15
+cmp r12, 1
+adc r2, r14, 1
+cmp r12, 1
+sbb r3, r14, 1
+mov r13, 0FFFFFFFFFFFFFFFFh
+mulh r4, r13, r15
+imulh r5, r13, r15
+mulh r6, r13, 10h
+imulh r7, r13, 10h
+div r8, r14, r15
+idiv r9, r13, r15
+rem r10, r14, r15
+irem r11, r13, 3
+exit
+";
+    let emulator = run(S);
+    // cmp r12, 1 sets CF right before adc and sbb
+    assert_eq!(emulator.regs.read(Register::R0), 0xF8);
+    assert_eq!(emulator.regs.read(Register::R1), 0xE8);
+    assert_eq!(emulator.regs.read(Register::R2), 0xF2);
+    assert_eq!(emulator.regs.read(Register::R3), 0xEE);
+    assert_eq!(emulator.regs.read(Register::R4), 6);
+    assert_eq!(emulator.regs.read(Register::R5), u64::MAX);
+    assert_eq!(emulator.regs.read(Register::R6), 0x0F);
+    assert_eq!(emulator.regs.read(Register::R7), u64::MAX);
+    assert_eq!(emulator.regs.read(Register::R8), 0x22);
+    assert_eq!(emulator.regs.read(Register::R9), 0);
+    assert_eq!(emulator.regs.read(Register::R10), 2);
+    assert_eq!(emulator.regs.read(Register::R11), u64::MAX);
+    // Sources are left untouched
+    assert_eq!(emulator.regs.read(Register::R13), u64::MAX);
+    assert_eq!(emulator.regs.read(Register::R14), 0xF0);
+    assert_eq!(emulator.regs.read(Register::R15), 7);
+
+    const T: &str = "
+mov r14, 8000000000000001h
+mov r15, 4
+rol r0, r14, r15
+ror r1, r14, 4
+cmp r12, 1
+rcl r2, r14, 1
+cmp r12, 1
+rcr r3, r14, r15
+mov r4, 100
+mov r5, 0
+div r4, r14, r5
+exit
+";
+    let mut emulator = build(T);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::DivideByZero)
+    ));
+    assert_eq!(emulator.regs.read(Register::R0), 0x18);
+    assert_eq!(emulator.regs.read(Register::R1), 0x1800000000000000);
+    assert_eq!(emulator.regs.read(Register::R2), 3);
+    assert_eq!(emulator.regs.read(Register::R3), 0x3800000000000000);
+    assert_eq!(emulator.regs.read(Register::R4), 100);
+}
+
+#[test]
+fn flags() {
+    let emulator = run("mov r1, 1\nmov r2, 2\nsub r0, r1, r2\nexit");
+    assert_eq!(emulator.regs.read_rf().read_cf(), 1);
+    assert_eq!(emulator.regs.read_rf().read_sf(), 1);
+
+    let emulator = run("mov r1, 0FFh\nadd r0b, r1b, 1\nexit");
+    assert_eq!(emulator.regs.read(Register::R0), 0);
+    assert_eq!(emulator.regs.read_rf().read_cf(), 1);
+    assert_eq!(emulator.regs.read_rf().read_zf(), 1);
+
+    let emulator = run("mov r1, 1\nmov r2, 2\nxor r0, r1, r2\nexit");
+    assert_eq!(emulator.regs.read_rf().read_zf(), 0);
+    assert_eq!(emulator.regs.read_rf().read_cf(), 0);
+}
+
+#[test]
+fn two_operand_form() {
+    // The destination is also a source, so the shorter form is chosen
+    assert_eq!(len("add r0, r0, r1"), len("add r0, r1"));
+    assert_eq!(len("add r0, r0, 1"), len("add r0, 1"));
+    assert_eq!(len("imul r0, r1, r0"), len("imul r0, r1"));
+    assert!(len("add r0, r1, r2") > len("add r0, r1"));
+    assert!(len("sub r0, r1, r0") > len("sub r0, r1"));
+    assert_eq!(len("mulh r0, r1, r0"), len("mulh r0, r1"));
+    assert!(len("div r0, r1, r0") > len("div r0, r1"));
+
+    const S: &str = "
+mov r0, 5
+mov r1, 7
+sub r0, r1, r0
+mov r2, 3
+imul r2, r1, r2
+exit
+";
+    let emulator = run(S);
+    assert_eq!(emulator.regs.read(Register::R0), 2);
+    assert_eq!(emulator.regs.read(Register::R2), 21);
+}
+
+#[test]
+fn encoding() {
+    // An immediate third operand is marked with FFh in place of a register
+    assert_eq!(len("add r0, r1, r2"), 4);
+    assert_eq!(len("add r0, r1, 5"), 12);
+    assert_eq!(len("rcr r0, r1, r2"), 4);
+    assert_eq!(len("rcr r0, r1, 5"), 12);
+}
+
+#[test]
+fn invalid_operands() {
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("add r0, r1, qword [r2]", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("add [r0], r1, r2", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("mov r0, r1, r2", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("mul r0, r1, r2", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("add r0b, r1, 100h", &mut builder).is_err());
+    let mut builder = Builder::new();
+    assert!(build_bytecode_s("add r0, r1, r2, r3", &mut builder).is_err());
+}
@@ -0,0 +1,112 @@
+use vm::{emulator::Register, exception::Exception};
+
+use super::build;
+use crate::builder::{build_bytecode_s, Builder};
+
+#[test]
+fn not() {
+    const S: &str = "
+mov r0, 0F0F0h
+cmp r0, 0F0F0h
+not r0
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), !0xF0F0);
+    // Flags are left untouched
+    let rf = emulator.regs.read_rf();
+    assert_eq!(rf.read_zf(), 1);
+}
+
+fn test_neg(input: u64, cf: u64, of: u64, zf: u64, sf: u64) {
+    let s = format!(
+        "
+mov r0, {input}
+neg r0
+exit
+"
+    );
+    let mut emulator = build(&s);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), input.wrapping_neg());
+    let rf = emulator.regs.read_rf();
+    assert_eq!(rf.read_cf(), cf, "neg {input:#x}: CF");
+    assert_eq!(rf.read_of(), of, "neg {input:#x}: OF");
+    assert_eq!(rf.read_zf(), zf, "neg {input:#x}: ZF");
+    assert_eq!(rf.read_sf(), sf, "neg {input:#x}: SF");
+}
+
+#[test]
+fn neg() {
+    test_neg(0, 0, 0, 1, 0);
+    test_neg(1, 1, 0, 0, 1);
+    test_neg(0xFFFFFFFFFFFFFFFF, 1, 0, 0, 0);
+    test_neg(0x8000000000000000, 1, 1, 0, 1);
+}
+
+#[test]
+fn not_neg_rm() {
+    const S: &str = "
+mov r6, offsetof data
+mov r5, 2
+not byte [r6]
+neg word [r6 + r5]
+exit
+
+data:
+    dq 0FFFF8001h
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    let data = emulator.regs.read(Register::R6) as usize;
+    assert_eq!(emulator.dram.read_u64le(data).unwrap(), 0x0001_80FE);
+    let rf = emulator.regs.read_rf();
+    assert_eq!(rf.read_cf(), 1);
+    assert_eq!(rf.read_zf(), 0);
+    assert_eq!(rf.read_sf(), 0);
+}
+
+#[test]
+fn nop() {
+    let mut builder = Builder::new();
+    build_bytecode_s("nop\nnop\nnop", &mut builder).unwrap();
+    builder.finalize().unwrap();
+    assert_eq!(builder.dump().unwrap().len(), 3);
+
+    const S: &str = "
+mov r0, 1
+nop
+nop
+add r0, 1
+exit
+";
+    let mut emulator = build(S);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 2);
+}
+
+#[test]
+fn hlt() {
+    const S: &str = "
+mov r0, 1
+hlt
+mov r0, 2
+hlt
+mov r0, 3
+exit
+";
+    let mut emulator = build(S);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::Halt)
+    ));
+    assert_eq!(emulator.regs.read(Register::R0), 1);
+    assert!(matches!(
+        emulator.execute().map_err(|e| e.kind),
+        Err(Exception::Halt)
+    ));
+    assert_eq!(emulator.regs.read(Register::R0), 2);
+    emulator.execute().unwrap();
+    assert_eq!(emulator.regs.read(Register::R0), 3);
+}
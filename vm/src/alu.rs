@@ -8,10 +8,88 @@
 //! This software-based ALU simulates these operations sequentially. Those
 //! handlers are not thread-safe and may cause data races if accessed
 //! concurrently.
+//!
+//! ## Flags
+//! Arithmetic and logic handlers update the [`RFlags`] following the x86
+//! rules:
+//! - `add`, `sub`, `cmp`: CF, ZF, SF, OF, PF and AF reflect the result.
+//! - `inc`, `dec`: Same as `add`/`sub` by one, but CF is preserved.
+//! - `neg`: Same as `sub` from zero, so CF is set unless the operand is zero.
+//! - `and`, `or`, `xor`, `test`: ZF, SF and PF reflect the result. CF, OF and
+//!   AF are cleared.
+//! - `rep cmpsb`: Same as `cmp` on the last pair of bytes compared. Flags are
+//!   left untouched if the count is zero.
+//! - `adc`, `sbb`: Same as `add`/`sub`, with CF added to the sum or subtracted
+//!   as a borrow.
+//! - `imul`: CF and OF are set if the signed result is truncated. ZF, SF and PF
+//!   reflect the truncated result and AF is cleared.
+//! - `mul`: CF and OF are set if the high half of the product is not zero. ZF,
+//!   SF and PF reflect the low half and AF is cleared.
+//! - `mulh`, `imulh`: CF and OF are set if the product does not fit in the
+//!   operand width. ZF, SF and PF reflect the high half and AF is cleared.
+//! - `div`, `idiv`, `rem`, `irem`: Flags are left untouched.
+//! - `shl`, `shr`, `sar`: CF holds the last bit shifted out. ZF, SF and PF
+//!   reflect the result and AF is cleared. OF is the MSB of the result XOR CF
+//!   for `shl`, the original MSB for `shr` and cleared for `sar`.
+//! - `rol`, `ror`, `rcl`, `rcr`: CF holds the last bit rotated out. OF is the
+//!   MSB of the result XOR CF for left rotations and the XOR of the two most
+//!   significant bits of the result for right rotations. Other flags are left
+//!   untouched.
+//! - `popcnt`: ZF is set if the source is zero. Other flags are cleared.
+//! - `lzcnt`, `tzcnt`: CF is set if the source is zero and ZF is set if the
+//!   result is zero. Other flags are cleared.
+//! - `bt`, `bts`, `btr`, `btc`: CF holds the selected bit before it is
+//!   modified. Other flags are left untouched.
+//! - `mov`, `xchg`, `cmovcc`, `setcc`, `bswap`, `not`, `rep movsb`, `rep
+//!   stosb`, stack and branch operators leave the flags untouched.
+//!
+//! The non-destructive forms `add r0, r1, r2` and `add r0, r1, imm` of the
+//! binary operators, i.e. `add`, `adc`, `sub`, `sbb`, `and`, `or`, `xor`,
+//! `imul`, `mulh`, `imulh`, `div`, `idiv`, `rem`, `irem` and the shifts and
+//! rotates, store the result of the last two operands in the first one, and
+//! update the flags exactly like their two-operand counterparts. `mul`, which
+//! writes two registers, has no such form.
+//!
+//! Shift and rotate counts are masked to 6 bits for 64-bit operands and to 5
+//! bits otherwise, and a zero count leaves both the operand and the flags
+//! untouched. Bit offsets of `bt`, `bts`, `btr` and `btc` are taken modulo the
+//! operand width.
+//!
+//! Division by zero raises [`Exception::DivideByZero`]. Unlike x86, dividing
+//! the most negative value by -1 does not fault: `idiv` wraps around to the
+//! dividend and `irem` yields 0.
+//!
+//! ## Sub-registers
+//! `R0B`, `R0W` and `R0D` name the low 8, 16 and 32 bits of `R0`, and likewise
+//! for `R1` to `R15`. Reading a sub-register zero-extends it. Writing an 8 or
+//! 16-bit sub-register merges into the full register, while writing a 32-bit
+//! one zero-extends into it, as on x86-64. Register forms operate at the width
+//! of the destination: the source is truncated to it, and the flags reflect
+//! the result at that width.
+//!
+//! ## Block operators
+//! `rep movsb`, `rep stosb` and `rep cmpsb` follow the argument order of
+//! `memcpy`, `memset` and `memcmp`: `R0` holds the destination (or the first
+//! buffer), `R1` the source (or the fill byte, or the second buffer) and `R2`
+//! the count. They operate on the whole range at once, so an out-of-bounds
+//! range raises [`Exception::AccessViolation`] before any byte is touched.
+//! Afterwards `R0` and `R1` point past the processed bytes and `R2` holds the
+//! remaining count, which is only non-zero if `rep cmpsb` found a mismatch.
+//! Overlapping ranges are copied as if through a temporary buffer, and a zero
+//! count is a no-op regardless of the addresses.
+//!
+//! ## Memory operands
+//! A memory source operand is zero-extended to 64 bits when the destination is
+//! a register, except for `movsx` which sign-extends it. When the destination
+//! is a memory operand, the register or immediate source is truncated to its
+//! width and both the result and the flags are computed at that width, e.g.
+//! `add byte [r0], 1` on `0FFh` stores `0` and sets CF and ZF. An `xmmword`
+//! operand is reserved for vector operators and raises an
+//! [`Exception::IllegalInstruction`].
 
 use crate::{
-    emulator::{Emulator, Register},
-    exception::Exception,
+    emulator::{Emulator, RFlags, Register},
+    exception::{AccessKind, Exception, MemoryAccess},
     isa::{Instruction, Operand, OperandSize},
 };
 
@@ -23,120 +101,765 @@ macro_rules! define_handler_trait {
     };
 }
 
-fn filter_special_reg(reg: Register) -> Option<Register> {
+pub(crate) use define_handler_trait;
+
+pub(crate) fn filter_special_reg(reg: Register) -> Option<Register> {
     (!matches!(reg, Register::IP)).then_some(reg)
 }
 
-fn handle_memop_read(
-    emulator: &mut Emulator,
+/// Computes the effective address of the specified memory operand.
+///
+/// The address is `base + index * scale + displacement` with a signed
+/// displacement and wraps around on overflow, so a negative displacement or
+/// index register addresses below the base.
+pub(crate) fn effective_address(
+    emulator: &Emulator,
     op: usize,
     insn: &Instruction,
 ) -> Result<u64, Exception> {
     match insn.operands[op] {
         Operand::Memory {
-            size,
+            size: _,
             displacement,
             scale,
             index_reg,
             base_reg,
         } => {
-            let mut address = displacement;
+            let mut address = displacement as u64;
             if let Some(base_reg) = base_reg {
-                address += emulator.regs.read(base_reg);
+                address = address.wrapping_add(emulator.regs.read(base_reg));
             }
             if let Some(index_reg) = index_reg {
                 if !matches!(scale, 1 | 2 | 4 | 8) {
                     return Err(Exception::IllegalInstruction);
                 }
-                address += emulator.regs.read(index_reg) * (scale as u64);
+                let index = emulator.regs.read(index_reg).wrapping_mul(scale as u64);
+                address = address.wrapping_add(index);
             }
 
-            Ok(match size {
-                OperandSize::Byte => emulator.dram.read_u8(address as usize)? as u64,
-                OperandSize::Word => emulator.dram.read_u16le(address as usize)? as u64,
-                OperandSize::DWord => emulator.dram.read_u32le(address as usize)? as u64,
-                OperandSize::QWord => emulator.dram.read_u64le(address as usize)?,
-            })
+            Ok(address)
         }
-        _ => return Err(Exception::IllegalInstruction),
+        _ => Err(Exception::IllegalInstruction),
     }
 }
 
-fn handle_memop_write(
+/// Computes the address of the specified memory operand in the DRAM, which is
+/// accessed as `kind`.
+///
+/// An effective address beyond the host address space raises an
+/// [`Exception::AccessViolation`] instead of being truncated.
+pub(crate) fn memop_address(
+    emulator: &Emulator,
+    op: usize,
+    insn: &Instruction,
+    kind: AccessKind,
+) -> Result<usize, Exception> {
+    let address = effective_address(emulator, op, insn)?;
+    let size = memop_size(insn, op)?.to_size();
+    usize::try_from(address).map_err(|_| {
+        Exception::AccessViolation(MemoryAccess {
+            address,
+            kind,
+            size,
+        })
+    })
+}
+
+/// Reads the value of the specified memory operand from the DRAM.
+pub(crate) fn handle_memop_read(
+    emulator: &mut Emulator,
+    op: usize,
+    insn: &Instruction,
+) -> Result<u64, Exception> {
+    let address = memop_address(emulator, op, insn, AccessKind::Read)?;
+
+    Ok(match memop_size(insn, op)? {
+        OperandSize::Byte => emulator.dram.read_u8(address)? as u64,
+        OperandSize::Word => emulator.dram.read_u16le(address)? as u64,
+        OperandSize::DWord => emulator.dram.read_u32le(address)? as u64,
+        OperandSize::QWord => emulator.dram.read_u64le(address)?,
+        OperandSize::XmmWord => return Err(Exception::IllegalInstruction),
+    })
+}
+
+/// Writes `value` to the specified memory operand in the DRAM.
+pub(crate) fn handle_memop_write(
     emulator: &mut Emulator,
     op: usize,
     insn: &Instruction,
     value: u64,
 ) -> Result<(), Exception> {
+    let address = memop_address(emulator, op, insn, AccessKind::Write)?;
+
+    match memop_size(insn, op)? {
+        OperandSize::Byte => emulator.dram.write_u8(address, value as u8),
+        OperandSize::Word => emulator.dram.write_u16le(address, value as u16),
+        OperandSize::DWord => emulator.dram.write_u32le(address, value as u32),
+        OperandSize::QWord => emulator.dram.write_u64le(address, value),
+        OperandSize::XmmWord => Err(Exception::IllegalInstruction),
+    }
+}
+
+/// Returns the memory [`OperandSize`] of the specified operand.
+pub(crate) fn memop_size(insn: &Instruction, op: usize) -> Result<OperandSize, Exception> {
     match insn.operands[op] {
-        Operand::Memory {
-            size,
-            displacement,
-            scale,
-            index_reg,
-            base_reg,
-        } => {
-            let mut address = displacement;
-            if let Some(base_reg) = base_reg {
-                address += emulator.regs.read(base_reg);
-            }
-            if let Some(index_reg) = index_reg {
-                if !matches!(scale, 1 | 2 | 4 | 8) {
-                    return Err(Exception::IllegalInstruction);
-                }
-                address += emulator.regs.read(index_reg) * (scale as u64);
-            }
+        Operand::Memory { size, .. } => Ok(size),
+        _ => Err(Exception::IllegalInstruction),
+    }
+}
+
+/// Returns the value of the third operand, either a register or an immediate.
+fn op2_value(emulator: &Emulator, insn: &Instruction) -> Result<u64, Exception> {
+    match insn.operands[2] {
+        Operand::Register(reg) => Ok(emulator.regs.read(reg)),
+        Operand::Immediate64(imm) => Ok(imm),
+        _ => Err(Exception::IllegalInstruction),
+    }
+}
+
+/// Returns the sign bit of `value` as a `size`-wide integer.
+fn sign_bit(size: OperandSize, value: u64) -> u64 {
+    (value >> (size.to_size() * 8 - 1)) & 1
+}
+
+/// Sign-extends a `size`-wide `value` to 64 bits.
+pub(crate) fn sign_extend(size: OperandSize, value: u64) -> u64 {
+    let shift = 64 - size.to_size() * 8;
+    (((value << shift) as i64) >> shift) as u64
+}
+
+/// Updates ZF, SF and PF according to the result of an operation.
+///
+/// All flag helpers expect operands and results already truncated to `size`.
+fn update_result_flags(rf: &mut RFlags, size: OperandSize, value: u64) {
+    rf.write_zf((value == 0).into());
+    rf.write_sf(sign_bit(size, value));
+    rf.write_pf(((value as u8).count_ones() % 2 == 0).into());
+}
+
+/// Updates RFlags according to the result of `lhs + rhs`.
+fn update_add_flags(emulator: &mut Emulator, size: OperandSize, lhs: u64, rhs: u64, value: u64) {
+    let mut rf = emulator.regs.read_rf();
+    update_result_flags(&mut rf, size, value);
+    rf.write_cf((value < lhs).into());
+    rf.write_of(sign_bit(size, (lhs ^ value) & (rhs ^ value)));
+    rf.write_af(((lhs ^ rhs ^ value) >> 4) & 1);
+    emulator.regs.write_rf(rf);
+}
+
+/// Updates RFlags according to the result of `lhs - rhs`.
+fn update_sub_flags(emulator: &mut Emulator, size: OperandSize, lhs: u64, rhs: u64, value: u64) {
+    let mut rf = emulator.regs.read_rf();
+    update_result_flags(&mut rf, size, value);
+    rf.write_cf((lhs < rhs).into());
+    rf.write_of(sign_bit(size, (lhs ^ rhs) & (lhs ^ value)));
+    rf.write_af(((lhs ^ rhs ^ value) >> 4) & 1);
+    emulator.regs.write_rf(rf);
+}
+
+/// Updates RFlags according to the result of a bitwise operation.
+///
+/// CF, OF and AF are always cleared.
+fn update_logic_flags(emulator: &mut Emulator, size: OperandSize, value: u64) {
+    let mut rf = emulator.regs.read_rf();
+    update_result_flags(&mut rf, size, value);
+    rf.write_cf(0);
+    rf.write_of(0);
+    rf.write_af(0);
+    emulator.regs.write_rf(rf);
+}
+
+/// Updates RFlags according to the result of a multiplication.
+///
+/// CF and OF are set if the result is truncated, AF is always cleared.
+fn update_imul_flags(emulator: &mut Emulator, size: OperandSize, value: u64, overflow: bool) {
+    let mut rf = emulator.regs.read_rf();
+    update_result_flags(&mut rf, size, value);
+    rf.write_cf(overflow.into());
+    rf.write_of(overflow.into());
+    rf.write_af(0);
+    emulator.regs.write_rf(rf);
+}
+
+/// Updates RFlags according to the result of an increment or decrement.
+///
+/// Same as [`update_add_flags`] or [`update_sub_flags`] but CF is preserved.
+fn update_incdec_flags(
+    emulator: &mut Emulator,
+    size: OperandSize,
+    lhs: u64,
+    value: u64,
+    is_inc: bool,
+) {
+    let cf = emulator.regs.read_rf().read_cf();
+    if is_inc {
+        update_add_flags(emulator, size, lhs, 1, value);
+    } else {
+        update_sub_flags(emulator, size, lhs, 1, value);
+    }
+    let mut rf = emulator.regs.read_rf();
+    rf.write_cf(cf);
+    emulator.regs.write_rf(rf);
+}
+
+/// Represents the condition codes shared by the conditional jump, move and
+/// set handlers.
+#[derive(Debug, Clone, Copy)]
+enum Condition {
+    /// The zero flag (ZF) is set (equal to zero).
+    Z,
+    /// The zero flag (ZF) is not set (not equal to zero).
+    Nz,
+    /// Less than or equal (ZF = 1 or SF ≠ OF).
+    Le,
+    /// Greater (ZF = 0 and SF = OF).
+    G,
+    /// Greater than or equal (SF = OF).
+    Ge,
+    /// Below (CF = 1).
+    B,
+    /// Less (SF ≠ OF).
+    L,
+    /// Above (CF = 0 and ZF = 0).
+    A,
+    /// Above or equal (CF = 0).
+    Ae,
+    /// Below or equal (CF = 1 or ZF = 1).
+    Be,
+    /// Sign (SF = 1).
+    S,
+    /// Not sign (SF = 0).
+    Ns,
+    /// Overflow (OF = 1).
+    O,
+    /// Not overflow (OF = 0).
+    No,
+    /// Parity (PF = 1).
+    P,
+    /// Not parity (PF = 0).
+    Np,
+}
 
-            Ok(match size {
-                OperandSize::Byte => emulator.dram.write_u8(address as usize, value as u8)?,
-                OperandSize::Word => emulator.dram.write_u16le(address as usize, value as u16)?,
-                OperandSize::DWord => emulator.dram.write_u32le(address as usize, value as u32)?,
-                OperandSize::QWord => emulator.dram.write_u64le(address as usize, value)?,
-            })
+impl Condition {
+    /// Evaluates the condition against the specified [`RFlags`].
+    fn holds(self, rf: &RFlags) -> bool {
+        match self {
+            Self::Z => rf.read_zf() & 1 == 1,
+            Self::Nz => rf.read_zf() & 1 == 0,
+            Self::Le => rf.read_zf() & 1 == 1 || rf.read_sf() != rf.read_of(),
+            Self::G => rf.read_zf() == 0 && rf.read_sf() == rf.read_of(),
+            Self::Ge => rf.read_sf() == rf.read_of(),
+            Self::B => rf.read_cf() & 1 == 1,
+            Self::L => rf.read_sf() != rf.read_of(),
+            Self::A => rf.read_cf() == 0 && rf.read_zf() == 0,
+            Self::Ae => rf.read_cf() == 0,
+            Self::Be => rf.read_cf() == 1 || rf.read_zf() == 1,
+            Self::S => rf.read_sf() == 1,
+            Self::Ns => rf.read_sf() == 0,
+            Self::O => rf.read_of() == 1,
+            Self::No => rf.read_of() == 0,
+            Self::P => rf.read_pf() == 1,
+            Self::Np => rf.read_pf() == 0,
         }
-        _ => return Err(Exception::IllegalInstruction),
     }
 }
 
+/// Jumps to the branch target if `cond` holds.
+fn handle_jcc(
+    emulator: &mut Emulator,
+    insn: &Instruction,
+    cond: Condition,
+) -> Result<(), Exception> {
+    let target = insn.branch_target();
+
+    if cond.holds(&emulator.regs.read_rf()) {
+        emulator.set_ip(emulator.ip().wrapping_add_signed(target));
+    }
+
+    Ok(())
+}
+
+/// Moves the second register operand into the first one if `cond` holds.
+/// The destination is written either way, so a 32-bit one is zero-extended.
+fn handle_cmovcc(
+    emulator: &mut Emulator,
+    insn: &Instruction,
+    cond: Condition,
+) -> Result<(), Exception> {
+    let op0_r = Some(insn.op0_reg())
+        .and_then(filter_special_reg)
+        .ok_or(Exception::IllegalInstruction)?;
+    let op1_r = insn.op1_reg();
+
+    let value = if cond.holds(&emulator.regs.read_rf()) {
+        emulator.regs.read(op1_r)
+    } else {
+        emulator.regs.read(op0_r)
+    };
+    emulator.regs.write(op0_r, value);
+
+    Ok(())
+}
+
+/// Sets the register operand to `1` if `cond` holds, `0` otherwise.
+fn handle_setcc(
+    emulator: &mut Emulator,
+    insn: &Instruction,
+    cond: Condition,
+) -> Result<(), Exception> {
+    let op0_r = Some(insn.op0_reg())
+        .and_then(filter_special_reg)
+        .ok_or(Exception::IllegalInstruction)?;
+
+    let value = cond.holds(&emulator.regs.read_rf());
+    emulator.regs.write(op0_r, value.into());
+
+    Ok(())
+}
+
+/// Updates RFlags according to the result of a shift operation.
+fn update_shift_flags(emulator: &mut Emulator, size: OperandSize, value: u64, cf: u64, of: u64) {
+    let mut rf = emulator.regs.read_rf();
+    update_result_flags(&mut rf, size, value);
+    rf.write_cf(cf);
+    rf.write_of(of);
+    rf.write_af(0);
+    emulator.regs.write_rf(rf);
+}
+
+/// Updates RFlags according to the result of a rotate operation.
+fn update_rotate_flags(emulator: &mut Emulator, cf: u64, of: u64) {
+    let mut rf = emulator.regs.read_rf();
+    rf.write_cf(cf);
+    rf.write_of(of);
+    emulator.regs.write_rf(rf);
+}
+
+/// Masks a shift or rotate `count` like x86, to 6 bits for a 64-bit operand
+/// and to 5 bits otherwise.
+fn shift_count(size: OperandSize, count: u64) -> u64 {
+    match size {
+        OperandSize::QWord => count & 63,
+        _ => count & 31,
+    }
+}
+
+/// Shifts the `size`-wide `value` to the left by `count` bits and updates
+/// RFlags.
+fn shl(emulator: &mut Emulator, size: OperandSize, value: u64, count: u64) -> u64 {
+    let count = shift_count(size, count);
+    if count == 0 {
+        return value;
+    }
+
+    let bits = size.to_size() * 8;
+    let wide = (value as u128) << count;
+    let result = wide as u64 & size.mask();
+    let cf = ((wide >> bits) & 1) as u64;
+    update_shift_flags(emulator, size, result, cf, sign_bit(size, result) ^ cf);
+    result
+}
+
+/// Logically shifts the `size`-wide `value` to the right by `count` bits and
+/// updates RFlags.
+fn shr(emulator: &mut Emulator, size: OperandSize, value: u64, count: u64) -> u64 {
+    let count = shift_count(size, count);
+    if count == 0 {
+        return value;
+    }
+
+    let result = value >> count;
+    let cf = (value >> (count - 1)) & 1;
+    update_shift_flags(emulator, size, result, cf, sign_bit(size, value));
+    result
+}
+
+/// Arithmetically shifts the `size`-wide `value` to the right by `count` bits
+/// and updates RFlags.
+fn sar(emulator: &mut Emulator, size: OperandSize, value: u64, count: u64) -> u64 {
+    let count = shift_count(size, count);
+    if count == 0 {
+        return value;
+    }
+
+    let signed = sign_extend(size, value) as i64;
+    let result = (signed >> count) as u64 & size.mask();
+    let cf = ((signed >> (count - 1)) as u64) & 1;
+    update_shift_flags(emulator, size, result, cf, 0);
+    result
+}
+
+/// Rotates the `size`-wide `value` to the left by `count` bits and updates
+/// RFlags.
+fn rol(emulator: &mut Emulator, size: OperandSize, value: u64, count: u64) -> u64 {
+    let count = shift_count(size, count);
+    if count == 0 {
+        return value;
+    }
+
+    let bits = size.to_size() as u64 * 8;
+    let count = count % bits;
+    let wide = value as u128;
+    let result = ((wide << count) | (wide >> (bits - count))) as u64 & size.mask();
+    let cf = result & 1;
+    update_rotate_flags(emulator, cf, sign_bit(size, result) ^ cf);
+    result
+}
+
+/// Rotates the `size`-wide `value` to the right by `count` bits and updates
+/// RFlags.
+fn ror(emulator: &mut Emulator, size: OperandSize, value: u64, count: u64) -> u64 {
+    let count = shift_count(size, count);
+    if count == 0 {
+        return value;
+    }
+
+    let bits = size.to_size() as u64 * 8;
+    let count = count % bits;
+    let wide = value as u128;
+    let result = ((wide >> count) | (wide << (bits - count))) as u64 & size.mask();
+    let cf = sign_bit(size, result);
+    update_rotate_flags(emulator, cf, cf ^ sign_bit(size, result << 1));
+    result
+}
+
+/// Rotates the value formed by CF and the `size`-wide `value` to the left by
+/// `count` bits and updates RFlags.
+fn rcl(emulator: &mut Emulator, size: OperandSize, value: u64, count: u64) -> u64 {
+    let bits = size.to_size() as u64 * 8;
+    let count = shift_count(size, count) % (bits + 1);
+    if count == 0 {
+        return value;
+    }
+
+    let mask = (1u128 << (bits + 1)) - 1;
+    let wide = ((emulator.regs.read_rf().read_cf() as u128) << bits) | value as u128;
+    let wide = ((wide << count) | (wide >> (bits + 1 - count))) & mask;
+    let result = wide as u64 & size.mask();
+    let cf = (wide >> bits) as u64;
+    update_rotate_flags(emulator, cf, sign_bit(size, result) ^ cf);
+    result
+}
+
+/// Rotates the value formed by CF and the `size`-wide `value` to the right by
+/// `count` bits and updates RFlags.
+fn rcr(emulator: &mut Emulator, size: OperandSize, value: u64, count: u64) -> u64 {
+    let bits = size.to_size() as u64 * 8;
+    let count = shift_count(size, count) % (bits + 1);
+    if count == 0 {
+        return value;
+    }
+
+    let mask = (1u128 << (bits + 1)) - 1;
+    let wide = ((emulator.regs.read_rf().read_cf() as u128) << bits) | value as u128;
+    let wide = ((wide >> count) | (wide << (bits + 1 - count))) & mask;
+    let result = wide as u64 & size.mask();
+    let cf = (wide >> bits) as u64;
+    update_rotate_flags(
+        emulator,
+        cf,
+        sign_bit(size, result) ^ sign_bit(size, result << 1),
+    );
+    result
+}
+
+/// Adds `rhs` and CF to the `size`-wide `lhs` and updates RFlags.
+fn adc(emulator: &mut Emulator, size: OperandSize, lhs: u64, rhs: u64) -> u64 {
+    let mut rf = emulator.regs.read_rf();
+    let wide = lhs as u128 + rhs as u128 + (rf.read_cf() & 1) as u128;
+    let value = wide as u64 & size.mask();
+    update_result_flags(&mut rf, size, value);
+    rf.write_cf((wide > size.mask() as u128).into());
+    rf.write_of(sign_bit(size, (lhs ^ value) & (rhs ^ value)));
+    rf.write_af(((lhs ^ rhs ^ value) >> 4) & 1);
+    emulator.regs.write_rf(rf);
+    value
+}
+
+/// Subtracts `rhs` and CF from the `size`-wide `lhs` and updates RFlags.
+fn sbb(emulator: &mut Emulator, size: OperandSize, lhs: u64, rhs: u64) -> u64 {
+    let mut rf = emulator.regs.read_rf();
+    let borrow = rf.read_cf() & 1;
+    let value = lhs.wrapping_sub(rhs).wrapping_sub(borrow) & size.mask();
+    update_result_flags(&mut rf, size, value);
+    rf.write_cf(((lhs as u128) < rhs as u128 + borrow as u128).into());
+    rf.write_of(sign_bit(size, (lhs ^ rhs) & (lhs ^ value)));
+    rf.write_af(((lhs ^ rhs ^ value) >> 4) & 1);
+    emulator.regs.write_rf(rf);
+    value
+}
+
+/// Updates RFlags according to the result of a bit count operation.
+fn update_count_flags(emulator: &mut Emulator, cf: bool, zf: bool) {
+    let mut rf = emulator.regs.read_rf();
+    rf.write_cf(cf.into());
+    rf.write_zf(zf.into());
+    rf.write_sf(0);
+    rf.write_of(0);
+    rf.write_pf(0);
+    rf.write_af(0);
+    emulator.regs.write_rf(rf);
+}
+
+/// Counts the set bits of `value` and updates RFlags.
+fn popcnt(emulator: &mut Emulator, value: u64) -> u64 {
+    let count = value.count_ones() as u64;
+    update_count_flags(emulator, false, value == 0);
+    count
+}
+
+/// Counts the leading zero bits of the `size`-wide `value` and updates
+/// RFlags.
+fn lzcnt(emulator: &mut Emulator, size: OperandSize, value: u64) -> u64 {
+    let bits = size.to_size() as u64 * 8;
+    let count = value.leading_zeros() as u64 - (64 - bits);
+    update_count_flags(emulator, count == bits, count == 0);
+    count
+}
+
+/// Counts the trailing zero bits of the `size`-wide `value` and updates
+/// RFlags.
+fn tzcnt(emulator: &mut Emulator, size: OperandSize, value: u64) -> u64 {
+    let bits = size.to_size() as u64 * 8;
+    let count = (value.trailing_zeros() as u64).min(bits);
+    update_count_flags(emulator, count == bits, count == 0);
+    count
+}
+
+/// Copies the bit of the `size`-wide `value` selected by `offset` into CF and
+/// returns the mask of that bit.
+fn bit_select(emulator: &mut Emulator, size: OperandSize, value: u64, offset: u64) -> u64 {
+    let mask = 1 << (offset % (size.to_size() as u64 * 8));
+    let mut rf = emulator.regs.read_rf();
+    rf.write_cf((value & mask != 0).into());
+    emulator.regs.write_rf(rf);
+    mask
+}
+
 // Memory operators
 define_handler_trait!(MovRIMM, handle_mov_r_imm);
 define_handler_trait!(MovRR, handle_mov_r_r);
 define_handler_trait!(MovRRM, handle_mov_r_rm);
 define_handler_trait!(MovRMR, handle_mov_rm_r);
+define_handler_trait!(MovRMIMM, handle_mov_rm_imm);
+define_handler_trait!(MovzxRRM, handle_movzx_r_rm);
+define_handler_trait!(MovsxRRM, handle_movsx_r_rm);
+define_handler_trait!(MovzxRR8, handle_movzx_r_r8);
+define_handler_trait!(MovzxRR16, handle_movzx_r_r16);
+define_handler_trait!(MovzxRR32, handle_movzx_r_r32);
+define_handler_trait!(MovsxRR8, handle_movsx_r_r8);
+define_handler_trait!(MovsxRR16, handle_movsx_r_r16);
+define_handler_trait!(MovsxRR32, handle_movsx_r_r32);
+define_handler_trait!(LeaRRM, handle_lea_r_rm);
 
 // Binary operators
 define_handler_trait!(AddRIMM, handle_add_r_imm);
 define_handler_trait!(AddRR, handle_add_r_r);
+define_handler_trait!(AddRRM, handle_add_r_rm);
+define_handler_trait!(AddRMR, handle_add_rm_r);
+define_handler_trait!(AddRMIMM, handle_add_rm_imm);
 define_handler_trait!(SubRIMM, handle_sub_r_imm);
 define_handler_trait!(SubRR, handle_sub_r_r);
+define_handler_trait!(SubRRM, handle_sub_r_rm);
+define_handler_trait!(SubRMR, handle_sub_rm_r);
+define_handler_trait!(SubRMIMM, handle_sub_rm_imm);
 define_handler_trait!(AndRIMM, handle_and_r_imm);
 define_handler_trait!(AndRR, handle_and_r_r);
+define_handler_trait!(AndRRM, handle_and_r_rm);
+define_handler_trait!(AndRMR, handle_and_rm_r);
+define_handler_trait!(AndRMIMM, handle_and_rm_imm);
 define_handler_trait!(OrRIMM, handle_or_r_imm);
 define_handler_trait!(OrRR, handle_or_r_r);
+define_handler_trait!(OrRRM, handle_or_r_rm);
+define_handler_trait!(OrRMR, handle_or_rm_r);
+define_handler_trait!(OrRMIMM, handle_or_rm_imm);
 define_handler_trait!(XorRIMM, handle_xor_r_imm);
 define_handler_trait!(XorRR, handle_xor_r_r);
+define_handler_trait!(XorRRM, handle_xor_r_rm);
+define_handler_trait!(XorRMR, handle_xor_rm_r);
+define_handler_trait!(XorRMIMM, handle_xor_rm_imm);
 define_handler_trait!(XchgRR, handle_xchg_r_r);
+define_handler_trait!(XchgRRM, handle_xchg_r_rm);
+define_handler_trait!(XchgRMR, handle_xchg_rm_r);
 define_handler_trait!(ImulRIMM, handle_imul_r_imm);
 define_handler_trait!(ImulRR, handle_imul_r_r);
+define_handler_trait!(ImulRRM, handle_imul_r_rm);
+define_handler_trait!(DivRIMM, handle_div_r_imm);
+define_handler_trait!(DivRR, handle_div_r_r);
+define_handler_trait!(IdivRIMM, handle_idiv_r_imm);
+define_handler_trait!(IdivRR, handle_idiv_r_r);
+define_handler_trait!(RemRIMM, handle_rem_r_imm);
+define_handler_trait!(RemRR, handle_rem_r_r);
+define_handler_trait!(IremRIMM, handle_irem_r_imm);
+define_handler_trait!(IremRR, handle_irem_r_r);
+define_handler_trait!(AdcRR, handle_adc_r_r);
+define_handler_trait!(AdcRIMM, handle_adc_r_imm);
+define_handler_trait!(AdcRRM, handle_adc_r_rm);
+define_handler_trait!(AdcRMR, handle_adc_rm_r);
+define_handler_trait!(AdcRMIMM, handle_adc_rm_imm);
+define_handler_trait!(SbbRR, handle_sbb_r_r);
+define_handler_trait!(SbbRIMM, handle_sbb_r_imm);
+define_handler_trait!(SbbRRM, handle_sbb_r_rm);
+define_handler_trait!(SbbRMR, handle_sbb_rm_r);
+define_handler_trait!(SbbRMIMM, handle_sbb_rm_imm);
+define_handler_trait!(MulRR, handle_mul_r_r);
+define_handler_trait!(MulhRR, handle_mulh_r_r);
+define_handler_trait!(MulhRRM, handle_mulh_r_rm);
+define_handler_trait!(ImulhRR, handle_imulh_r_r);
+define_handler_trait!(ImulhRRM, handle_imulh_r_rm);
+
+// Shift and rotate operators
+define_handler_trait!(ShlRIMM, handle_shl_r_imm);
+define_handler_trait!(ShlRR, handle_shl_r_r);
+define_handler_trait!(ShrRIMM, handle_shr_r_imm);
+define_handler_trait!(ShrRR, handle_shr_r_r);
+define_handler_trait!(SarRIMM, handle_sar_r_imm);
+define_handler_trait!(SarRR, handle_sar_r_r);
+define_handler_trait!(RolRIMM, handle_rol_r_imm);
+define_handler_trait!(RolRR, handle_rol_r_r);
+define_handler_trait!(RorRIMM, handle_ror_r_imm);
+define_handler_trait!(RorRR, handle_ror_r_r);
+define_handler_trait!(RclRIMM, handle_rcl_r_imm);
+define_handler_trait!(RclRR, handle_rcl_r_r);
+define_handler_trait!(RcrRIMM, handle_rcr_r_imm);
+define_handler_trait!(RcrRR, handle_rcr_r_r);
+
+// Bit manipulation operators
+define_handler_trait!(PopcntRR, handle_popcnt_r_r);
+define_handler_trait!(PopcntRRM, handle_popcnt_r_rm);
+define_handler_trait!(LzcntRR, handle_lzcnt_r_r);
+define_handler_trait!(LzcntRRM, handle_lzcnt_r_rm);
+define_handler_trait!(TzcntRR, handle_tzcnt_r_r);
+define_handler_trait!(TzcntRRM, handle_tzcnt_r_rm);
+define_handler_trait!(BswapR, handle_bswap_r);
+define_handler_trait!(BtRR, handle_bt_r_r);
+define_handler_trait!(BtRIMM, handle_bt_r_imm);
+define_handler_trait!(BtRMR, handle_bt_rm_r);
+define_handler_trait!(BtRMIMM, handle_bt_rm_imm);
+define_handler_trait!(BtsRR, handle_bts_r_r);
+define_handler_trait!(BtsRIMM, handle_bts_r_imm);
+define_handler_trait!(BtsRMR, handle_bts_rm_r);
+define_handler_trait!(BtsRMIMM, handle_bts_rm_imm);
+define_handler_trait!(BtrRR, handle_btr_r_r);
+define_handler_trait!(BtrRIMM, handle_btr_r_imm);
+define_handler_trait!(BtrRMR, handle_btr_rm_r);
+define_handler_trait!(BtrRMIMM, handle_btr_rm_imm);
+define_handler_trait!(BtcRR, handle_btc_r_r);
+define_handler_trait!(BtcRIMM, handle_btc_r_imm);
+define_handler_trait!(BtcRMR, handle_btc_rm_r);
+define_handler_trait!(BtcRMIMM, handle_btc_rm_imm);
 
 // Unary operators
 define_handler_trait!(IncR, handle_inc_r);
+define_handler_trait!(IncRM, handle_inc_rm);
 define_handler_trait!(DecR, handle_dec_r);
+define_handler_trait!(DecRM, handle_dec_rm);
+define_handler_trait!(NotR, handle_not_r);
+define_handler_trait!(NotRM, handle_not_rm);
+define_handler_trait!(NegR, handle_neg_r);
+define_handler_trait!(NegRM, handle_neg_rm);
 
 // Branch operators
 define_handler_trait!(Jmp, handle_jmp);
+define_handler_trait!(JmpR, handle_jmp_r);
+define_handler_trait!(JmpRM, handle_jmp_rm);
 define_handler_trait!(Jz, handle_jz);
 define_handler_trait!(Jnz, handle_jnz);
 define_handler_trait!(Jle, handle_jle);
 define_handler_trait!(Jg, handle_jg);
 define_handler_trait!(Jge, handle_jge);
 define_handler_trait!(Jb, handle_jb);
+define_handler_trait!(Jl, handle_jl);
+define_handler_trait!(Ja, handle_ja);
+define_handler_trait!(Jae, handle_jae);
+define_handler_trait!(Jbe, handle_jbe);
+define_handler_trait!(Js, handle_js);
+define_handler_trait!(Jns, handle_jns);
+define_handler_trait!(Jo, handle_jo);
+define_handler_trait!(Jno, handle_jno);
+define_handler_trait!(Jp, handle_jp);
+define_handler_trait!(Jnp, handle_jnp);
+
+// Stack operators
+define_handler_trait!(PushR, handle_push_r);
+define_handler_trait!(PushIMM, handle_push_imm);
+define_handler_trait!(PopR, handle_pop_r);
+define_handler_trait!(Call, handle_call);
+define_handler_trait!(CallR, handle_call_r);
+define_handler_trait!(CallRM, handle_call_rm);
+define_handler_trait!(Ret, handle_ret);
+
+// Block operators
+define_handler_trait!(RepMovsb, handle_rep_movsb);
+define_handler_trait!(RepStosb, handle_rep_stosb);
+define_handler_trait!(RepCmpsb, handle_rep_cmpsb);
 
 // Comparison operators
 define_handler_trait!(TestRIMM, handle_test_r_imm);
 define_handler_trait!(TestRR, handle_test_r_r);
+define_handler_trait!(TestRRM, handle_test_r_rm);
+define_handler_trait!(TestRMR, handle_test_rm_r);
+define_handler_trait!(TestRMIMM, handle_test_rm_imm);
 define_handler_trait!(CmpRIMM, handle_cmp_r_imm);
 define_handler_trait!(CmpRR, handle_cmp_r_r);
+define_handler_trait!(CmpRRM, handle_cmp_r_rm);
+define_handler_trait!(CmpRMR, handle_cmp_rm_r);
+define_handler_trait!(CmpRMIMM, handle_cmp_rm_imm);
+
+// Conditional operators
+define_handler_trait!(CmovzRR, handle_cmovz_r_r);
+define_handler_trait!(CmovnzRR, handle_cmovnz_r_r);
+define_handler_trait!(CmovleRR, handle_cmovle_r_r);
+define_handler_trait!(CmovgRR, handle_cmovg_r_r);
+define_handler_trait!(CmovgeRR, handle_cmovge_r_r);
+define_handler_trait!(CmovbRR, handle_cmovb_r_r);
+define_handler_trait!(CmovlRR, handle_cmovl_r_r);
+define_handler_trait!(CmovaRR, handle_cmova_r_r);
+define_handler_trait!(CmovaeRR, handle_cmovae_r_r);
+define_handler_trait!(CmovbeRR, handle_cmovbe_r_r);
+define_handler_trait!(CmovsRR, handle_cmovs_r_r);
+define_handler_trait!(CmovnsRR, handle_cmovns_r_r);
+define_handler_trait!(CmovoRR, handle_cmovo_r_r);
+define_handler_trait!(CmovnoRR, handle_cmovno_r_r);
+define_handler_trait!(CmovpRR, handle_cmovp_r_r);
+define_handler_trait!(CmovnpRR, handle_cmovnp_r_r);
+define_handler_trait!(SetzR, handle_setz_r);
+define_handler_trait!(SetnzR, handle_setnz_r);
+define_handler_trait!(SetleR, handle_setle_r);
+define_handler_trait!(SetgR, handle_setg_r);
+define_handler_trait!(SetgeR, handle_setge_r);
+define_handler_trait!(SetbR, handle_setb_r);
+define_handler_trait!(SetlR, handle_setl_r);
+define_handler_trait!(SetaR, handle_seta_r);
+define_handler_trait!(SetaeR, handle_setae_r);
+define_handler_trait!(SetbeR, handle_setbe_r);
+define_handler_trait!(SetsR, handle_sets_r);
+define_handler_trait!(SetnsR, handle_setns_r);
+define_handler_trait!(SetoR, handle_seto_r);
+define_handler_trait!(SetnoR, handle_setno_r);
+define_handler_trait!(SetpR, handle_setp_r);
+define_handler_trait!(SetnpR, handle_setnp_r);
+define_handler_trait!(AddRRRI, handle_add_r_r_ri);
+define_handler_trait!(SubRRRI, handle_sub_r_r_ri);
+define_handler_trait!(AndRRRI, handle_and_r_r_ri);
+define_handler_trait!(OrRRRI, handle_or_r_r_ri);
+define_handler_trait!(XorRRRI, handle_xor_r_r_ri);
+define_handler_trait!(ImulRRRI, handle_imul_r_r_ri);
+define_handler_trait!(ShlRRRI, handle_shl_r_r_ri);
+define_handler_trait!(ShrRRRI, handle_shr_r_r_ri);
+define_handler_trait!(SarRRRI, handle_sar_r_r_ri);
+define_handler_trait!(AdcRRRI, handle_adc_r_r_ri);
+define_handler_trait!(SbbRRRI, handle_sbb_r_r_ri);
+define_handler_trait!(MulhRRRI, handle_mulh_r_r_ri);
+define_handler_trait!(ImulhRRRI, handle_imulh_r_r_ri);
+define_handler_trait!(DivRRRI, handle_div_r_r_ri);
+define_handler_trait!(IdivRRRI, handle_idiv_r_r_ri);
+define_handler_trait!(RemRRRI, handle_rem_r_r_ri);
+define_handler_trait!(IremRRRI, handle_irem_r_r_ri);
+define_handler_trait!(RolRRRI, handle_rol_r_r_ri);
+define_handler_trait!(RorRRRI, handle_ror_r_r_ri);
+define_handler_trait!(RclRRRI, handle_rcl_r_r_ri);
+define_handler_trait!(RcrRRRI, handle_rcr_r_r_ri);
 
 impl MovRIMM for Emulator {
     fn handle_mov_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
@@ -190,16 +913,153 @@ impl MovRMR for Emulator {
     }
 }
 
+impl MovRMIMM for Emulator {
+    fn handle_mov_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let imm = insn.immediate();
+
+        handle_memop_write(self, 0, insn, imm)?;
+
+        Ok(())
+    }
+}
+
+impl MovzxRRM for Emulator {
+    fn handle_movzx_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+
+        let value = handle_memop_read(self, 1, insn)?;
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl MovsxRRM for Emulator {
+    fn handle_movsx_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = memop_size(insn, 1)?;
+
+        let value = handle_memop_read(self, 1, insn)?;
+        self.regs.write(op0_r, sign_extend(size, value));
+
+        Ok(())
+    }
+}
+
+impl MovzxRR8 for Emulator {
+    fn handle_movzx_r_r8(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = self.regs.read(op1_r) & OperandSize::Byte.mask();
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl MovzxRR16 for Emulator {
+    fn handle_movzx_r_r16(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = self.regs.read(op1_r) & OperandSize::Word.mask();
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl MovzxRR32 for Emulator {
+    fn handle_movzx_r_r32(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = self.regs.read(op1_r) & OperandSize::DWord.mask();
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl MovsxRR8 for Emulator {
+    fn handle_movsx_r_r8(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = sign_extend(OperandSize::Byte, self.regs.read(op1_r));
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl MovsxRR16 for Emulator {
+    fn handle_movsx_r_r16(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = sign_extend(OperandSize::Word, self.regs.read(op1_r));
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl MovsxRR32 for Emulator {
+    fn handle_movsx_r_r32(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = sign_extend(OperandSize::DWord, self.regs.read(op1_r));
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl LeaRRM for Emulator {
+    fn handle_lea_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+
+        let address = effective_address(self, 1, insn)?;
+        self.regs.write(op0_r, address);
+
+        Ok(())
+    }
+}
+
 impl AddRIMM for Emulator {
     fn handle_add_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
         let r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = r.size();
         let imm = insn.immediate();
 
         let lhs = self.regs.read(r);
-        let rhs = imm;
-        self.regs.write(r, lhs.wrapping_add(rhs));
+        let rhs = imm & size.mask();
+        let value = lhs.wrapping_add(rhs) & size.mask();
+        self.regs.write(r, value);
+        update_add_flags(self, size, lhs, rhs, value);
 
         Ok(())
     }
@@ -210,11 +1070,14 @@ impl AddRR for Emulator {
         let op0_r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
         let op1_r = insn.op1_reg();
 
         let lhs = self.regs.read(op0_r);
-        let rhs = self.regs.read(op1_r);
-        self.regs.write(op0_r, lhs.wrapping_add(rhs));
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs.wrapping_add(rhs) & size.mask();
+        self.regs.write(op0_r, value);
+        update_add_flags(self, size, lhs, rhs, value);
 
         Ok(())
     }
@@ -225,11 +1088,14 @@ impl SubRIMM for Emulator {
         let r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = r.size();
         let imm = insn.immediate();
 
         let lhs = self.regs.read(r);
-        let rhs = imm;
-        self.regs.write(r, lhs.wrapping_sub(rhs));
+        let rhs = imm & size.mask();
+        let value = lhs.wrapping_sub(rhs) & size.mask();
+        self.regs.write(r, value);
+        update_sub_flags(self, size, lhs, rhs, value);
 
         Ok(())
     }
@@ -240,11 +1106,14 @@ impl SubRR for Emulator {
         let op0_r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
         let op1_r = insn.op1_reg();
 
         let lhs = self.regs.read(op0_r);
-        let rhs = self.regs.read(op1_r);
-        self.regs.write(op0_r, lhs.wrapping_sub(rhs));
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs.wrapping_sub(rhs) & size.mask();
+        self.regs.write(op0_r, value);
+        update_sub_flags(self, size, lhs, rhs, value);
 
         Ok(())
     }
@@ -255,13 +1124,14 @@ impl AndRIMM for Emulator {
         let r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = r.size();
         let imm = insn.immediate();
 
         let lhs = self.regs.read(r);
-        let rhs = imm;
-        self.regs.write(r, lhs & rhs);
-
-        // println!("AND/R/IMM {lhs} & {rhs} = {}", lhs & rhs);
+        let rhs = imm & size.mask();
+        let value = lhs & rhs;
+        self.regs.write(r, value);
+        update_logic_flags(self, size, value);
 
         Ok(())
     }
@@ -272,11 +1142,14 @@ impl AndRR for Emulator {
         let op0_r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
         let op1_r = insn.op1_reg();
 
         let lhs = self.regs.read(op0_r);
-        let rhs = self.regs.read(op1_r);
-        self.regs.write(op0_r, lhs & rhs);
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs & rhs;
+        self.regs.write(op0_r, value);
+        update_logic_flags(self, size, value);
 
         Ok(())
     }
@@ -287,11 +1160,14 @@ impl OrRIMM for Emulator {
         let r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = r.size();
         let imm = insn.immediate();
 
         let lhs = self.regs.read(r);
-        let rhs = imm;
-        self.regs.write(r, lhs | rhs);
+        let rhs = imm & size.mask();
+        let value = lhs | rhs;
+        self.regs.write(r, value);
+        update_logic_flags(self, size, value);
 
         Ok(())
     }
@@ -302,11 +1178,14 @@ impl OrRR for Emulator {
         let op0_r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
         let op1_r = insn.op1_reg();
 
         let lhs = self.regs.read(op0_r);
-        let rhs = self.regs.read(op1_r);
-        self.regs.write(op0_r, lhs | rhs);
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs | rhs;
+        self.regs.write(op0_r, value);
+        update_logic_flags(self, size, value);
 
         Ok(())
     }
@@ -317,11 +1196,14 @@ impl XorRIMM for Emulator {
         let r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = r.size();
         let imm = insn.immediate();
 
         let lhs = self.regs.read(r);
-        let rhs = imm;
-        self.regs.write(r, lhs ^ rhs);
+        let rhs = imm & size.mask();
+        let value = lhs ^ rhs;
+        self.regs.write(r, value);
+        update_logic_flags(self, size, value);
 
         Ok(())
     }
@@ -332,11 +1214,14 @@ impl XorRR for Emulator {
         let op0_r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
         let op1_r = insn.op1_reg();
 
         let lhs = self.regs.read(op0_r);
-        let rhs = self.regs.read(op1_r);
-        self.regs.write(op0_r, lhs ^ rhs);
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs ^ rhs;
+        self.regs.write(op0_r, value);
+        update_logic_flags(self, size, value);
 
         Ok(())
     }
@@ -365,9 +1250,19 @@ impl ImulRIMM for Emulator {
             .ok_or(Exception::IllegalInstruction)?;
         let imm = insn.immediate();
 
-        let lhs = self.regs.read(r) as i64;
-        let rhs = imm as i64;
-        self.regs.write(r, lhs.wrapping_mul(rhs) as u64);
+        let size = r.size();
+
+        let lhs = sign_extend(size, self.regs.read(r)) as i64 as i128;
+        let rhs = sign_extend(size, imm & size.mask()) as i64 as i128;
+        let wide = lhs * rhs;
+        let value = wide as u64 & size.mask();
+        self.regs.write(r, value);
+        update_imul_flags(
+            self,
+            size,
+            value,
+            wide != sign_extend(size, value) as i64 as i128,
+        );
 
         Ok(())
     }
@@ -380,246 +1275,2408 @@ impl ImulRR for Emulator {
             .ok_or(Exception::IllegalInstruction)?;
         let op1_r = insn.op1_reg();
 
-        let lhs = self.regs.read(op0_r) as i64;
-        let rhs = self.regs.read(op1_r) as i64;
-        self.regs.write(op0_r, lhs.wrapping_mul(rhs) as u64);
+        let size = op0_r.size();
+
+        let lhs = sign_extend(size, self.regs.read(op0_r)) as i64 as i128;
+        let rhs = sign_extend(size, self.regs.read(op1_r) & size.mask()) as i64 as i128;
+        let wide = lhs * rhs;
+        let value = wide as u64 & size.mask();
+        self.regs.write(op0_r, value);
+        update_imul_flags(
+            self,
+            size,
+            value,
+            wide != sign_extend(size, value) as i64 as i128,
+        );
 
         Ok(())
     }
 }
 
-impl IncR for Emulator {
-    fn handle_inc_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+impl AddRRM for Emulator {
+    fn handle_add_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
         let op0_r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
 
-        let value = self.regs.read(op0_r).wrapping_add(1u64);
+        let lhs = self.regs.read(op0_r);
+        let rhs = handle_memop_read(self, 1, insn)? & size.mask();
+        let value = lhs.wrapping_add(rhs) & size.mask();
         self.regs.write(op0_r, value);
+        update_add_flags(self, size, lhs, rhs, value);
 
         Ok(())
     }
 }
 
-impl DecR for Emulator {
-    fn handle_dec_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+impl AddRMR for Emulator {
+    fn handle_add_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let op1_r = insn.op1_reg();
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs.wrapping_add(rhs) & size.mask();
+        handle_memop_write(self, 0, insn, value)?;
+        update_add_flags(self, size, lhs, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl AddRMIMM for Emulator {
+    fn handle_add_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = insn.immediate() & size.mask();
+        let value = lhs.wrapping_add(rhs) & size.mask();
+        handle_memop_write(self, 0, insn, value)?;
+        update_add_flags(self, size, lhs, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl SubRRM for Emulator {
+    fn handle_sub_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
         let op0_r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
 
-        let value = self.regs.read(op0_r).wrapping_sub(1u64);
+        let lhs = self.regs.read(op0_r);
+        let rhs = handle_memop_read(self, 1, insn)? & size.mask();
+        let value = lhs.wrapping_sub(rhs) & size.mask();
         self.regs.write(op0_r, value);
+        update_sub_flags(self, size, lhs, rhs, value);
 
         Ok(())
     }
 }
 
-impl TestRIMM for Emulator {
-    fn handle_test_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+impl SubRMR for Emulator {
+    fn handle_sub_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let op1_r = insn.op1_reg();
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs.wrapping_sub(rhs) & size.mask();
+        handle_memop_write(self, 0, insn, value)?;
+        update_sub_flags(self, size, lhs, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl SubRMIMM for Emulator {
+    fn handle_sub_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = insn.immediate() & size.mask();
+        let value = lhs.wrapping_sub(rhs) & size.mask();
+        handle_memop_write(self, 0, insn, value)?;
+        update_sub_flags(self, size, lhs, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl AndRRM for Emulator {
+    fn handle_and_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
         let op0_r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
 
         let lhs = self.regs.read(op0_r);
-        let rhs = insn.immediate();
+        let rhs = handle_memop_read(self, 1, insn)? & size.mask();
         let value = lhs & rhs;
+        self.regs.write(op0_r, value);
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl AndRMR for Emulator {
+    fn handle_and_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let op1_r = insn.op1_reg();
 
-        let mut rf = self.regs.read_rf();
-        rf.write_zf((value == 0u64).into());
-        self.regs.write_rf(rf);
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs & rhs;
+        handle_memop_write(self, 0, insn, value)?;
+        update_logic_flags(self, size, value);
 
         Ok(())
     }
 }
 
-impl TestRR for Emulator {
-    fn handle_test_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+impl AndRMIMM for Emulator {
+    fn handle_and_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = insn.immediate() & size.mask();
+        let value = lhs & rhs;
+        handle_memop_write(self, 0, insn, value)?;
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl OrRRM for Emulator {
+    fn handle_or_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
         let op0_r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
-        let op1_r = insn.op1_reg();
+        let size = op0_r.size();
 
         let lhs = self.regs.read(op0_r);
-        let rhs = self.regs.read(op1_r);
-        let value = lhs & rhs;
+        let rhs = handle_memop_read(self, 1, insn)? & size.mask();
+        let value = lhs | rhs;
+        self.regs.write(op0_r, value);
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl OrRMR for Emulator {
+    fn handle_or_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let op1_r = insn.op1_reg();
 
-        let mut rf = self.regs.read_rf();
-        rf.write_zf((value == 0u64).into());
-        self.regs.write_rf(rf);
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs | rhs;
+        handle_memop_write(self, 0, insn, value)?;
+        update_logic_flags(self, size, value);
 
         Ok(())
     }
 }
 
-impl CmpRIMM for Emulator {
-    fn handle_cmp_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+impl OrRMIMM for Emulator {
+    fn handle_or_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = insn.immediate() & size.mask();
+        let value = lhs | rhs;
+        handle_memop_write(self, 0, insn, value)?;
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl XorRRM for Emulator {
+    fn handle_xor_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
         let op0_r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = handle_memop_read(self, 1, insn)? & size.mask();
+        let value = lhs ^ rhs;
+        self.regs.write(op0_r, value);
+        update_logic_flags(self, size, value);
 
-        let lhs = self.regs.read(op0_r) as i64;
-        let rhs = insn.immediate() as i64;
-        let value = lhs.wrapping_sub(rhs);
+        Ok(())
+    }
+}
 
-        let mut rf = self.regs.read_rf();
-        rf.write_zf((value == 0).into());
-        rf.write_sf(((value as u64) >> 63).into());
+impl XorRMR for Emulator {
+    fn handle_xor_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let op1_r = insn.op1_reg();
 
-        let lhs_sign = (lhs >> 63) & 1;
-        let rhs_sign = (rhs >> 63) & 1;
-        let res_sign = (value >> 63) & 1;
-        let of = (lhs_sign != rhs_sign) && (lhs_sign != res_sign);
-        rf.write_of(of.into());
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs ^ rhs;
+        handle_memop_write(self, 0, insn, value)?;
+        update_logic_flags(self, size, value);
 
-        self.regs.write_rf(rf);
         Ok(())
     }
 }
 
-impl CmpRR for Emulator {
-    fn handle_cmp_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+impl XorRMIMM for Emulator {
+    fn handle_xor_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = insn.immediate() & size.mask();
+        let value = lhs ^ rhs;
+        handle_memop_write(self, 0, insn, value)?;
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl XchgRRM for Emulator {
+    fn handle_xchg_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
         let op0_r = Some(insn.op0_reg())
             .and_then(filter_special_reg)
             .ok_or(Exception::IllegalInstruction)?;
-        let op1_r = insn.op1_reg();
 
-        let lhs = self.regs.read(op0_r) as i64;
-        let rhs = self.regs.read(op1_r) as i64;
-        let value = lhs.wrapping_sub(rhs);
+        let value = handle_memop_read(self, 1, insn)?;
+        handle_memop_write(self, 1, insn, self.regs.read(op0_r))?;
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
 
-        let mut rf = self.regs.read_rf();
-        rf.write_zf((value == 0).into());
-        rf.write_sf(((value as u64) >> 63).into());
+impl XchgRMR for Emulator {
+    fn handle_xchg_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op1_r = Some(insn.op1_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
 
-        let lhs_sign = (lhs >> 63) & 1;
-        let rhs_sign = (rhs >> 63) & 1;
-        let res_sign = (value >> 63) & 1;
-        let of = (lhs_sign != rhs_sign) && (lhs_sign != res_sign);
-        rf.write_of(of.into());
+        let value = handle_memop_read(self, 0, insn)?;
+        handle_memop_write(self, 0, insn, self.regs.read(op1_r))?;
+        self.regs.write(op1_r, value);
 
-        self.regs.write_rf(rf);
         Ok(())
     }
 }
 
-impl Jmp for Emulator {
-    fn handle_jmp(&mut self, insn: &Instruction) -> Result<(), Exception> {
-        let target = insn.branch_target();
-        if target == 0 {
-            return Err(Exception::IllegalInstruction);
-        }
+impl ImulRRM for Emulator {
+    fn handle_imul_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
 
-        let value = (self.ip() as i64) + target;
-        self.set_ip(value as u64);
+        let size = op0_r.size();
+
+        let lhs = sign_extend(size, self.regs.read(op0_r)) as i64 as i128;
+        let rhs = sign_extend(size, handle_memop_read(self, 1, insn)? & size.mask()) as i64 as i128;
+        let wide = lhs * rhs;
+        let value = wide as u64 & size.mask();
+        self.regs.write(op0_r, value);
+        update_imul_flags(
+            self,
+            size,
+            value,
+            wide != sign_extend(size, value) as i64 as i128,
+        );
 
         Ok(())
     }
 }
 
-impl Jz for Emulator {
-    fn handle_jz(&mut self, insn: &Instruction) -> Result<(), Exception> {
-        let target = insn.branch_target();
-        if target == 0 {
-            return Err(Exception::IllegalInstruction);
+impl DivRIMM for Emulator {
+    fn handle_div_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let imm = insn.immediate();
+
+        let lhs = self.regs.read(r);
+        let rhs = imm & r.size().mask();
+        if rhs == 0 {
+            return Err(Exception::DivideByZero);
         }
 
-        let value = (self.ip() as i64) + target;
-        let rf = self.regs.read_rf();
+        self.regs.write(r, lhs / rhs);
+
+        Ok(())
+    }
+}
+
+impl DivRR for Emulator {
+    fn handle_div_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
 
-        if rf.read_zf() & 1 == 1 {
-            self.set_ip(value as u64);
+        let lhs = self.regs.read(op0_r);
+        let rhs = self.regs.read(op1_r) & op0_r.size().mask();
+        if rhs == 0 {
+            return Err(Exception::DivideByZero);
         }
 
+        self.regs.write(op0_r, lhs / rhs);
+
         Ok(())
     }
 }
 
-impl Jnz for Emulator {
-    fn handle_jnz(&mut self, insn: &Instruction) -> Result<(), Exception> {
-        let target = insn.branch_target();
-        if target == 0 {
-            return Err(Exception::IllegalInstruction);
-        }
+impl IdivRIMM for Emulator {
+    fn handle_idiv_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let imm = insn.immediate();
 
-        let value = (self.ip() as i64) + target;
-        let rf = self.regs.read_rf();
+        let size = r.size();
 
-        if rf.read_zf() & 1 == 0 {
-            self.set_ip(value as u64);
+        let lhs = sign_extend(size, self.regs.read(r)) as i64;
+        let rhs = sign_extend(size, imm & size.mask()) as i64;
+        if rhs == 0 {
+            return Err(Exception::DivideByZero);
         }
 
+        self.regs.write(r, lhs.wrapping_div(rhs) as u64);
+
         Ok(())
     }
 }
 
-impl Jle for Emulator {
-    fn handle_jle(&mut self, insn: &Instruction) -> Result<(), Exception> {
-        let target = insn.branch_target();
-        if target == 0 {
-            return Err(Exception::IllegalInstruction);
-        }
+impl IdivRR for Emulator {
+    fn handle_idiv_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
 
-        let value = (self.ip() as i64) + target;
-        let rf = self.regs.read_rf();
+        let size = op0_r.size();
 
-        if rf.read_zf() & 1 == 1 || rf.read_sf() != rf.read_of() {
-            self.set_ip(value as u64);
+        let lhs = sign_extend(size, self.regs.read(op0_r)) as i64;
+        let rhs = sign_extend(size, self.regs.read(op1_r) & size.mask()) as i64;
+        if rhs == 0 {
+            return Err(Exception::DivideByZero);
         }
 
+        self.regs.write(op0_r, lhs.wrapping_div(rhs) as u64);
+
         Ok(())
     }
 }
 
-impl Jg for Emulator {
-    fn handle_jg(&mut self, insn: &Instruction) -> Result<(), Exception> {
-        let target = insn.branch_target();
-        if target == 0 {
-            return Err(Exception::IllegalInstruction);
+impl RemRIMM for Emulator {
+    fn handle_rem_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let imm = insn.immediate();
+
+        let lhs = self.regs.read(r);
+        let rhs = imm & r.size().mask();
+        if rhs == 0 {
+            return Err(Exception::DivideByZero);
         }
 
-        let value = (self.ip() as i64) + target;
-        let rf = self.regs.read_rf();
+        self.regs.write(r, lhs % rhs);
+
+        Ok(())
+    }
+}
+
+impl RemRR for Emulator {
+    fn handle_rem_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
 
-        if rf.read_zf() == 0 && rf.read_sf() == rf.read_of() {
-            self.set_ip(value as u64);
+        let lhs = self.regs.read(op0_r);
+        let rhs = self.regs.read(op1_r) & op0_r.size().mask();
+        if rhs == 0 {
+            return Err(Exception::DivideByZero);
         }
 
+        self.regs.write(op0_r, lhs % rhs);
+
         Ok(())
     }
 }
 
-impl Jge for Emulator {
-    fn handle_jge(&mut self, insn: &Instruction) -> Result<(), Exception> {
-        let target = insn.branch_target();
-        if target == 0 {
-            return Err(Exception::IllegalInstruction);
-        }
+impl IremRIMM for Emulator {
+    fn handle_irem_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let imm = insn.immediate();
 
-        let value = (self.ip() as i64) + target;
-        let rf = self.regs.read_rf();
+        let size = r.size();
 
-        if rf.read_sf() == rf.read_of() {
-            self.set_ip(value as u64);
+        let lhs = sign_extend(size, self.regs.read(r)) as i64;
+        let rhs = sign_extend(size, imm & size.mask()) as i64;
+        if rhs == 0 {
+            return Err(Exception::DivideByZero);
         }
 
+        self.regs.write(r, lhs.wrapping_rem(rhs) as u64);
+
         Ok(())
     }
 }
 
-impl Jb for Emulator {
-    fn handle_jb(&mut self, insn: &Instruction) -> Result<(), Exception> {
-        let target = insn.branch_target();
-        if target == 0 {
-            return Err(Exception::IllegalInstruction);
-        }
+impl IremRR for Emulator {
+    fn handle_irem_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
 
-        let value = (self.ip() as i64) + target;
-        let rf = self.regs.read_rf();
+        let size = op0_r.size();
 
-        if rf.read_cf() & 1 == 1 {
-            self.set_ip(value as u64);
+        let lhs = sign_extend(size, self.regs.read(op0_r)) as i64;
+        let rhs = sign_extend(size, self.regs.read(op1_r) & size.mask()) as i64;
+        if rhs == 0 {
+            return Err(Exception::DivideByZero);
         }
 
+        self.regs.write(op0_r, lhs.wrapping_rem(rhs) as u64);
+
+        Ok(())
+    }
+}
+
+impl AdcRR for Emulator {
+    fn handle_adc_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = adc(self, size, lhs, rhs);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl AdcRIMM for Emulator {
+    fn handle_adc_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = insn.immediate() & size.mask();
+        let value = adc(self, size, lhs, rhs);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl AdcRRM for Emulator {
+    fn handle_adc_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = handle_memop_read(self, 1, insn)? & size.mask();
+        let value = adc(self, size, lhs, rhs);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl AdcRMR for Emulator {
+    fn handle_adc_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let op1_r = insn.op1_reg();
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = adc(self, size, lhs, rhs);
+        handle_memop_write(self, 0, insn, value)?;
+
+        Ok(())
+    }
+}
+
+impl AdcRMIMM for Emulator {
+    fn handle_adc_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = insn.immediate() & size.mask();
+        let value = adc(self, size, lhs, rhs);
+        handle_memop_write(self, 0, insn, value)?;
+
+        Ok(())
+    }
+}
+
+impl SbbRR for Emulator {
+    fn handle_sbb_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = sbb(self, size, lhs, rhs);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl SbbRIMM for Emulator {
+    fn handle_sbb_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = insn.immediate() & size.mask();
+        let value = sbb(self, size, lhs, rhs);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl SbbRRM for Emulator {
+    fn handle_sbb_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = handle_memop_read(self, 1, insn)? & size.mask();
+        let value = sbb(self, size, lhs, rhs);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl SbbRMR for Emulator {
+    fn handle_sbb_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let op1_r = insn.op1_reg();
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = sbb(self, size, lhs, rhs);
+        handle_memop_write(self, 0, insn, value)?;
+
+        Ok(())
+    }
+}
+
+impl SbbRMIMM for Emulator {
+    fn handle_sbb_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = insn.immediate() & size.mask();
+        let value = sbb(self, size, lhs, rhs);
+        handle_memop_write(self, 0, insn, value)?;
+
+        Ok(())
+    }
+}
+
+impl MulRR for Emulator {
+    fn handle_mul_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = Some(insn.op1_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        // The high half would overwrite the low half
+        if op0_r.full() == op1_r.full() {
+            return Err(Exception::IllegalInstruction);
+        }
+
+        let size = op0_r.size();
+        let bits = size.to_size() * 8;
+
+        let lhs = self.regs.read(op0_r) as u128;
+        let rhs = (self.regs.read(op1_r) & size.mask()) as u128;
+        let value = lhs * rhs;
+        let (low, high) = (
+            value as u64 & size.mask(),
+            (value >> bits) as u64 & size.mask(),
+        );
+        self.regs.write(op0_r, low);
+        self.regs.write(op1_r, high);
+        update_imul_flags(self, size, low, high != 0);
+
+        Ok(())
+    }
+}
+
+impl MulhRR for Emulator {
+    fn handle_mulh_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let size = op0_r.size();
+        let bits = size.to_size() * 8;
+
+        let lhs = self.regs.read(op0_r) as u128;
+        let rhs = (self.regs.read(op1_r) & size.mask()) as u128;
+        let value = ((lhs * rhs) >> bits) as u64 & size.mask();
+        self.regs.write(op0_r, value);
+        update_imul_flags(self, size, value, value != 0);
+
+        Ok(())
+    }
+}
+
+impl MulhRRM for Emulator {
+    fn handle_mulh_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+
+        let size = op0_r.size();
+        let bits = size.to_size() * 8;
+
+        let lhs = self.regs.read(op0_r) as u128;
+        // A narrower memory operand is zero-extended from its own size
+        let rhs = (handle_memop_read(self, 1, insn)? & size.mask()) as u128;
+        let value = ((lhs * rhs) >> bits) as u64 & size.mask();
+        self.regs.write(op0_r, value);
+        update_imul_flags(self, size, value, value != 0);
+
+        Ok(())
+    }
+}
+
+impl ImulhRR for Emulator {
+    fn handle_imulh_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let size = op0_r.size();
+        let bits = size.to_size() * 8;
+
+        let lhs = sign_extend(size, self.regs.read(op0_r)) as i64 as i128;
+        let rhs = sign_extend(size, self.regs.read(op1_r) & size.mask()) as i64 as i128;
+        let wide = lhs * rhs;
+        let value = (wide >> bits) as u64 & size.mask();
+        let overflow = wide != sign_extend(size, wide as u64 & size.mask()) as i64 as i128;
+        self.regs.write(op0_r, value);
+        update_imul_flags(self, size, value, overflow);
+
+        Ok(())
+    }
+}
+
+impl ImulhRRM for Emulator {
+    fn handle_imulh_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+
+        let size = op0_r.size();
+        let bits = size.to_size() * 8;
+
+        let lhs = sign_extend(size, self.regs.read(op0_r)) as i64 as i128;
+        // A narrower memory operand is sign-extended from its own size
+        let rhs = sign_extend(memop_size(insn, 1)?, handle_memop_read(self, 1, insn)?);
+        let rhs = sign_extend(size, rhs & size.mask()) as i64 as i128;
+        let wide = lhs * rhs;
+        let value = (wide >> bits) as u64 & size.mask();
+        let overflow = wide != sign_extend(size, wide as u64 & size.mask()) as i64 as i128;
+        self.regs.write(op0_r, value);
+        update_imul_flags(self, size, value, overflow);
+
+        Ok(())
+    }
+}
+
+impl ShlRIMM for Emulator {
+    fn handle_shl_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let imm = insn.immediate();
+
+        let value = shl(self, r.size(), self.regs.read(r), imm);
+        self.regs.write(r, value);
+
+        Ok(())
+    }
+}
+
+impl ShlRR for Emulator {
+    fn handle_shl_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = shl(
+            self,
+            op0_r.size(),
+            self.regs.read(op0_r),
+            self.regs.read(op1_r),
+        );
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl ShrRIMM for Emulator {
+    fn handle_shr_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let imm = insn.immediate();
+
+        let value = shr(self, r.size(), self.regs.read(r), imm);
+        self.regs.write(r, value);
+
+        Ok(())
+    }
+}
+
+impl ShrRR for Emulator {
+    fn handle_shr_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = shr(
+            self,
+            op0_r.size(),
+            self.regs.read(op0_r),
+            self.regs.read(op1_r),
+        );
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl SarRIMM for Emulator {
+    fn handle_sar_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let imm = insn.immediate();
+
+        let value = sar(self, r.size(), self.regs.read(r), imm);
+        self.regs.write(r, value);
+
+        Ok(())
+    }
+}
+
+impl SarRR for Emulator {
+    fn handle_sar_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = sar(
+            self,
+            op0_r.size(),
+            self.regs.read(op0_r),
+            self.regs.read(op1_r),
+        );
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl RolRIMM for Emulator {
+    fn handle_rol_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let imm = insn.immediate();
+
+        let value = rol(self, r.size(), self.regs.read(r), imm);
+        self.regs.write(r, value);
+
+        Ok(())
+    }
+}
+
+impl RolRR for Emulator {
+    fn handle_rol_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = rol(
+            self,
+            op0_r.size(),
+            self.regs.read(op0_r),
+            self.regs.read(op1_r),
+        );
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl RorRIMM for Emulator {
+    fn handle_ror_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let imm = insn.immediate();
+
+        let value = ror(self, r.size(), self.regs.read(r), imm);
+        self.regs.write(r, value);
+
+        Ok(())
+    }
+}
+
+impl RorRR for Emulator {
+    fn handle_ror_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = ror(
+            self,
+            op0_r.size(),
+            self.regs.read(op0_r),
+            self.regs.read(op1_r),
+        );
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl RclRIMM for Emulator {
+    fn handle_rcl_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let imm = insn.immediate();
+
+        let value = rcl(self, r.size(), self.regs.read(r), imm);
+        self.regs.write(r, value);
+
+        Ok(())
+    }
+}
+
+impl RclRR for Emulator {
+    fn handle_rcl_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = rcl(
+            self,
+            op0_r.size(),
+            self.regs.read(op0_r),
+            self.regs.read(op1_r),
+        );
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl RcrRIMM for Emulator {
+    fn handle_rcr_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let imm = insn.immediate();
+
+        let value = rcr(self, r.size(), self.regs.read(r), imm);
+        self.regs.write(r, value);
+
+        Ok(())
+    }
+}
+
+impl RcrRR for Emulator {
+    fn handle_rcr_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = rcr(
+            self,
+            op0_r.size(),
+            self.regs.read(op0_r),
+            self.regs.read(op1_r),
+        );
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl PopcntRR for Emulator {
+    fn handle_popcnt_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = popcnt(self, self.regs.read(op1_r));
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl PopcntRRM for Emulator {
+    fn handle_popcnt_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+
+        let value = handle_memop_read(self, 1, insn)?;
+        let value = popcnt(self, value);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl LzcntRR for Emulator {
+    fn handle_lzcnt_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = lzcnt(self, op1_r.size(), self.regs.read(op1_r));
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl LzcntRRM for Emulator {
+    fn handle_lzcnt_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = memop_size(insn, 1)?;
+
+        let value = handle_memop_read(self, 1, insn)?;
+        let value = lzcnt(self, size, value);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl TzcntRR for Emulator {
+    fn handle_tzcnt_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let op1_r = insn.op1_reg();
+
+        let value = tzcnt(self, op1_r.size(), self.regs.read(op1_r));
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl TzcntRRM for Emulator {
+    fn handle_tzcnt_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = memop_size(insn, 1)?;
+
+        let value = handle_memop_read(self, 1, insn)?;
+        let value = tzcnt(self, size, value);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl BswapR for Emulator {
+    fn handle_bswap_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+
+        let bits = op0_r.size().to_size() * 8;
+        let value = self.regs.read(op0_r).swap_bytes() >> (64 - bits);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl BtRR for Emulator {
+    fn handle_bt_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let offset = self.regs.read(insn.op1_reg());
+
+        let value = self.regs.read(op0_r);
+        bit_select(self, size, value, offset);
+
+        Ok(())
+    }
+}
+
+impl BtRIMM for Emulator {
+    fn handle_bt_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let offset = insn.immediate();
+
+        let value = self.regs.read(op0_r);
+        bit_select(self, size, value, offset);
+
+        Ok(())
+    }
+}
+
+impl BtRMR for Emulator {
+    fn handle_bt_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let offset = self.regs.read(insn.op1_reg());
+
+        let value = handle_memop_read(self, 0, insn)?;
+        bit_select(self, size, value, offset);
+
+        Ok(())
+    }
+}
+
+impl BtRMIMM for Emulator {
+    fn handle_bt_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let offset = insn.immediate();
+
+        let value = handle_memop_read(self, 0, insn)?;
+        bit_select(self, size, value, offset);
+
+        Ok(())
+    }
+}
+
+impl BtsRR for Emulator {
+    fn handle_bts_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let offset = self.regs.read(insn.op1_reg());
+
+        let value = self.regs.read(op0_r);
+        let mask = bit_select(self, size, value, offset);
+        self.regs.write(op0_r, value | mask);
+
+        Ok(())
+    }
+}
+
+impl BtsRIMM for Emulator {
+    fn handle_bts_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let offset = insn.immediate();
+
+        let value = self.regs.read(op0_r);
+        let mask = bit_select(self, size, value, offset);
+        self.regs.write(op0_r, value | mask);
+
+        Ok(())
+    }
+}
+
+impl BtsRMR for Emulator {
+    fn handle_bts_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let offset = self.regs.read(insn.op1_reg());
+
+        let value = handle_memop_read(self, 0, insn)?;
+        let mask = bit_select(self, size, value, offset);
+        handle_memop_write(self, 0, insn, value | mask)?;
+
+        Ok(())
+    }
+}
+
+impl BtsRMIMM for Emulator {
+    fn handle_bts_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let offset = insn.immediate();
+
+        let value = handle_memop_read(self, 0, insn)?;
+        let mask = bit_select(self, size, value, offset);
+        handle_memop_write(self, 0, insn, value | mask)?;
+
+        Ok(())
+    }
+}
+
+impl BtrRR for Emulator {
+    fn handle_btr_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let offset = self.regs.read(insn.op1_reg());
+
+        let value = self.regs.read(op0_r);
+        let mask = bit_select(self, size, value, offset);
+        self.regs.write(op0_r, value & !mask);
+
+        Ok(())
+    }
+}
+
+impl BtrRIMM for Emulator {
+    fn handle_btr_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let offset = insn.immediate();
+
+        let value = self.regs.read(op0_r);
+        let mask = bit_select(self, size, value, offset);
+        self.regs.write(op0_r, value & !mask);
+
+        Ok(())
+    }
+}
+
+impl BtrRMR for Emulator {
+    fn handle_btr_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let offset = self.regs.read(insn.op1_reg());
+
+        let value = handle_memop_read(self, 0, insn)?;
+        let mask = bit_select(self, size, value, offset);
+        handle_memop_write(self, 0, insn, value & !mask)?;
+
+        Ok(())
+    }
+}
+
+impl BtrRMIMM for Emulator {
+    fn handle_btr_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let offset = insn.immediate();
+
+        let value = handle_memop_read(self, 0, insn)?;
+        let mask = bit_select(self, size, value, offset);
+        handle_memop_write(self, 0, insn, value & !mask)?;
+
+        Ok(())
+    }
+}
+
+impl BtcRR for Emulator {
+    fn handle_btc_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let offset = self.regs.read(insn.op1_reg());
+
+        let value = self.regs.read(op0_r);
+        let mask = bit_select(self, size, value, offset);
+        self.regs.write(op0_r, value ^ mask);
+
+        Ok(())
+    }
+}
+
+impl BtcRIMM for Emulator {
+    fn handle_btc_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let offset = insn.immediate();
+
+        let value = self.regs.read(op0_r);
+        let mask = bit_select(self, size, value, offset);
+        self.regs.write(op0_r, value ^ mask);
+
+        Ok(())
+    }
+}
+
+impl BtcRMR for Emulator {
+    fn handle_btc_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let offset = self.regs.read(insn.op1_reg());
+
+        let value = handle_memop_read(self, 0, insn)?;
+        let mask = bit_select(self, size, value, offset);
+        handle_memop_write(self, 0, insn, value ^ mask)?;
+
+        Ok(())
+    }
+}
+
+impl BtcRMIMM for Emulator {
+    fn handle_btc_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let offset = insn.immediate();
+
+        let value = handle_memop_read(self, 0, insn)?;
+        let mask = bit_select(self, size, value, offset);
+        handle_memop_write(self, 0, insn, value ^ mask)?;
+
+        Ok(())
+    }
+}
+
+impl IncR for Emulator {
+    fn handle_inc_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+
+        let lhs = self.regs.read(op0_r);
+        let value = lhs.wrapping_add(1u64) & size.mask();
+        self.regs.write(op0_r, value);
+        update_incdec_flags(self, size, lhs, value, true);
+
+        Ok(())
+    }
+}
+
+impl DecR for Emulator {
+    fn handle_dec_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+
+        let lhs = self.regs.read(op0_r);
+        let value = lhs.wrapping_sub(1u64) & size.mask();
+        self.regs.write(op0_r, value);
+        update_incdec_flags(self, size, lhs, value, false);
+
+        Ok(())
+    }
+}
+
+impl IncRM for Emulator {
+    fn handle_inc_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let value = lhs.wrapping_add(1u64) & size.mask();
+        handle_memop_write(self, 0, insn, value)?;
+        update_incdec_flags(self, size, lhs, value, true);
+
+        Ok(())
+    }
+}
+
+impl DecRM for Emulator {
+    fn handle_dec_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let value = lhs.wrapping_sub(1u64) & size.mask();
+        handle_memop_write(self, 0, insn, value)?;
+        update_incdec_flags(self, size, lhs, value, false);
+
+        Ok(())
+    }
+}
+
+impl NotR for Emulator {
+    fn handle_not_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+
+        let value = !self.regs.read(op0_r);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl NotRM for Emulator {
+    fn handle_not_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let value = !handle_memop_read(self, 0, insn)? & size.mask();
+        handle_memop_write(self, 0, insn, value)?;
+
+        Ok(())
+    }
+}
+
+impl NegR for Emulator {
+    fn handle_neg_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+
+        let rhs = self.regs.read(op0_r);
+        let value = 0u64.wrapping_sub(rhs) & size.mask();
+        self.regs.write(op0_r, value);
+        update_sub_flags(self, size, 0, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl NegRM for Emulator {
+    fn handle_neg_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let rhs = handle_memop_read(self, 0, insn)?;
+        let value = 0u64.wrapping_sub(rhs) & size.mask();
+        handle_memop_write(self, 0, insn, value)?;
+        update_sub_flags(self, size, 0, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl TestRIMM for Emulator {
+    fn handle_test_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = insn.immediate() & size.mask();
+        let value = lhs & rhs;
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl TestRR for Emulator {
+    fn handle_test_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs & rhs;
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl CmpRIMM for Emulator {
+    fn handle_cmp_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = insn.immediate() & size.mask();
+        let value = lhs.wrapping_sub(rhs) & size.mask();
+        update_sub_flags(self, size, lhs, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl CmpRR for Emulator {
+    fn handle_cmp_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs.wrapping_sub(rhs) & size.mask();
+        update_sub_flags(self, size, lhs, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl TestRRM for Emulator {
+    fn handle_test_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = handle_memop_read(self, 1, insn)? & size.mask();
+        let value = lhs & rhs;
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl TestRMR for Emulator {
+    fn handle_test_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let op1_r = insn.op1_reg();
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs & rhs;
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl TestRMIMM for Emulator {
+    fn handle_test_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = insn.immediate() & size.mask();
+        let value = lhs & rhs;
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl CmpRRM for Emulator {
+    fn handle_cmp_r_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+
+        let lhs = self.regs.read(op0_r);
+        let rhs = handle_memop_read(self, 1, insn)? & size.mask();
+        let value = lhs.wrapping_sub(rhs) & size.mask();
+        update_sub_flags(self, size, lhs, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl CmpRMR for Emulator {
+    fn handle_cmp_rm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+        let op1_r = insn.op1_reg();
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = self.regs.read(op1_r) & size.mask();
+        let value = lhs.wrapping_sub(rhs) & size.mask();
+        update_sub_flags(self, size, lhs, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl CmpRMIMM for Emulator {
+    fn handle_cmp_rm_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let size = memop_size(insn, 0)?;
+
+        let lhs = handle_memop_read(self, 0, insn)?;
+        let rhs = insn.immediate() & size.mask();
+        let value = lhs.wrapping_sub(rhs) & size.mask();
+        update_sub_flags(self, size, lhs, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl Jmp for Emulator {
+    fn handle_jmp(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let target = insn.branch_target();
+
+        self.set_ip(self.ip().wrapping_add_signed(target));
+
+        Ok(())
+    }
+}
+
+impl JmpR for Emulator {
+    fn handle_jmp_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let value = self.regs.read(insn.op0_reg());
+        self.set_ip(value);
+
+        Ok(())
+    }
+}
+
+impl JmpRM for Emulator {
+    fn handle_jmp_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let value = handle_memop_read(self, 0, insn)?;
+        self.set_ip(value);
+
+        Ok(())
+    }
+}
+
+impl Jz for Emulator {
+    fn handle_jz(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::Z)
+    }
+}
+
+impl Jnz for Emulator {
+    fn handle_jnz(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::Nz)
+    }
+}
+
+impl Jle for Emulator {
+    fn handle_jle(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::Le)
+    }
+}
+
+impl Jg for Emulator {
+    fn handle_jg(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::G)
+    }
+}
+
+impl Jge for Emulator {
+    fn handle_jge(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::Ge)
+    }
+}
+
+impl Jb for Emulator {
+    fn handle_jb(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::B)
+    }
+}
+
+impl Jl for Emulator {
+    fn handle_jl(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::L)
+    }
+}
+
+impl Ja for Emulator {
+    fn handle_ja(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::A)
+    }
+}
+
+impl Jae for Emulator {
+    fn handle_jae(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::Ae)
+    }
+}
+
+impl Jbe for Emulator {
+    fn handle_jbe(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::Be)
+    }
+}
+
+impl Js for Emulator {
+    fn handle_js(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::S)
+    }
+}
+
+impl Jns for Emulator {
+    fn handle_jns(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::Ns)
+    }
+}
+
+impl Jo for Emulator {
+    fn handle_jo(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::O)
+    }
+}
+
+impl Jno for Emulator {
+    fn handle_jno(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::No)
+    }
+}
+
+impl Jp for Emulator {
+    fn handle_jp(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::P)
+    }
+}
+
+impl Jnp for Emulator {
+    fn handle_jnp(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_jcc(self, insn, Condition::Np)
+    }
+}
+
+impl CmovzRR for Emulator {
+    fn handle_cmovz_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::Z)
+    }
+}
+
+impl CmovnzRR for Emulator {
+    fn handle_cmovnz_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::Nz)
+    }
+}
+
+impl CmovleRR for Emulator {
+    fn handle_cmovle_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::Le)
+    }
+}
+
+impl CmovgRR for Emulator {
+    fn handle_cmovg_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::G)
+    }
+}
+
+impl CmovgeRR for Emulator {
+    fn handle_cmovge_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::Ge)
+    }
+}
+
+impl CmovbRR for Emulator {
+    fn handle_cmovb_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::B)
+    }
+}
+
+impl CmovlRR for Emulator {
+    fn handle_cmovl_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::L)
+    }
+}
+
+impl CmovaRR for Emulator {
+    fn handle_cmova_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::A)
+    }
+}
+
+impl CmovaeRR for Emulator {
+    fn handle_cmovae_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::Ae)
+    }
+}
+
+impl CmovbeRR for Emulator {
+    fn handle_cmovbe_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::Be)
+    }
+}
+
+impl CmovsRR for Emulator {
+    fn handle_cmovs_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::S)
+    }
+}
+
+impl CmovnsRR for Emulator {
+    fn handle_cmovns_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::Ns)
+    }
+}
+
+impl CmovoRR for Emulator {
+    fn handle_cmovo_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::O)
+    }
+}
+
+impl CmovnoRR for Emulator {
+    fn handle_cmovno_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::No)
+    }
+}
+
+impl CmovpRR for Emulator {
+    fn handle_cmovp_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::P)
+    }
+}
+
+impl CmovnpRR for Emulator {
+    fn handle_cmovnp_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_cmovcc(self, insn, Condition::Np)
+    }
+}
+
+impl SetzR for Emulator {
+    fn handle_setz_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::Z)
+    }
+}
+
+impl SetnzR for Emulator {
+    fn handle_setnz_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::Nz)
+    }
+}
+
+impl SetleR for Emulator {
+    fn handle_setle_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::Le)
+    }
+}
+
+impl SetgR for Emulator {
+    fn handle_setg_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::G)
+    }
+}
+
+impl SetgeR for Emulator {
+    fn handle_setge_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::Ge)
+    }
+}
+
+impl SetbR for Emulator {
+    fn handle_setb_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::B)
+    }
+}
+
+impl SetlR for Emulator {
+    fn handle_setl_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::L)
+    }
+}
+
+impl SetaR for Emulator {
+    fn handle_seta_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::A)
+    }
+}
+
+impl SetaeR for Emulator {
+    fn handle_setae_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::Ae)
+    }
+}
+
+impl SetbeR for Emulator {
+    fn handle_setbe_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::Be)
+    }
+}
+
+impl SetsR for Emulator {
+    fn handle_sets_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::S)
+    }
+}
+
+impl SetnsR for Emulator {
+    fn handle_setns_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::Ns)
+    }
+}
+
+impl SetoR for Emulator {
+    fn handle_seto_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::O)
+    }
+}
+
+impl SetnoR for Emulator {
+    fn handle_setno_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::No)
+    }
+}
+
+impl SetpR for Emulator {
+    fn handle_setp_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::P)
+    }
+}
+
+impl SetnpR for Emulator {
+    fn handle_setnp_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_setcc(self, insn, Condition::Np)
+    }
+}
+
+impl PushR for Emulator {
+    fn handle_push_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = insn.op0_reg();
+
+        self.push_u64(self.regs.read(op0_r))?;
+
+        Ok(())
+    }
+}
+
+impl PushIMM for Emulator {
+    fn handle_push_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let imm = insn.op0_immediate();
+
+        self.push_u64(imm)?;
+
+        Ok(())
+    }
+}
+
+impl PopR for Emulator {
+    fn handle_pop_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+
+        let value = self.pop_u64()?;
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl Call for Emulator {
+    fn handle_call(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let target = insn.branch_target();
+
+        // The IP already points to the next instruction, which is the return
+        // address
+        self.push_u64(self.ip())?;
+
+        self.set_ip(self.ip().wrapping_add_signed(target));
+
+        Ok(())
+    }
+}
+
+impl CallR for Emulator {
+    fn handle_call_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let value = self.regs.read(insn.op0_reg());
+        self.push_u64(self.ip())?;
+        self.set_ip(value);
+
+        Ok(())
+    }
+}
+
+impl CallRM for Emulator {
+    fn handle_call_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        // Reads the target first so that `call [sp]` sees the value before
+        // the return address is pushed
+        let value = handle_memop_read(self, 0, insn)?;
+        self.push_u64(self.ip())?;
+        self.set_ip(value);
+
+        Ok(())
+    }
+}
+
+impl Ret for Emulator {
+    fn handle_ret(&mut self, _insn: &Instruction) -> Result<(), Exception> {
+        let value = self.pop_u64()?;
+        self.set_ip(value);
+
+        Ok(())
+    }
+}
+
+impl RepMovsb for Emulator {
+    fn handle_rep_movsb(&mut self, _insn: &Instruction) -> Result<(), Exception> {
+        let dst = self.regs.read(Register::R0);
+        let src = self.regs.read(Register::R1);
+        let count = self.regs.read(Register::R2);
+        if count == 0 {
+            return Ok(());
+        }
+
+        self.dram
+            .copy_within(src as usize, dst as usize, count as usize)?;
+        self.regs.write(Register::R0, dst.wrapping_add(count));
+        self.regs.write(Register::R1, src.wrapping_add(count));
+        self.regs.write(Register::R2, 0);
+
+        Ok(())
+    }
+}
+
+impl RepStosb for Emulator {
+    fn handle_rep_stosb(&mut self, _insn: &Instruction) -> Result<(), Exception> {
+        let dst = self.regs.read(Register::R0);
+        let value = self.regs.read(Register::R1) as u8;
+        let count = self.regs.read(Register::R2);
+        if count == 0 {
+            return Ok(());
+        }
+
+        self.dram
+            .slice_mut(dst as usize, count as usize)?
+            .fill(value);
+        self.regs.write(Register::R0, dst.wrapping_add(count));
+        self.regs.write(Register::R2, 0);
+
+        Ok(())
+    }
+}
+
+impl RepCmpsb for Emulator {
+    fn handle_rep_cmpsb(&mut self, _insn: &Instruction) -> Result<(), Exception> {
+        let lhs = self.regs.read(Register::R0);
+        let rhs = self.regs.read(Register::R1);
+        let count = self.regs.read(Register::R2);
+        if count == 0 {
+            return Ok(());
+        }
+
+        let a = self.dram.slice(lhs as usize, count as usize)?;
+        let b = self.dram.slice(rhs as usize, count as usize)?;
+        // Stops right after the first mismatch, or at the last byte
+        let last = a
+            .iter()
+            .zip(b)
+            .position(|(x, y)| x != y)
+            .unwrap_or(count as usize - 1);
+        let (x, y) = (a[last] as u64, b[last] as u64);
+
+        let compared = last as u64 + 1;
+        self.regs.write(Register::R0, lhs.wrapping_add(compared));
+        self.regs.write(Register::R1, rhs.wrapping_add(compared));
+        self.regs.write(Register::R2, count - compared);
+        let value = x.wrapping_sub(y) & OperandSize::Byte.mask();
+        update_sub_flags(self, OperandSize::Byte, x, y, value);
+
+        Ok(())
+    }
+}
+
+impl AddRRRI for Emulator {
+    fn handle_add_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let lhs = self.regs.read(op1_r) & size.mask();
+        let rhs = op2 & size.mask();
+        let value = lhs.wrapping_add(rhs) & size.mask();
+        self.regs.write(op0_r, value);
+        update_add_flags(self, size, lhs, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl SubRRRI for Emulator {
+    fn handle_sub_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let lhs = self.regs.read(op1_r) & size.mask();
+        let rhs = op2 & size.mask();
+        let value = lhs.wrapping_sub(rhs) & size.mask();
+        self.regs.write(op0_r, value);
+        update_sub_flags(self, size, lhs, rhs, value);
+
+        Ok(())
+    }
+}
+
+impl AndRRRI for Emulator {
+    fn handle_and_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let lhs = self.regs.read(op1_r) & size.mask();
+        let rhs = op2 & size.mask();
+        let value = lhs & rhs;
+        self.regs.write(op0_r, value);
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl OrRRRI for Emulator {
+    fn handle_or_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let lhs = self.regs.read(op1_r) & size.mask();
+        let rhs = op2 & size.mask();
+        let value = lhs | rhs;
+        self.regs.write(op0_r, value);
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl XorRRRI for Emulator {
+    fn handle_xor_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let lhs = self.regs.read(op1_r) & size.mask();
+        let rhs = op2 & size.mask();
+        let value = lhs ^ rhs;
+        self.regs.write(op0_r, value);
+        update_logic_flags(self, size, value);
+
+        Ok(())
+    }
+}
+
+impl ImulRRRI for Emulator {
+    fn handle_imul_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let lhs = self.regs.read(op1_r) & size.mask();
+        let rhs = op2 & size.mask();
+        let wide = sign_extend(size, lhs) as i64 as i128 * sign_extend(size, rhs) as i64 as i128;
+        let value = wide as u64 & size.mask();
+        self.regs.write(op0_r, value);
+        update_imul_flags(
+            self,
+            size,
+            value,
+            wide != sign_extend(size, value) as i64 as i128,
+        );
+
+        Ok(())
+    }
+}
+
+impl ShlRRRI for Emulator {
+    fn handle_shl_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let value = shl(self, size, self.regs.read(op1_r) & size.mask(), op2);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl ShrRRRI for Emulator {
+    fn handle_shr_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let value = shr(self, size, self.regs.read(op1_r) & size.mask(), op2);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl SarRRRI for Emulator {
+    fn handle_sar_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let value = sar(self, size, self.regs.read(op1_r) & size.mask(), op2);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl AdcRRRI for Emulator {
+    fn handle_adc_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let lhs = self.regs.read(op1_r) & size.mask();
+        let rhs = op2 & size.mask();
+        let value = adc(self, size, lhs, rhs);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl SbbRRRI for Emulator {
+    fn handle_sbb_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let lhs = self.regs.read(op1_r) & size.mask();
+        let rhs = op2 & size.mask();
+        let value = sbb(self, size, lhs, rhs);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl MulhRRRI for Emulator {
+    fn handle_mulh_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+        let bits = size.to_size() * 8;
+
+        let lhs = (self.regs.read(op1_r) & size.mask()) as u128;
+        let rhs = (op2 & size.mask()) as u128;
+        let value = ((lhs * rhs) >> bits) as u64 & size.mask();
+        self.regs.write(op0_r, value);
+        update_imul_flags(self, size, value, value != 0);
+
+        Ok(())
+    }
+}
+
+impl ImulhRRRI for Emulator {
+    fn handle_imulh_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+        let bits = size.to_size() * 8;
+
+        let lhs = self.regs.read(op1_r) & size.mask();
+        let rhs = op2 & size.mask();
+        let wide = sign_extend(size, lhs) as i64 as i128 * sign_extend(size, rhs) as i64 as i128;
+        let value = (wide >> bits) as u64 & size.mask();
+        let overflow = wide != sign_extend(size, wide as u64 & size.mask()) as i64 as i128;
+        self.regs.write(op0_r, value);
+        update_imul_flags(self, size, value, overflow);
+
+        Ok(())
+    }
+}
+
+impl DivRRRI for Emulator {
+    fn handle_div_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let lhs = self.regs.read(op1_r) & size.mask();
+        let rhs = op2 & size.mask();
+        if rhs == 0 {
+            return Err(Exception::DivideByZero);
+        }
+
+        self.regs.write(op0_r, lhs / rhs);
+
+        Ok(())
+    }
+}
+
+impl IdivRRRI for Emulator {
+    fn handle_idiv_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let lhs = sign_extend(size, self.regs.read(op1_r) & size.mask()) as i64;
+        let rhs = sign_extend(size, op2 & size.mask()) as i64;
+        if rhs == 0 {
+            return Err(Exception::DivideByZero);
+        }
+
+        self.regs.write(op0_r, lhs.wrapping_div(rhs) as u64);
+
+        Ok(())
+    }
+}
+
+impl RemRRRI for Emulator {
+    fn handle_rem_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let lhs = self.regs.read(op1_r) & size.mask();
+        let rhs = op2 & size.mask();
+        if rhs == 0 {
+            return Err(Exception::DivideByZero);
+        }
+
+        self.regs.write(op0_r, lhs % rhs);
+
+        Ok(())
+    }
+}
+
+impl IremRRRI for Emulator {
+    fn handle_irem_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let lhs = sign_extend(size, self.regs.read(op1_r) & size.mask()) as i64;
+        let rhs = sign_extend(size, op2 & size.mask()) as i64;
+        if rhs == 0 {
+            return Err(Exception::DivideByZero);
+        }
+
+        self.regs.write(op0_r, lhs.wrapping_rem(rhs) as u64);
+
+        Ok(())
+    }
+}
+
+impl RolRRRI for Emulator {
+    fn handle_rol_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let value = rol(self, size, self.regs.read(op1_r) & size.mask(), op2);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl RorRRRI for Emulator {
+    fn handle_ror_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let value = ror(self, size, self.regs.read(op1_r) & size.mask(), op2);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl RclRRRI for Emulator {
+    fn handle_rcl_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let value = rcl(self, size, self.regs.read(op1_r) & size.mask(), op2);
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl RcrRRRI for Emulator {
+    fn handle_rcr_r_r_ri(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let size = op0_r.size();
+        let op1_r = insn.op1_reg();
+        let op2 = op2_value(self, insn)?;
+
+        let value = rcr(self, size, self.regs.read(op1_r) & size.mask(), op2);
+        self.regs.write(op0_r, value);
+
         Ok(())
     }
 }
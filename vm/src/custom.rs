@@ -0,0 +1,103 @@
+//! This module implements custom instructions registered by the host.
+//!
+//! The second page reserves [`OpCode::Custom`] for instructions the ISA does
+//! not define: it is followed by a custom opcode byte, so `FFh 01h 00h` to
+//! `FFh 01h FFh` form a range of 256 custom opcodes. The host registers a
+//! [`CustomHandler`] for a custom opcode at runtime, along with the
+//! [`OperandKind`] of each of its operands so that [`Emulator::decode`] can
+//! parse them. The handler receives the decoded [`Instruction`] and reads its
+//! operands with the usual accessors, e.g. [`Instruction::op0_reg`], and a
+//! memory operand with [`Emulator::read_memop`] and [`Emulator::write_memop`].
+//!
+//! Executing a custom opcode without a handler raises an
+//! [`Exception::IllegalInstruction`]. The custom instructions belong to
+//! [`Extension::Custom`], so a guest can check for them with `cpuid`.
+//!
+//! [`OpCode::Custom`]: crate::isa::OpCode::Custom
+//! [`Extension::Custom`]: crate::isa::Extension::Custom
+
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use crate::{
+    emulator::Emulator,
+    error::{self, Error},
+    exception::Exception,
+    isa::Instruction,
+};
+
+/// Represents the kind of an operand of a custom instruction.
+///
+/// The operand is encoded like the operand of the same kind of a built-in
+/// instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandKind {
+    /// A [`Register`](crate::emulator::Register).
+    Register,
+    /// A [`FloatRegister`](crate::emulator::FloatRegister).
+    FloatRegister,
+    /// A [`VectorRegister`](crate::emulator::VectorRegister).
+    VectorRegister,
+    /// A memory operand, decoded as an
+    /// [`Operand::Memory`](crate::isa::Operand::Memory).
+    Memory,
+    /// A 64-bit immediate.
+    Immediate,
+}
+
+/// The handler executing a custom instruction.
+pub type CustomHandler =
+    Arc<dyn Fn(&mut Emulator, &Instruction) -> Result<(), Exception> + Send + Sync>;
+
+/// Represents a custom instruction registered on the [`Emulator`].
+#[derive(Clone)]
+pub struct CustomInstruction {
+    /// The kinds of the operands, at most three.
+    pub operands: Vec<OperandKind>,
+    /// The handler executing the instruction.
+    pub handler: CustomHandler,
+}
+
+impl fmt::Debug for CustomInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomInstruction")
+            .field("operands", &self.operands)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The custom instructions registered on the [`Emulator`], by custom opcode.
+///
+/// Cloning the set, e.g. along with the [`Emulator`], shares the handlers.
+#[derive(Debug, Clone, Default)]
+pub struct CustomInstructions(BTreeMap<u8, CustomInstruction>);
+
+impl CustomInstructions {
+    /// Make an new instance of [`CustomInstructions`] without any instruction
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `insn` for `opcode`, replacing the instruction previously
+    /// registered there, or fails if `insn` takes more than three operands.
+    pub fn register(
+        &mut self,
+        opcode: u8,
+        insn: CustomInstruction,
+    ) -> error::Result<Option<CustomInstruction>> {
+        if insn.operands.len() > 3 {
+            return Err(Error::TooManyOperands(insn.operands.len()));
+        }
+        Ok(self.0.insert(opcode, insn))
+    }
+
+    /// Unregisters the instruction of `opcode` and returns it.
+    pub fn unregister(&mut self, opcode: u8) -> Option<CustomInstruction> {
+        self.0.remove(&opcode)
+    }
+
+    /// Returns the instruction registered for `opcode`.
+    pub fn get(&self, opcode: u8) -> Option<&CustomInstruction> {
+        self.0.get(&opcode)
+    }
+}
@@ -10,16 +10,25 @@
 //! debugging, and exploring low-level system behavior.
 
 use core::fmt;
+use std::sync::Arc;
 
 use strum_macros::FromRepr;
 
 use crate::{
     alu::*,
-    exception::Exception,
-    isa::{Instruction, OpCode, Operand, OperandSize},
+    custom::{CustomInstruction, CustomInstructions, OperandKind},
+    error,
+    exception::{AccessKind, Exception, ExceptionRecord, MemoryAccess},
+    fpu::*,
+    isa::{Extensions, Instruction, OpCode, Operand, OperandSize, ESCAPE, ISA_VERSION},
+    port::*,
     ram::Dram,
+    simd::*,
 };
 
+/// The default stack size is set to 64KiB.
+pub const DEFAULT_STACK_SIZE: usize = 64 * 1024;
+
 /// Represents the state of a virtual CPU (Emulator).
 ///
 /// This struct holds the current state of the emulator, including its registers
@@ -30,19 +39,34 @@ use crate::{
 pub struct Emulator {
     /// A set of registers representing the CPU state.
     pub regs: Registers,
+    /// A set of floating-point registers.
+    pub fregs: FloatRegisters,
+    /// A set of vector registers.
+    pub vregs: VectorRegisters,
     /// The Dynamic Random-Access Memory (DRAM) of the emulator.
     pub dram: Dram,
+    /// The devices mapped in the I/O port space.
+    pub ports: PortBus,
+    /// The ISA extensions enabled on this emulator, all of them by default.
+    pub extensions: Extensions,
+    /// The custom instructions registered by the host.
+    pub custom: CustomInstructions,
     /// The clock cycle state
     pub cycle: u64,
+    /// The initial value of the [`Register::SP`].
+    ///
+    /// The stack grows downwards from this address, popping above it raises
+    /// an [`Exception::StackUnderflow`].
+    pub stack_base: u64,
+    /// The lowest address of the stack region.
+    ///
+    /// Pushing below this address raises an [`Exception::StackOverflow`].
+    pub stack_limit: u64,
 }
 
 impl Default for Emulator {
     fn default() -> Self {
-        Self {
-            regs: Default::default(),
-            dram: Default::default(),
-            cycle: 0,
-        }
+        Self::with_bytecode(Vec::new())
     }
 }
 
@@ -56,18 +80,65 @@ impl Emulator {
     /// Make an new instance of [`Emulator`] with a VM bytecode
     #[must_use]
     pub fn with_bytecode<S: Into<Vec<u8>>>(bytecode: S) -> Self {
-        Self {
-            dram: Dram::with_data(bytecode),
-            ..Default::default()
-        }
+        Self::with_stack(bytecode, DEFAULT_STACK_SIZE)
+    }
+
+    /// Make an new instance of [`Emulator`] with a VM bytecode and a stack of
+    /// the specified size.
+    ///
+    /// The stack region is placed right after the bytecode in the DRAM and the
+    /// [`Register::SP`] initially points to the end of it.
+    #[must_use]
+    pub fn with_stack<S: Into<Vec<u8>>>(bytecode: S, stack_size: usize) -> Self {
+        let mut dram = Dram::with_data(bytecode);
+        let stack_limit = dram.0.len() as u64;
+        dram.0.resize(dram.0.len() + stack_size, 0);
+        let stack_base = dram.0.len() as u64;
+
+        let mut emulator = Self {
+            regs: Default::default(),
+            fregs: Default::default(),
+            vregs: Default::default(),
+            dram,
+            ports: Default::default(),
+            extensions: Default::default(),
+            custom: Default::default(),
+            cycle: 0,
+            stack_base,
+            stack_limit,
+        };
+        emulator.reset();
+        emulator
     }
 
     /// Reset the CPU state
     pub fn reset(&mut self) {
         self.regs.reset();
+        self.fregs.reset();
+        self.vregs.reset();
+        self.regs.write(Register::SP, self.stack_base);
         self.cycle = 0;
     }
 
+    /// Registers `handler` for the custom `opcode`, whose operands are decoded
+    /// as `operands`, and returns the instruction previously registered.
+    ///
+    /// Fails if more than three operands are declared.
+    pub fn register_custom<F>(
+        &mut self,
+        opcode: u8,
+        operands: &[OperandKind],
+        handler: F,
+    ) -> error::Result<Option<CustomInstruction>>
+    where
+        F: Fn(&mut Emulator, &Instruction) -> Result<(), Exception> + Send + Sync + 'static,
+    {
+        self.custom.register(opcode, CustomInstruction {
+            operands: operands.to_vec(),
+            handler: Arc::new(handler),
+        })
+    }
+
     /// Fetches an 8-bit unsigned integer from DRAM at the current
     /// instruction pointer (IP) and increments IP if succeeded.
     ///
@@ -76,10 +147,13 @@ impl Emulator {
     /// - `Err(Exception::AccessViolation)`: If the read operation exceeds
     ///   memory bounds.
     pub fn fetch_u8(&mut self) -> Result<u8, Exception> {
-        self.dram.read_u8(self.ip() as usize).and_then(|x| {
-            self.increment_ip(core::mem::size_of_val(&x) as u64);
-            Ok(x)
-        })
+        self.dram
+            .read_u8(self.ip() as usize)
+            .map_err(as_fetch)
+            .and_then(|x| {
+                self.increment_ip(core::mem::size_of_val(&x) as u64);
+                Ok(x)
+            })
     }
 
     /// Fetches an 16-bit unsigned integer from DRAM at the current
@@ -90,10 +164,13 @@ impl Emulator {
     /// - `Err(Exception::AccessViolation)`: If the read operation exceeds
     ///   memory bounds.
     pub fn fetch_u16le(&mut self) -> Result<u16, Exception> {
-        self.dram.read_u16le(self.ip() as usize).map(|x| {
-            self.increment_ip(core::mem::size_of_val(&x) as u64);
-            x
-        })
+        self.dram
+            .read_u16le(self.ip() as usize)
+            .map_err(as_fetch)
+            .map(|x| {
+                self.increment_ip(core::mem::size_of_val(&x) as u64);
+                x
+            })
     }
 
     /// Fetches an 32-bit unsigned integer from DRAM at the current
@@ -104,10 +181,13 @@ impl Emulator {
     /// - `Err(Exception::AccessViolation)`: If the read operation exceeds
     ///   memory bounds.
     pub fn fetch_u32le(&mut self) -> Result<u32, Exception> {
-        self.dram.read_u32le(self.ip() as usize).map(|x| {
-            self.increment_ip(core::mem::size_of_val(&x) as u64);
-            x
-        })
+        self.dram
+            .read_u32le(self.ip() as usize)
+            .map_err(as_fetch)
+            .map(|x| {
+                self.increment_ip(core::mem::size_of_val(&x) as u64);
+                x
+            })
     }
 
     /// Fetches an 64-bit unsigned integer from DRAM at the current
@@ -118,10 +198,13 @@ impl Emulator {
     /// - `Err(Exception::AccessViolation)`: If the read operation exceeds
     ///   memory bounds.
     pub fn fetch_u64le(&mut self) -> Result<u64, Exception> {
-        self.dram.read_u64le(self.ip() as usize).map(|x| {
-            self.increment_ip(core::mem::size_of_val(&x) as u64);
-            x
-        })
+        self.dram
+            .read_u64le(self.ip() as usize)
+            .map_err(as_fetch)
+            .map(|x| {
+                self.increment_ip(core::mem::size_of_val(&x) as u64);
+                x
+            })
     }
 
     /// Returns the current instruction pointer (IP)
@@ -139,6 +222,89 @@ impl Emulator {
         self.regs.write(Register::IP, value);
     }
 
+    /// Pushes a 64-bit value onto the stack.
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the value is pushed successfully.
+    /// - `Err(Exception::StackOverflow)`: If the stack has no room for the
+    ///   value.
+    pub fn push_u64(&mut self, value: u64) -> Result<(), Exception> {
+        const SIZE: u64 = core::mem::size_of::<u64>() as u64;
+        let sp = self.regs.read(Register::SP);
+        if sp.saturating_sub(self.stack_limit) < SIZE {
+            return Err(Exception::StackOverflow);
+        }
+
+        self.dram.write_u64le((sp - SIZE) as usize, value)?;
+        self.regs.write(Register::SP, sp - SIZE);
+
+        Ok(())
+    }
+
+    /// Pops a 64-bit value from the stack.
+    ///
+    /// # Returns
+    /// - `Ok(u64)`: The value popped from the stack.
+    /// - `Err(Exception::StackUnderflow)`: If the stack is empty.
+    pub fn pop_u64(&mut self) -> Result<u64, Exception> {
+        const SIZE: u64 = core::mem::size_of::<u64>() as u64;
+        let sp = self.regs.read(Register::SP);
+        if sp < self.stack_limit || self.stack_base.saturating_sub(sp) < SIZE {
+            return Err(Exception::StackUnderflow);
+        }
+
+        let value = self.dram.read_u64le(sp as usize)?;
+        self.regs.write(Register::SP, sp + SIZE);
+
+        Ok(value)
+    }
+
+    /// Reads the memory operand `op` of `insn` from the DRAM, e.g. for a
+    /// custom instruction.
+    ///
+    /// # Returns
+    /// - `Ok(u64)`: The value read, zero-extended from the operand size.
+    /// - `Err(Exception::IllegalInstruction)`: If the operand is not a memory
+    ///   operand of at most 64 bits.
+    /// - `Err(Exception::AccessViolation)`: If the operand is out of bounds.
+    pub fn read_memop(&mut self, insn: &Instruction, op: usize) -> Result<u64, Exception> {
+        handle_memop_read(self, op, insn)
+    }
+
+    /// Writes `value`, truncated to the operand size, to the memory operand
+    /// `op` of `insn` in the DRAM, e.g. for a custom instruction.
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the value is written successfully.
+    /// - `Err(Exception::IllegalInstruction)`: If the operand is not a memory
+    ///   operand of at most 64 bits.
+    /// - `Err(Exception::AccessViolation)`: If the operand is out of bounds.
+    pub fn write_memop(
+        &mut self,
+        insn: &Instruction,
+        op: usize,
+        value: u64,
+    ) -> Result<(), Exception> {
+        handle_memop_write(self, op, insn, value)
+    }
+
+    /// Fetches an [`OpCode`], escaped to the second page or not, and checks
+    /// that its extension is enabled.
+    fn fetch_opcode(&mut self) -> Result<OpCode, Exception> {
+        let opcode = match self.fetch_u8()? {
+            ESCAPE => ((ESCAPE as u16) << 8) | self.fetch_u8()? as u16,
+            opcode => opcode as u16,
+        };
+        let opcode = OpCode::from_repr(opcode).ok_or(Exception::IllegalInstruction)?;
+        if let Some(ext) = opcode.extension() {
+            if !self.extensions.contains(ext) {
+                return Err(Exception::IllegalInstruction);
+            }
+        }
+
+        Ok(opcode)
+    }
+
     /// Decodes Op/R/IMM pattern instructions.
     fn decode_r_imm(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
         let reg = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
@@ -149,41 +315,41 @@ impl Emulator {
         Ok(())
     }
 
-    /// Decodes Op/R/RM pattern instructions.
-    fn decode_r_rm(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
-        let reg = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
-        insn.set_op0_reg(reg);
-
+    /// Fetches an [`Operand::Memory`] and validates its scale.
+    fn fetch_mem(&mut self) -> Result<Operand, Exception> {
         let size = OperandSize::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
-        let displacement = self.fetch_u64le()?;
+        let displacement = self.fetch_u64le()? as i64;
         let scale = self.fetch_u8()?;
+        if !matches!(scale, 1 | 2 | 4 | 8) {
+            return Err(Exception::IllegalInstruction);
+        }
         let index_reg = Register::from_repr(self.fetch_u8()?);
         let base_reg = Register::from_repr(self.fetch_u8()?);
-        insn.set_op1_mem(Operand::Memory {
+
+        Ok(Operand::Memory {
             size,
             displacement,
             scale,
             index_reg,
             base_reg,
-        });
+        })
+    }
+
+    /// Decodes Op/R/RM pattern instructions.
+    fn decode_r_rm(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let reg = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
+        insn.set_op0_reg(reg);
+
+        let mem = self.fetch_mem()?;
+        insn.set_op1_mem(mem);
 
         Ok(())
     }
 
     /// Decodes Op/RM/R pattern instructions.
     fn decode_rm_r(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
-        let size = OperandSize::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
-        let displacement = self.fetch_u64le()?;
-        let scale = self.fetch_u8()?;
-        let index_reg = Register::from_repr(self.fetch_u8()?);
-        let base_reg = Register::from_repr(self.fetch_u8()?);
-        insn.set_op0_mem(Operand::Memory {
-            size,
-            displacement,
-            scale,
-            index_reg,
-            base_reg,
-        });
+        let mem = self.fetch_mem()?;
+        insn.set_op0_mem(mem);
 
         let reg = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
         insn.set_op1_reg(reg);
@@ -191,6 +357,17 @@ impl Emulator {
         Ok(())
     }
 
+    /// Decodes Op/RM/IMM pattern instructions.
+    fn decode_rm_imm(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let mem = self.fetch_mem()?;
+        insn.set_op0_mem(mem);
+
+        let imm = self.fetch_u64le()?;
+        insn.set_immediate(imm);
+
+        Ok(())
+    }
+
     /// Decodes Op/R/R pattern instructions.
     fn decode_r_r(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
         let reg0 = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
@@ -201,6 +378,23 @@ impl Emulator {
         Ok(())
     }
 
+    /// Decodes Op/R/R/R and Op/R/R/FFh/IMM pattern instructions.
+    fn decode_r_r_ri(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let reg0 = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
+        let reg1 = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
+        insn.set_op0_reg(reg0);
+        insn.set_op1_reg(reg1);
+        match self.fetch_u8()? {
+            u8::MAX => insn.set_op2_immediate(self.fetch_u64le()?),
+            reg2 => {
+                let reg2 = Register::from_repr(reg2).ok_or(Exception::IllegalInstruction)?;
+                insn.set_op2_reg(reg2);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Decodes Op/R pattern instructions.
     fn decode_r(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
         let reg0 = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
@@ -209,7 +403,180 @@ impl Emulator {
         Ok(())
     }
 
+    /// Decodes Op/RM pattern instructions.
+    fn decode_rm(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let mem = self.fetch_mem()?;
+        insn.set_op0_mem(mem);
+
+        Ok(())
+    }
+
+    /// Fetches a [`FloatRegister`].
+    fn fetch_freg(&mut self) -> Result<FloatRegister, Exception> {
+        FloatRegister::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)
+    }
+
+    /// Fetches a [`VectorRegister`].
+    fn fetch_vreg(&mut self) -> Result<VectorRegister, Exception> {
+        VectorRegister::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)
+    }
+
+    /// Decodes the custom opcode and the operands registered for it.
+    fn decode_custom(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        insn.custom = self.fetch_u8()?;
+        let operands = match self.custom.get(insn.custom) {
+            Some(custom) => custom.operands.clone(),
+            None => return Err(Exception::IllegalInstruction),
+        };
+
+        for (i, kind) in operands.into_iter().enumerate() {
+            insn.operands[i] = match kind {
+                OperandKind::Register => Operand::Register(
+                    Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?,
+                ),
+                OperandKind::FloatRegister => Operand::FloatRegister(self.fetch_freg()?),
+                OperandKind::VectorRegister => Operand::VectorRegister(self.fetch_vreg()?),
+                OperandKind::Memory => self.fetch_mem()?,
+                OperandKind::Immediate => Operand::Immediate64(self.fetch_u64le()?),
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Decodes Op/V/V pattern instructions.
+    fn decode_v_v(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let vreg0 = self.fetch_vreg()?;
+        let vreg1 = self.fetch_vreg()?;
+        insn.set_op0_vreg(vreg0);
+        insn.set_op1_vreg(vreg1);
+
+        Ok(())
+    }
+
+    /// Decodes Op/V/RM pattern instructions.
+    fn decode_v_rm(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let vreg = self.fetch_vreg()?;
+        insn.set_op0_vreg(vreg);
+
+        let mem = self.fetch_mem()?;
+        insn.set_op1_mem(mem);
+
+        Ok(())
+    }
+
+    /// Decodes Op/RM/V pattern instructions.
+    fn decode_rm_v(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let mem = self.fetch_mem()?;
+        insn.set_op0_mem(mem);
+
+        let vreg = self.fetch_vreg()?;
+        insn.set_op1_vreg(vreg);
+
+        Ok(())
+    }
+
+    /// Decodes Op/V/R pattern instructions.
+    fn decode_v_r(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let vreg = self.fetch_vreg()?;
+        let reg = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
+        insn.set_op0_vreg(vreg);
+        insn.set_op1_reg(reg);
+
+        Ok(())
+    }
+
+    /// Decodes Op/R/V pattern instructions.
+    fn decode_r_v(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let reg = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
+        let vreg = self.fetch_vreg()?;
+        insn.set_op0_reg(reg);
+        insn.set_op1_vreg(vreg);
+
+        Ok(())
+    }
+
+    /// Decodes Op/F/F pattern instructions.
+    fn decode_f_f(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let freg0 = self.fetch_freg()?;
+        let freg1 = self.fetch_freg()?;
+        insn.set_op0_freg(freg0);
+        insn.set_op1_freg(freg1);
+
+        Ok(())
+    }
+
+    /// Decodes Op/F/RM pattern instructions.
+    fn decode_f_rm(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let freg = self.fetch_freg()?;
+        insn.set_op0_freg(freg);
+
+        let mem = self.fetch_mem()?;
+        insn.set_op1_mem(mem);
+
+        Ok(())
+    }
+
+    /// Decodes Op/RM/F pattern instructions.
+    fn decode_rm_f(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let mem = self.fetch_mem()?;
+        insn.set_op0_mem(mem);
+
+        let freg = self.fetch_freg()?;
+        insn.set_op1_freg(freg);
+
+        Ok(())
+    }
+
+    /// Decodes Op/F/IMM pattern instructions.
+    fn decode_f_imm(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let freg = self.fetch_freg()?;
+        let imm = self.fetch_u64le()?;
+        insn.set_op0_freg(freg);
+        insn.set_immediate(imm);
+
+        Ok(())
+    }
+
+    /// Decodes Op/F/R pattern instructions.
+    fn decode_f_r(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let freg = self.fetch_freg()?;
+        let reg = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
+        insn.set_op0_freg(freg);
+        insn.set_op1_reg(reg);
+
+        Ok(())
+    }
+
+    /// Decodes Op/R/F pattern instructions.
+    fn decode_r_f(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let reg = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
+        let freg = self.fetch_freg()?;
+        insn.set_op0_reg(reg);
+        insn.set_op1_freg(freg);
+
+        Ok(())
+    }
+
     /// Decodes Op/IMM pattern instructions.
+    fn decode_imm(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let imm = self.fetch_u64le()?;
+        insn.set_op0_immediate(imm);
+
+        Ok(())
+    }
+
+    /// Decodes Op/IMM/R pattern instructions.
+    fn decode_imm_r(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
+        let imm = self.fetch_u64le()?;
+        let reg = Register::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
+        insn.set_op0_immediate(imm);
+        insn.set_op1_reg(reg);
+
+        Ok(())
+    }
+
+    /// Decodes Op/BRANCH pattern instructions.
     fn decode_branch(&mut self, insn: &mut Instruction) -> Result<(), Exception> {
         let target = self.fetch_u64le()?;
         insn.set_branch_target(target as i64);
@@ -269,6 +636,224 @@ impl Emulator {
             OpCode::Jg => self.decode_branch(&mut insn)?,
             OpCode::Jge => self.decode_branch(&mut insn)?,
             OpCode::Jb => self.decode_branch(&mut insn)?,
+            OpCode::PushR => self.decode_r(&mut insn)?,
+            OpCode::PushIMM => self.decode_imm(&mut insn)?,
+            OpCode::PopR => self.decode_r(&mut insn)?,
+            OpCode::Call => self.decode_branch(&mut insn)?,
+            OpCode::Ret => {} // No operands
+            OpCode::Jl => self.decode_branch(&mut insn)?,
+            OpCode::Ja => self.decode_branch(&mut insn)?,
+            OpCode::Jae => self.decode_branch(&mut insn)?,
+            OpCode::Jbe => self.decode_branch(&mut insn)?,
+            OpCode::Js => self.decode_branch(&mut insn)?,
+            OpCode::Jns => self.decode_branch(&mut insn)?,
+            OpCode::Jo => self.decode_branch(&mut insn)?,
+            OpCode::Jno => self.decode_branch(&mut insn)?,
+            OpCode::Jp => self.decode_branch(&mut insn)?,
+            OpCode::Jnp => self.decode_branch(&mut insn)?,
+            OpCode::DivRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::DivRR => self.decode_r_r(&mut insn)?,
+            OpCode::IdivRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::IdivRR => self.decode_r_r(&mut insn)?,
+            OpCode::RemRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::RemRR => self.decode_r_r(&mut insn)?,
+            OpCode::IremRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::IremRR => self.decode_r_r(&mut insn)?,
+            OpCode::ShlRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::ShlRR => self.decode_r_r(&mut insn)?,
+            OpCode::ShrRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::ShrRR => self.decode_r_r(&mut insn)?,
+            OpCode::SarRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::SarRR => self.decode_r_r(&mut insn)?,
+            OpCode::RolRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::RolRR => self.decode_r_r(&mut insn)?,
+            OpCode::RorRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::RorRR => self.decode_r_r(&mut insn)?,
+            OpCode::RclRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::RclRR => self.decode_r_r(&mut insn)?,
+            OpCode::RcrRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::RcrRR => self.decode_r_r(&mut insn)?,
+            OpCode::AddRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::AddRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::AddRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::SubRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::SubRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::SubRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::AndRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::AndRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::AndRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::OrRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::OrRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::OrRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::XorRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::XorRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::XorRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::XchgRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::XchgRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::ImulRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::IncRM => self.decode_rm(&mut insn)?,
+            OpCode::DecRM => self.decode_rm(&mut insn)?,
+            OpCode::CmpRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::CmpRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::CmpRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::TestRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::TestRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::TestRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::MovRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::MovzxRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::MovsxRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::MovzxRR8 => self.decode_r_r(&mut insn)?,
+            OpCode::MovzxRR16 => self.decode_r_r(&mut insn)?,
+            OpCode::MovzxRR32 => self.decode_r_r(&mut insn)?,
+            OpCode::MovsxRR8 => self.decode_r_r(&mut insn)?,
+            OpCode::MovsxRR16 => self.decode_r_r(&mut insn)?,
+            OpCode::MovsxRR32 => self.decode_r_r(&mut insn)?,
+            OpCode::LeaRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::CmovzRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovnzRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovleRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovgRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovgeRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovbRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovlRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovaRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovaeRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovbeRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovsRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovnsRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovoRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovnoRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovpRR => self.decode_r_r(&mut insn)?,
+            OpCode::CmovnpRR => self.decode_r_r(&mut insn)?,
+            OpCode::SetzR => self.decode_r(&mut insn)?,
+            OpCode::SetnzR => self.decode_r(&mut insn)?,
+            OpCode::SetleR => self.decode_r(&mut insn)?,
+            OpCode::SetgR => self.decode_r(&mut insn)?,
+            OpCode::SetgeR => self.decode_r(&mut insn)?,
+            OpCode::SetbR => self.decode_r(&mut insn)?,
+            OpCode::SetlR => self.decode_r(&mut insn)?,
+            OpCode::SetaR => self.decode_r(&mut insn)?,
+            OpCode::SetaeR => self.decode_r(&mut insn)?,
+            OpCode::SetbeR => self.decode_r(&mut insn)?,
+            OpCode::SetsR => self.decode_r(&mut insn)?,
+            OpCode::SetnsR => self.decode_r(&mut insn)?,
+            OpCode::SetoR => self.decode_r(&mut insn)?,
+            OpCode::SetnoR => self.decode_r(&mut insn)?,
+            OpCode::SetpR => self.decode_r(&mut insn)?,
+            OpCode::SetnpR => self.decode_r(&mut insn)?,
+            OpCode::JmpR => self.decode_r(&mut insn)?,
+            OpCode::JmpRM => self.decode_rm(&mut insn)?,
+            OpCode::CallR => self.decode_r(&mut insn)?,
+            OpCode::CallRM => self.decode_rm(&mut insn)?,
+            OpCode::PopcntRR => self.decode_r_r(&mut insn)?,
+            OpCode::PopcntRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::LzcntRR => self.decode_r_r(&mut insn)?,
+            OpCode::LzcntRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::TzcntRR => self.decode_r_r(&mut insn)?,
+            OpCode::TzcntRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::BswapR => self.decode_r(&mut insn)?,
+            OpCode::BtRR => self.decode_r_r(&mut insn)?,
+            OpCode::BtRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::BtRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::BtRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::BtsRR => self.decode_r_r(&mut insn)?,
+            OpCode::BtsRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::BtsRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::BtsRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::BtrRR => self.decode_r_r(&mut insn)?,
+            OpCode::BtrRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::BtrRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::BtrRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::BtcRR => self.decode_r_r(&mut insn)?,
+            OpCode::BtcRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::BtcRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::BtcRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::AdcRR => self.decode_r_r(&mut insn)?,
+            OpCode::AdcRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::AdcRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::AdcRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::AdcRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::SbbRR => self.decode_r_r(&mut insn)?,
+            OpCode::SbbRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::SbbRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::SbbRMR => self.decode_rm_r(&mut insn)?,
+            OpCode::SbbRMIMM => self.decode_rm_imm(&mut insn)?,
+            OpCode::MulRR => self.decode_r_r(&mut insn)?,
+            OpCode::MulhRR => self.decode_r_r(&mut insn)?,
+            OpCode::MulhRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::ImulhRR => self.decode_r_r(&mut insn)?,
+            OpCode::ImulhRRM => self.decode_r_rm(&mut insn)?,
+            OpCode::Nop => {} // No operands
+            OpCode::Hlt => {} // No operands
+            OpCode::NotR => self.decode_r(&mut insn)?,
+            OpCode::NotRM => self.decode_rm(&mut insn)?,
+            OpCode::NegR => self.decode_r(&mut insn)?,
+            OpCode::NegRM => self.decode_rm(&mut insn)?,
+            OpCode::RepMovsb => {} // No operands
+            OpCode::RepStosb => {} // No operands
+            OpCode::RepCmpsb => {} // No operands
+            OpCode::FmovFF => self.decode_f_f(&mut insn)?,
+            OpCode::FmovFRM => self.decode_f_rm(&mut insn)?,
+            OpCode::FmovRMF => self.decode_rm_f(&mut insn)?,
+            OpCode::FmovFIMM => self.decode_f_imm(&mut insn)?,
+            OpCode::FmovFR => self.decode_f_r(&mut insn)?,
+            OpCode::FmovRF => self.decode_r_f(&mut insn)?,
+            OpCode::FaddFF => self.decode_f_f(&mut insn)?,
+            OpCode::FsubFF => self.decode_f_f(&mut insn)?,
+            OpCode::FmulFF => self.decode_f_f(&mut insn)?,
+            OpCode::FdivFF => self.decode_f_f(&mut insn)?,
+            OpCode::FsqrtFF => self.decode_f_f(&mut insn)?,
+            OpCode::FcmpFF => self.decode_f_f(&mut insn)?,
+            OpCode::Cvtsi2fFR => self.decode_f_r(&mut insn)?,
+            OpCode::Cvtf2siRF => self.decode_r_f(&mut insn)?,
+            OpCode::MovdquVV => self.decode_v_v(&mut insn)?,
+            OpCode::MovdquVRM => self.decode_v_rm(&mut insn)?,
+            OpCode::MovdquRMV => self.decode_rm_v(&mut insn)?,
+            OpCode::MovqRV => self.decode_r_v(&mut insn)?,
+            OpCode::MovqVR => self.decode_v_r(&mut insn)?,
+            OpCode::PaddbVV => self.decode_v_v(&mut insn)?,
+            OpCode::PaddwVV => self.decode_v_v(&mut insn)?,
+            OpCode::PadddVV => self.decode_v_v(&mut insn)?,
+            OpCode::PaddqVV => self.decode_v_v(&mut insn)?,
+            OpCode::PsubbVV => self.decode_v_v(&mut insn)?,
+            OpCode::PsubwVV => self.decode_v_v(&mut insn)?,
+            OpCode::PsubdVV => self.decode_v_v(&mut insn)?,
+            OpCode::PsubqVV => self.decode_v_v(&mut insn)?,
+            OpCode::PandVV => self.decode_v_v(&mut insn)?,
+            OpCode::PorVV => self.decode_v_v(&mut insn)?,
+            OpCode::PxorVV => self.decode_v_v(&mut insn)?,
+            OpCode::PcmpeqbVV => self.decode_v_v(&mut insn)?,
+            OpCode::PcmpeqwVV => self.decode_v_v(&mut insn)?,
+            OpCode::PcmpeqdVV => self.decode_v_v(&mut insn)?,
+            OpCode::PcmpeqqVV => self.decode_v_v(&mut insn)?,
+            OpCode::PshufbVV => self.decode_v_v(&mut insn)?,
+            OpCode::AddRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::SubRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::AndRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::OrRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::XorRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::ImulRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::ShlRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::ShrRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::SarRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::AdcRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::SbbRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::MulhRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::ImulhRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::DivRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::IdivRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::RemRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::IremRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::RolRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::RorRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::RclRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::RcrRRRI => self.decode_r_r_ri(&mut insn)?,
+            OpCode::InRIMM => self.decode_r_imm(&mut insn)?,
+            OpCode::InRR => self.decode_r_r(&mut insn)?,
+            OpCode::OutIMMR => self.decode_imm_r(&mut insn)?,
+            OpCode::OutRR => self.decode_r_r(&mut insn)?,
+            OpCode::Cpuid => {} // No operands
+            OpCode::Custom => self.decode_custom(&mut insn)?,
         };
 
         Ok(insn)
@@ -284,11 +869,31 @@ impl Emulator {
     ///
     /// # Returns
     /// - `Ok(())`: If the instruction executes successfully without errors.
-    /// - `Err(Exception)`: If an exception occurs during instruction execution
-    ///   (e.g., [`Exception::IllegalInstruction`],
-    ///   [`Exception::AccessViolation`]).
-    pub fn single_step(&mut self) -> Result<(), Exception> {
-        let opcode = OpCode::from_repr(self.fetch_u8()?).ok_or(Exception::IllegalInstruction)?;
+    /// - `Err(ExceptionRecord)`: If an exception occurs during instruction
+    ///   execution (e.g., [`Exception::IllegalInstruction`],
+    ///   [`Exception::AccessViolation`]), along with the IP and the opcode of
+    ///   the instruction.
+    pub fn single_step(&mut self) -> Result<(), ExceptionRecord> {
+        let ip = self.ip();
+        self.step().map_err(|kind| ExceptionRecord {
+            kind,
+            ip,
+            opcode: self.peek_opcode(ip),
+        })
+    }
+
+    /// Reads the raw opcode at `ip` without fetching it.
+    fn peek_opcode(&self, ip: u64) -> Option<u16> {
+        let ip = usize::try_from(ip).ok()?;
+        match self.dram.read_u8(ip).ok()? {
+            ESCAPE => Some(((ESCAPE as u16) << 8) | self.dram.read_u8(ip + 1).ok()? as u16),
+            opcode => Some(opcode as u16),
+        }
+    }
+
+    /// Fetches, decodes and executes a single instruction.
+    fn step(&mut self) -> Result<(), Exception> {
+        let opcode = self.fetch_opcode()?;
         let insn = self.decode(opcode)?;
 
         match opcode {
@@ -324,6 +929,230 @@ impl Emulator {
             OpCode::Jg => self.handle_jg(&insn)?,
             OpCode::Jge => self.handle_jge(&insn)?,
             OpCode::Jb => self.handle_jb(&insn)?,
+            OpCode::PushR => self.handle_push_r(&insn)?,
+            OpCode::PushIMM => self.handle_push_imm(&insn)?,
+            OpCode::PopR => self.handle_pop_r(&insn)?,
+            OpCode::Call => self.handle_call(&insn)?,
+            OpCode::Ret => self.handle_ret(&insn)?,
+            OpCode::Jl => self.handle_jl(&insn)?,
+            OpCode::Ja => self.handle_ja(&insn)?,
+            OpCode::Jae => self.handle_jae(&insn)?,
+            OpCode::Jbe => self.handle_jbe(&insn)?,
+            OpCode::Js => self.handle_js(&insn)?,
+            OpCode::Jns => self.handle_jns(&insn)?,
+            OpCode::Jo => self.handle_jo(&insn)?,
+            OpCode::Jno => self.handle_jno(&insn)?,
+            OpCode::Jp => self.handle_jp(&insn)?,
+            OpCode::Jnp => self.handle_jnp(&insn)?,
+            OpCode::DivRIMM => self.handle_div_r_imm(&insn)?,
+            OpCode::DivRR => self.handle_div_r_r(&insn)?,
+            OpCode::IdivRIMM => self.handle_idiv_r_imm(&insn)?,
+            OpCode::IdivRR => self.handle_idiv_r_r(&insn)?,
+            OpCode::RemRIMM => self.handle_rem_r_imm(&insn)?,
+            OpCode::RemRR => self.handle_rem_r_r(&insn)?,
+            OpCode::IremRIMM => self.handle_irem_r_imm(&insn)?,
+            OpCode::IremRR => self.handle_irem_r_r(&insn)?,
+            OpCode::ShlRIMM => self.handle_shl_r_imm(&insn)?,
+            OpCode::ShlRR => self.handle_shl_r_r(&insn)?,
+            OpCode::ShrRIMM => self.handle_shr_r_imm(&insn)?,
+            OpCode::ShrRR => self.handle_shr_r_r(&insn)?,
+            OpCode::SarRIMM => self.handle_sar_r_imm(&insn)?,
+            OpCode::SarRR => self.handle_sar_r_r(&insn)?,
+            OpCode::RolRIMM => self.handle_rol_r_imm(&insn)?,
+            OpCode::RolRR => self.handle_rol_r_r(&insn)?,
+            OpCode::RorRIMM => self.handle_ror_r_imm(&insn)?,
+            OpCode::RorRR => self.handle_ror_r_r(&insn)?,
+            OpCode::RclRIMM => self.handle_rcl_r_imm(&insn)?,
+            OpCode::RclRR => self.handle_rcl_r_r(&insn)?,
+            OpCode::RcrRIMM => self.handle_rcr_r_imm(&insn)?,
+            OpCode::RcrRR => self.handle_rcr_r_r(&insn)?,
+            OpCode::AddRRM => self.handle_add_r_rm(&insn)?,
+            OpCode::AddRMR => self.handle_add_rm_r(&insn)?,
+            OpCode::AddRMIMM => self.handle_add_rm_imm(&insn)?,
+            OpCode::SubRRM => self.handle_sub_r_rm(&insn)?,
+            OpCode::SubRMR => self.handle_sub_rm_r(&insn)?,
+            OpCode::SubRMIMM => self.handle_sub_rm_imm(&insn)?,
+            OpCode::AndRRM => self.handle_and_r_rm(&insn)?,
+            OpCode::AndRMR => self.handle_and_rm_r(&insn)?,
+            OpCode::AndRMIMM => self.handle_and_rm_imm(&insn)?,
+            OpCode::OrRRM => self.handle_or_r_rm(&insn)?,
+            OpCode::OrRMR => self.handle_or_rm_r(&insn)?,
+            OpCode::OrRMIMM => self.handle_or_rm_imm(&insn)?,
+            OpCode::XorRRM => self.handle_xor_r_rm(&insn)?,
+            OpCode::XorRMR => self.handle_xor_rm_r(&insn)?,
+            OpCode::XorRMIMM => self.handle_xor_rm_imm(&insn)?,
+            OpCode::XchgRRM => self.handle_xchg_r_rm(&insn)?,
+            OpCode::XchgRMR => self.handle_xchg_rm_r(&insn)?,
+            OpCode::ImulRRM => self.handle_imul_r_rm(&insn)?,
+            OpCode::IncRM => self.handle_inc_rm(&insn)?,
+            OpCode::DecRM => self.handle_dec_rm(&insn)?,
+            OpCode::CmpRRM => self.handle_cmp_r_rm(&insn)?,
+            OpCode::CmpRMR => self.handle_cmp_rm_r(&insn)?,
+            OpCode::CmpRMIMM => self.handle_cmp_rm_imm(&insn)?,
+            OpCode::TestRRM => self.handle_test_r_rm(&insn)?,
+            OpCode::TestRMR => self.handle_test_rm_r(&insn)?,
+            OpCode::TestRMIMM => self.handle_test_rm_imm(&insn)?,
+            OpCode::MovRMIMM => self.handle_mov_rm_imm(&insn)?,
+            OpCode::MovzxRRM => self.handle_movzx_r_rm(&insn)?,
+            OpCode::MovsxRRM => self.handle_movsx_r_rm(&insn)?,
+            OpCode::MovzxRR8 => self.handle_movzx_r_r8(&insn)?,
+            OpCode::MovzxRR16 => self.handle_movzx_r_r16(&insn)?,
+            OpCode::MovzxRR32 => self.handle_movzx_r_r32(&insn)?,
+            OpCode::MovsxRR8 => self.handle_movsx_r_r8(&insn)?,
+            OpCode::MovsxRR16 => self.handle_movsx_r_r16(&insn)?,
+            OpCode::MovsxRR32 => self.handle_movsx_r_r32(&insn)?,
+            OpCode::LeaRRM => self.handle_lea_r_rm(&insn)?,
+            OpCode::CmovzRR => self.handle_cmovz_r_r(&insn)?,
+            OpCode::CmovnzRR => self.handle_cmovnz_r_r(&insn)?,
+            OpCode::CmovleRR => self.handle_cmovle_r_r(&insn)?,
+            OpCode::CmovgRR => self.handle_cmovg_r_r(&insn)?,
+            OpCode::CmovgeRR => self.handle_cmovge_r_r(&insn)?,
+            OpCode::CmovbRR => self.handle_cmovb_r_r(&insn)?,
+            OpCode::CmovlRR => self.handle_cmovl_r_r(&insn)?,
+            OpCode::CmovaRR => self.handle_cmova_r_r(&insn)?,
+            OpCode::CmovaeRR => self.handle_cmovae_r_r(&insn)?,
+            OpCode::CmovbeRR => self.handle_cmovbe_r_r(&insn)?,
+            OpCode::CmovsRR => self.handle_cmovs_r_r(&insn)?,
+            OpCode::CmovnsRR => self.handle_cmovns_r_r(&insn)?,
+            OpCode::CmovoRR => self.handle_cmovo_r_r(&insn)?,
+            OpCode::CmovnoRR => self.handle_cmovno_r_r(&insn)?,
+            OpCode::CmovpRR => self.handle_cmovp_r_r(&insn)?,
+            OpCode::CmovnpRR => self.handle_cmovnp_r_r(&insn)?,
+            OpCode::SetzR => self.handle_setz_r(&insn)?,
+            OpCode::SetnzR => self.handle_setnz_r(&insn)?,
+            OpCode::SetleR => self.handle_setle_r(&insn)?,
+            OpCode::SetgR => self.handle_setg_r(&insn)?,
+            OpCode::SetgeR => self.handle_setge_r(&insn)?,
+            OpCode::SetbR => self.handle_setb_r(&insn)?,
+            OpCode::SetlR => self.handle_setl_r(&insn)?,
+            OpCode::SetaR => self.handle_seta_r(&insn)?,
+            OpCode::SetaeR => self.handle_setae_r(&insn)?,
+            OpCode::SetbeR => self.handle_setbe_r(&insn)?,
+            OpCode::SetsR => self.handle_sets_r(&insn)?,
+            OpCode::SetnsR => self.handle_setns_r(&insn)?,
+            OpCode::SetoR => self.handle_seto_r(&insn)?,
+            OpCode::SetnoR => self.handle_setno_r(&insn)?,
+            OpCode::SetpR => self.handle_setp_r(&insn)?,
+            OpCode::SetnpR => self.handle_setnp_r(&insn)?,
+            OpCode::JmpR => self.handle_jmp_r(&insn)?,
+            OpCode::JmpRM => self.handle_jmp_rm(&insn)?,
+            OpCode::CallR => self.handle_call_r(&insn)?,
+            OpCode::CallRM => self.handle_call_rm(&insn)?,
+            OpCode::PopcntRR => self.handle_popcnt_r_r(&insn)?,
+            OpCode::PopcntRRM => self.handle_popcnt_r_rm(&insn)?,
+            OpCode::LzcntRR => self.handle_lzcnt_r_r(&insn)?,
+            OpCode::LzcntRRM => self.handle_lzcnt_r_rm(&insn)?,
+            OpCode::TzcntRR => self.handle_tzcnt_r_r(&insn)?,
+            OpCode::TzcntRRM => self.handle_tzcnt_r_rm(&insn)?,
+            OpCode::BswapR => self.handle_bswap_r(&insn)?,
+            OpCode::BtRR => self.handle_bt_r_r(&insn)?,
+            OpCode::BtRIMM => self.handle_bt_r_imm(&insn)?,
+            OpCode::BtRMR => self.handle_bt_rm_r(&insn)?,
+            OpCode::BtRMIMM => self.handle_bt_rm_imm(&insn)?,
+            OpCode::BtsRR => self.handle_bts_r_r(&insn)?,
+            OpCode::BtsRIMM => self.handle_bts_r_imm(&insn)?,
+            OpCode::BtsRMR => self.handle_bts_rm_r(&insn)?,
+            OpCode::BtsRMIMM => self.handle_bts_rm_imm(&insn)?,
+            OpCode::BtrRR => self.handle_btr_r_r(&insn)?,
+            OpCode::BtrRIMM => self.handle_btr_r_imm(&insn)?,
+            OpCode::BtrRMR => self.handle_btr_rm_r(&insn)?,
+            OpCode::BtrRMIMM => self.handle_btr_rm_imm(&insn)?,
+            OpCode::BtcRR => self.handle_btc_r_r(&insn)?,
+            OpCode::BtcRIMM => self.handle_btc_r_imm(&insn)?,
+            OpCode::BtcRMR => self.handle_btc_rm_r(&insn)?,
+            OpCode::BtcRMIMM => self.handle_btc_rm_imm(&insn)?,
+            OpCode::AdcRR => self.handle_adc_r_r(&insn)?,
+            OpCode::AdcRIMM => self.handle_adc_r_imm(&insn)?,
+            OpCode::AdcRRM => self.handle_adc_r_rm(&insn)?,
+            OpCode::AdcRMR => self.handle_adc_rm_r(&insn)?,
+            OpCode::AdcRMIMM => self.handle_adc_rm_imm(&insn)?,
+            OpCode::SbbRR => self.handle_sbb_r_r(&insn)?,
+            OpCode::SbbRIMM => self.handle_sbb_r_imm(&insn)?,
+            OpCode::SbbRRM => self.handle_sbb_r_rm(&insn)?,
+            OpCode::SbbRMR => self.handle_sbb_rm_r(&insn)?,
+            OpCode::SbbRMIMM => self.handle_sbb_rm_imm(&insn)?,
+            OpCode::MulRR => self.handle_mul_r_r(&insn)?,
+            OpCode::MulhRR => self.handle_mulh_r_r(&insn)?,
+            OpCode::MulhRRM => self.handle_mulh_r_rm(&insn)?,
+            OpCode::ImulhRR => self.handle_imulh_r_r(&insn)?,
+            OpCode::ImulhRRM => self.handle_imulh_r_rm(&insn)?,
+            OpCode::Nop => {}
+            OpCode::Hlt => return Err(Exception::Halt),
+            OpCode::NotR => self.handle_not_r(&insn)?,
+            OpCode::NotRM => self.handle_not_rm(&insn)?,
+            OpCode::NegR => self.handle_neg_r(&insn)?,
+            OpCode::NegRM => self.handle_neg_rm(&insn)?,
+            OpCode::RepMovsb => self.handle_rep_movsb(&insn)?,
+            OpCode::RepStosb => self.handle_rep_stosb(&insn)?,
+            OpCode::RepCmpsb => self.handle_rep_cmpsb(&insn)?,
+            OpCode::FmovFF => self.handle_fmov_f_f(&insn)?,
+            OpCode::FmovFRM => self.handle_fmov_f_rm(&insn)?,
+            OpCode::FmovRMF => self.handle_fmov_rm_f(&insn)?,
+            OpCode::FmovFIMM => self.handle_fmov_f_imm(&insn)?,
+            OpCode::FmovFR => self.handle_fmov_f_r(&insn)?,
+            OpCode::FmovRF => self.handle_fmov_r_f(&insn)?,
+            OpCode::FaddFF => self.handle_fadd_f_f(&insn)?,
+            OpCode::FsubFF => self.handle_fsub_f_f(&insn)?,
+            OpCode::FmulFF => self.handle_fmul_f_f(&insn)?,
+            OpCode::FdivFF => self.handle_fdiv_f_f(&insn)?,
+            OpCode::FsqrtFF => self.handle_fsqrt_f_f(&insn)?,
+            OpCode::FcmpFF => self.handle_fcmp_f_f(&insn)?,
+            OpCode::Cvtsi2fFR => self.handle_cvtsi2f_f_r(&insn)?,
+            OpCode::Cvtf2siRF => self.handle_cvtf2si_r_f(&insn)?,
+            OpCode::MovdquVV => self.handle_movdqu_v_v(&insn)?,
+            OpCode::MovdquVRM => self.handle_movdqu_v_rm(&insn)?,
+            OpCode::MovdquRMV => self.handle_movdqu_rm_v(&insn)?,
+            OpCode::MovqRV => self.handle_movq_r_v(&insn)?,
+            OpCode::MovqVR => self.handle_movq_v_r(&insn)?,
+            OpCode::PaddbVV => self.handle_paddb_v_v(&insn)?,
+            OpCode::PaddwVV => self.handle_paddw_v_v(&insn)?,
+            OpCode::PadddVV => self.handle_paddd_v_v(&insn)?,
+            OpCode::PaddqVV => self.handle_paddq_v_v(&insn)?,
+            OpCode::PsubbVV => self.handle_psubb_v_v(&insn)?,
+            OpCode::PsubwVV => self.handle_psubw_v_v(&insn)?,
+            OpCode::PsubdVV => self.handle_psubd_v_v(&insn)?,
+            OpCode::PsubqVV => self.handle_psubq_v_v(&insn)?,
+            OpCode::PandVV => self.handle_pand_v_v(&insn)?,
+            OpCode::PorVV => self.handle_por_v_v(&insn)?,
+            OpCode::PxorVV => self.handle_pxor_v_v(&insn)?,
+            OpCode::PcmpeqbVV => self.handle_pcmpeqb_v_v(&insn)?,
+            OpCode::PcmpeqwVV => self.handle_pcmpeqw_v_v(&insn)?,
+            OpCode::PcmpeqdVV => self.handle_pcmpeqd_v_v(&insn)?,
+            OpCode::PcmpeqqVV => self.handle_pcmpeqq_v_v(&insn)?,
+            OpCode::PshufbVV => self.handle_pshufb_v_v(&insn)?,
+            OpCode::AddRRRI => self.handle_add_r_r_ri(&insn)?,
+            OpCode::SubRRRI => self.handle_sub_r_r_ri(&insn)?,
+            OpCode::AndRRRI => self.handle_and_r_r_ri(&insn)?,
+            OpCode::OrRRRI => self.handle_or_r_r_ri(&insn)?,
+            OpCode::XorRRRI => self.handle_xor_r_r_ri(&insn)?,
+            OpCode::ImulRRRI => self.handle_imul_r_r_ri(&insn)?,
+            OpCode::ShlRRRI => self.handle_shl_r_r_ri(&insn)?,
+            OpCode::ShrRRRI => self.handle_shr_r_r_ri(&insn)?,
+            OpCode::SarRRRI => self.handle_sar_r_r_ri(&insn)?,
+            OpCode::AdcRRRI => self.handle_adc_r_r_ri(&insn)?,
+            OpCode::SbbRRRI => self.handle_sbb_r_r_ri(&insn)?,
+            OpCode::MulhRRRI => self.handle_mulh_r_r_ri(&insn)?,
+            OpCode::ImulhRRRI => self.handle_imulh_r_r_ri(&insn)?,
+            OpCode::DivRRRI => self.handle_div_r_r_ri(&insn)?,
+            OpCode::IdivRRRI => self.handle_idiv_r_r_ri(&insn)?,
+            OpCode::RemRRRI => self.handle_rem_r_r_ri(&insn)?,
+            OpCode::IremRRRI => self.handle_irem_r_r_ri(&insn)?,
+            OpCode::RolRRRI => self.handle_rol_r_r_ri(&insn)?,
+            OpCode::RorRRRI => self.handle_ror_r_r_ri(&insn)?,
+            OpCode::RclRRRI => self.handle_rcl_r_r_ri(&insn)?,
+            OpCode::RcrRRRI => self.handle_rcr_r_r_ri(&insn)?,
+            OpCode::InRIMM => self.handle_in_r_imm(&insn)?,
+            OpCode::InRR => self.handle_in_r_r(&insn)?,
+            OpCode::OutIMMR => self.handle_out_imm_r(&insn)?,
+            OpCode::OutRR => self.handle_out_r_r(&insn)?,
+            OpCode::Cpuid => self.handle_cpuid(&insn)?,
+            OpCode::Custom => {
+                let handler = match self.custom.get(insn.custom) {
+                    Some(custom) => custom.handler.clone(),
+                    None => return Err(Exception::IllegalInstruction),
+                };
+                handler(self, &insn)?
+            }
         }
 
         self.cycle += 1;
@@ -343,17 +1172,20 @@ impl Emulator {
     ///   successfully and returns `Ok(())`.
     /// - If any other exception is encountered (such as `IllegalInstruction` or
     ///   `AccessViolation`), the function returns the corresponding error.
+    /// - An [`Exception::Halt`] is returned as an error as well, but IP already
+    ///   points past the `hlt` so calling this function again resumes
+    ///   execution.
     ///
     /// # Returns
     /// - `Ok(())`: If the execution completes successfully or is explicitly
     ///   exited by an [`Exception::Exit`] exception.
-    /// - `Err(Exception)`: If an error occurs during execution (e.g., invalid
-    ///   instruction or memory access violation).
-    pub fn execute(&mut self) -> Result<(), Exception> {
+    /// - `Err(ExceptionRecord)`: If an error occurs during execution (e.g.,
+    ///   invalid instruction or memory access violation).
+    pub fn execute(&mut self) -> Result<(), ExceptionRecord> {
         loop {
             match self.single_step() {
                 Ok(_) => {}
-                Err(ex) => match ex {
+                Err(ex) => match ex.kind {
                     Exception::Exit => return Ok(()),
                     _ => return Err(ex),
                 },
@@ -362,6 +1194,28 @@ impl Emulator {
     }
 }
 
+/// Marks the failed read of an instruction as a fetch.
+fn as_fetch(ex: Exception) -> Exception {
+    match ex {
+        Exception::AccessViolation(access) => Exception::AccessViolation(MemoryAccess {
+            kind: AccessKind::Fetch,
+            ..access
+        }),
+        ex => ex,
+    }
+}
+
+define_handler_trait!(Cpuid, handle_cpuid);
+
+impl Cpuid for Emulator {
+    fn handle_cpuid(&mut self, _insn: &Instruction) -> Result<(), Exception> {
+        self.regs.write(Register::R0, ISA_VERSION);
+        self.regs.write(Register::R1, self.extensions.0);
+
+        Ok(())
+    }
+}
+
 /// Represents the set of registers used by the virtual CPU.
 #[repr(u8)]
 #[derive(FromRepr, Debug, Clone, Copy, PartialEq, Eq)]
@@ -407,6 +1261,107 @@ pub enum Register {
     R14,
     /// A 64-bit general purpose register.
     R15,
+    /// A 64-bit Stack Pointer register.
+    ///
+    /// Holds the memory address of the top of the stack. The stack grows
+    /// downwards, see [`Emulator::stack_base`] and [`Emulator::stack_limit`].
+    SP,
+    /// The low 8 bits of [`Register::R0`].
+    R0B,
+    /// The low 8 bits of [`Register::R1`].
+    R1B,
+    /// The low 8 bits of [`Register::R2`].
+    R2B,
+    /// The low 8 bits of [`Register::R3`].
+    R3B,
+    /// The low 8 bits of [`Register::R4`].
+    R4B,
+    /// The low 8 bits of [`Register::R5`].
+    R5B,
+    /// The low 8 bits of [`Register::R6`].
+    R6B,
+    /// The low 8 bits of [`Register::R7`].
+    R7B,
+    /// The low 8 bits of [`Register::R8`].
+    R8B,
+    /// The low 8 bits of [`Register::R9`].
+    R9B,
+    /// The low 8 bits of [`Register::R10`].
+    R10B,
+    /// The low 8 bits of [`Register::R11`].
+    R11B,
+    /// The low 8 bits of [`Register::R12`].
+    R12B,
+    /// The low 8 bits of [`Register::R13`].
+    R13B,
+    /// The low 8 bits of [`Register::R14`].
+    R14B,
+    /// The low 8 bits of [`Register::R15`].
+    R15B,
+    /// The low 16 bits of [`Register::R0`].
+    R0W,
+    /// The low 16 bits of [`Register::R1`].
+    R1W,
+    /// The low 16 bits of [`Register::R2`].
+    R2W,
+    /// The low 16 bits of [`Register::R3`].
+    R3W,
+    /// The low 16 bits of [`Register::R4`].
+    R4W,
+    /// The low 16 bits of [`Register::R5`].
+    R5W,
+    /// The low 16 bits of [`Register::R6`].
+    R6W,
+    /// The low 16 bits of [`Register::R7`].
+    R7W,
+    /// The low 16 bits of [`Register::R8`].
+    R8W,
+    /// The low 16 bits of [`Register::R9`].
+    R9W,
+    /// The low 16 bits of [`Register::R10`].
+    R10W,
+    /// The low 16 bits of [`Register::R11`].
+    R11W,
+    /// The low 16 bits of [`Register::R12`].
+    R12W,
+    /// The low 16 bits of [`Register::R13`].
+    R13W,
+    /// The low 16 bits of [`Register::R14`].
+    R14W,
+    /// The low 16 bits of [`Register::R15`].
+    R15W,
+    /// The low 32 bits of [`Register::R0`].
+    R0D,
+    /// The low 32 bits of [`Register::R1`].
+    R1D,
+    /// The low 32 bits of [`Register::R2`].
+    R2D,
+    /// The low 32 bits of [`Register::R3`].
+    R3D,
+    /// The low 32 bits of [`Register::R4`].
+    R4D,
+    /// The low 32 bits of [`Register::R5`].
+    R5D,
+    /// The low 32 bits of [`Register::R6`].
+    R6D,
+    /// The low 32 bits of [`Register::R7`].
+    R7D,
+    /// The low 32 bits of [`Register::R8`].
+    R8D,
+    /// The low 32 bits of [`Register::R9`].
+    R9D,
+    /// The low 32 bits of [`Register::R10`].
+    R10D,
+    /// The low 32 bits of [`Register::R11`].
+    R11D,
+    /// The low 32 bits of [`Register::R12`].
+    R12D,
+    /// The low 32 bits of [`Register::R13`].
+    R13D,
+    /// The low 32 bits of [`Register::R14`].
+    R14D,
+    /// The low 32 bits of [`Register::R15`].
+    R15D,
 }
 
 impl Register {
@@ -431,13 +1386,82 @@ impl Register {
             "r13" => Some(Self::R13),
             "r14" => Some(Self::R14),
             "r15" => Some(Self::R15),
+            "sp" => Some(Self::SP),
+            "r0b" => Some(Self::R0B),
+            "r1b" => Some(Self::R1B),
+            "r2b" => Some(Self::R2B),
+            "r3b" => Some(Self::R3B),
+            "r4b" => Some(Self::R4B),
+            "r5b" => Some(Self::R5B),
+            "r6b" => Some(Self::R6B),
+            "r7b" => Some(Self::R7B),
+            "r8b" => Some(Self::R8B),
+            "r9b" => Some(Self::R9B),
+            "r10b" => Some(Self::R10B),
+            "r11b" => Some(Self::R11B),
+            "r12b" => Some(Self::R12B),
+            "r13b" => Some(Self::R13B),
+            "r14b" => Some(Self::R14B),
+            "r15b" => Some(Self::R15B),
+            "r0w" => Some(Self::R0W),
+            "r1w" => Some(Self::R1W),
+            "r2w" => Some(Self::R2W),
+            "r3w" => Some(Self::R3W),
+            "r4w" => Some(Self::R4W),
+            "r5w" => Some(Self::R5W),
+            "r6w" => Some(Self::R6W),
+            "r7w" => Some(Self::R7W),
+            "r8w" => Some(Self::R8W),
+            "r9w" => Some(Self::R9W),
+            "r10w" => Some(Self::R10W),
+            "r11w" => Some(Self::R11W),
+            "r12w" => Some(Self::R12W),
+            "r13w" => Some(Self::R13W),
+            "r14w" => Some(Self::R14W),
+            "r15w" => Some(Self::R15W),
+            "r0d" => Some(Self::R0D),
+            "r1d" => Some(Self::R1D),
+            "r2d" => Some(Self::R2D),
+            "r3d" => Some(Self::R3D),
+            "r4d" => Some(Self::R4D),
+            "r5d" => Some(Self::R5D),
+            "r6d" => Some(Self::R6D),
+            "r7d" => Some(Self::R7D),
+            "r8d" => Some(Self::R8D),
+            "r9d" => Some(Self::R9D),
+            "r10d" => Some(Self::R10D),
+            "r11d" => Some(Self::R11D),
+            "r12d" => Some(Self::R12D),
+            "r13d" => Some(Self::R13D),
+            "r14d" => Some(Self::R14D),
+            "r15d" => Some(Self::R15D),
             _ => None,
         }
     }
+
+    /// Returns the width of the [`Register`], which is narrower than
+    /// [`OperandSize::QWord`] for the sub-registers (e.g., `R0B`)
+    pub fn size(&self) -> OperandSize {
+        match *self as u8 {
+            r if r < NUM_REGS as u8 => OperandSize::QWord,
+            r if r < (NUM_REGS + 16) as u8 => OperandSize::Byte,
+            r if r < (NUM_REGS + 32) as u8 => OperandSize::Word,
+            _ => OperandSize::DWord,
+        }
+    }
+
+    /// Returns the full-width [`Register`] containing the sub-register, or
+    /// itself if it is already full-width
+    pub fn full(&self) -> Register {
+        match *self as u8 {
+            r if r < NUM_REGS as u8 => *self,
+            r => Self::from_repr((r - NUM_REGS as u8) % 16 + Self::R0 as u8).unwrap(),
+        }
+    }
 }
 
-/// The number of [`Register`]s
-pub const NUM_REGS: usize = 18;
+/// The number of full-width [`Register`]s
+pub const NUM_REGS: usize = 19;
 
 impl fmt::Display for Register {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -460,6 +1484,55 @@ impl fmt::Display for Register {
             Self::R13 => write!(f, "R13"),
             Self::R14 => write!(f, "R14"),
             Self::R15 => write!(f, "R15"),
+            Self::SP => write!(f, "SP"),
+            Self::R0B => write!(f, "R0B"),
+            Self::R1B => write!(f, "R1B"),
+            Self::R2B => write!(f, "R2B"),
+            Self::R3B => write!(f, "R3B"),
+            Self::R4B => write!(f, "R4B"),
+            Self::R5B => write!(f, "R5B"),
+            Self::R6B => write!(f, "R6B"),
+            Self::R7B => write!(f, "R7B"),
+            Self::R8B => write!(f, "R8B"),
+            Self::R9B => write!(f, "R9B"),
+            Self::R10B => write!(f, "R10B"),
+            Self::R11B => write!(f, "R11B"),
+            Self::R12B => write!(f, "R12B"),
+            Self::R13B => write!(f, "R13B"),
+            Self::R14B => write!(f, "R14B"),
+            Self::R15B => write!(f, "R15B"),
+            Self::R0W => write!(f, "R0W"),
+            Self::R1W => write!(f, "R1W"),
+            Self::R2W => write!(f, "R2W"),
+            Self::R3W => write!(f, "R3W"),
+            Self::R4W => write!(f, "R4W"),
+            Self::R5W => write!(f, "R5W"),
+            Self::R6W => write!(f, "R6W"),
+            Self::R7W => write!(f, "R7W"),
+            Self::R8W => write!(f, "R8W"),
+            Self::R9W => write!(f, "R9W"),
+            Self::R10W => write!(f, "R10W"),
+            Self::R11W => write!(f, "R11W"),
+            Self::R12W => write!(f, "R12W"),
+            Self::R13W => write!(f, "R13W"),
+            Self::R14W => write!(f, "R14W"),
+            Self::R15W => write!(f, "R15W"),
+            Self::R0D => write!(f, "R0D"),
+            Self::R1D => write!(f, "R1D"),
+            Self::R2D => write!(f, "R2D"),
+            Self::R3D => write!(f, "R3D"),
+            Self::R4D => write!(f, "R4D"),
+            Self::R5D => write!(f, "R5D"),
+            Self::R6D => write!(f, "R6D"),
+            Self::R7D => write!(f, "R7D"),
+            Self::R8D => write!(f, "R8D"),
+            Self::R9D => write!(f, "R9D"),
+            Self::R10D => write!(f, "R10D"),
+            Self::R11D => write!(f, "R11D"),
+            Self::R12D => write!(f, "R12D"),
+            Self::R13D => write!(f, "R13D"),
+            Self::R14D => write!(f, "R14D"),
+            Self::R15D => write!(f, "R15D"),
         }
     }
 }
@@ -489,13 +1562,25 @@ impl Registers {
     }
 
     /// Reads the value from the specified register.
+    ///
+    /// A sub-register reads the low bits of its full-width register,
+    /// zero-extended.
     pub fn read(&self, reg: Register) -> u64 {
-        self.0[reg as usize]
+        self.0[reg.full() as usize] & reg.size().mask()
     }
 
     /// Writes a value to the specified register.
+    ///
+    /// Like x86, writing an 8 or 16-bit sub-register preserves the upper bits
+    /// of its full-width register, while writing a 32-bit sub-register
+    /// zero-extends the value into it.
     pub fn write(&mut self, reg: Register, value: u64) {
-        self.0[reg as usize] = value;
+        let size = reg.size();
+        let full = &mut self.0[reg.full() as usize];
+        *full = match size {
+            OperandSize::Byte | OperandSize::Word => (*full & !size.mask()) | (value & size.mask()),
+            _ => value & size.mask(),
+        };
     }
 
     /// Reads the value of the [`Register::RF`] and reinterprets it to a
@@ -510,6 +1595,265 @@ impl Registers {
     }
 }
 
+/// Represents the set of floating-point registers used by the virtual CPU.
+///
+/// Floating-point registers live in a separate bank from [`Register`]s and
+/// hold IEEE-754 double precision values.
+#[repr(u8)]
+#[derive(FromRepr, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatRegister {
+    /// A 64-bit floating-point register.
+    F0,
+    /// A 64-bit floating-point register.
+    F1,
+    /// A 64-bit floating-point register.
+    F2,
+    /// A 64-bit floating-point register.
+    F3,
+    /// A 64-bit floating-point register.
+    F4,
+    /// A 64-bit floating-point register.
+    F5,
+    /// A 64-bit floating-point register.
+    F6,
+    /// A 64-bit floating-point register.
+    F7,
+    /// A 64-bit floating-point register.
+    F8,
+    /// A 64-bit floating-point register.
+    F9,
+    /// A 64-bit floating-point register.
+    F10,
+    /// A 64-bit floating-point register.
+    F11,
+    /// A 64-bit floating-point register.
+    F12,
+    /// A 64-bit floating-point register.
+    F13,
+    /// A 64-bit floating-point register.
+    F14,
+    /// A 64-bit floating-point register.
+    F15,
+}
+
+impl FloatRegister {
+    /// Reinterpret the [`FloatRegister`] from [`str`]
+    pub fn from_str<S: AsRef<str>>(s: S) -> Option<Self> {
+        match &*s.as_ref().to_lowercase() {
+            "f0" => Some(Self::F0),
+            "f1" => Some(Self::F1),
+            "f2" => Some(Self::F2),
+            "f3" => Some(Self::F3),
+            "f4" => Some(Self::F4),
+            "f5" => Some(Self::F5),
+            "f6" => Some(Self::F6),
+            "f7" => Some(Self::F7),
+            "f8" => Some(Self::F8),
+            "f9" => Some(Self::F9),
+            "f10" => Some(Self::F10),
+            "f11" => Some(Self::F11),
+            "f12" => Some(Self::F12),
+            "f13" => Some(Self::F13),
+            "f14" => Some(Self::F14),
+            "f15" => Some(Self::F15),
+            _ => None,
+        }
+    }
+}
+
+/// The number of [`FloatRegister`]s
+pub const NUM_FLOAT_REGS: usize = 16;
+
+impl fmt::Display for FloatRegister {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::F0 => write!(f, "F0"),
+            Self::F1 => write!(f, "F1"),
+            Self::F2 => write!(f, "F2"),
+            Self::F3 => write!(f, "F3"),
+            Self::F4 => write!(f, "F4"),
+            Self::F5 => write!(f, "F5"),
+            Self::F6 => write!(f, "F6"),
+            Self::F7 => write!(f, "F7"),
+            Self::F8 => write!(f, "F8"),
+            Self::F9 => write!(f, "F9"),
+            Self::F10 => write!(f, "F10"),
+            Self::F11 => write!(f, "F11"),
+            Self::F12 => write!(f, "F12"),
+            Self::F13 => write!(f, "F13"),
+            Self::F14 => write!(f, "F14"),
+            Self::F15 => write!(f, "F15"),
+        }
+    }
+}
+
+/// Represents the set of [`FloatRegister`]s
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FloatRegisters(pub [f64; NUM_FLOAT_REGS]);
+
+impl fmt::Display for FloatRegisters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0
+            .iter()
+            .enumerate()
+            .try_for_each(|(r, v)| write!(f, "{}={v} ", FloatRegister::from_repr(r as u8).unwrap()))
+    }
+}
+
+impl FloatRegisters {
+    /// Make an new [`FloatRegisters`] instance with default state
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resets all floating-point registers to their default state (`0.0`).
+    pub fn reset(&mut self) {
+        self.0.iter_mut().for_each(|r| *r = 0.0);
+    }
+
+    /// Reads the value from the specified floating-point register.
+    pub fn read(&self, reg: FloatRegister) -> f64 {
+        self.0[reg as usize]
+    }
+
+    /// Writes a value to the specified floating-point register.
+    pub fn write(&mut self, reg: FloatRegister, value: f64) {
+        self.0[reg as usize] = value;
+    }
+}
+
+/// Represents the set of vector registers used by the virtual CPU.
+///
+/// Vector registers live in a separate bank from [`Register`]s and hold
+/// 128-bit values, which packed operators split into 8, 16, 32 or 64-bit lanes.
+#[repr(u8)]
+#[derive(FromRepr, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorRegister {
+    /// A 128-bit vector register.
+    V0,
+    /// A 128-bit vector register.
+    V1,
+    /// A 128-bit vector register.
+    V2,
+    /// A 128-bit vector register.
+    V3,
+    /// A 128-bit vector register.
+    V4,
+    /// A 128-bit vector register.
+    V5,
+    /// A 128-bit vector register.
+    V6,
+    /// A 128-bit vector register.
+    V7,
+    /// A 128-bit vector register.
+    V8,
+    /// A 128-bit vector register.
+    V9,
+    /// A 128-bit vector register.
+    V10,
+    /// A 128-bit vector register.
+    V11,
+    /// A 128-bit vector register.
+    V12,
+    /// A 128-bit vector register.
+    V13,
+    /// A 128-bit vector register.
+    V14,
+    /// A 128-bit vector register.
+    V15,
+}
+
+impl VectorRegister {
+    /// Reinterpret the [`VectorRegister`] from [`str`]
+    pub fn from_str<S: AsRef<str>>(s: S) -> Option<Self> {
+        match &*s.as_ref().to_lowercase() {
+            "v0" => Some(Self::V0),
+            "v1" => Some(Self::V1),
+            "v2" => Some(Self::V2),
+            "v3" => Some(Self::V3),
+            "v4" => Some(Self::V4),
+            "v5" => Some(Self::V5),
+            "v6" => Some(Self::V6),
+            "v7" => Some(Self::V7),
+            "v8" => Some(Self::V8),
+            "v9" => Some(Self::V9),
+            "v10" => Some(Self::V10),
+            "v11" => Some(Self::V11),
+            "v12" => Some(Self::V12),
+            "v13" => Some(Self::V13),
+            "v14" => Some(Self::V14),
+            "v15" => Some(Self::V15),
+            _ => None,
+        }
+    }
+}
+
+/// The number of [`VectorRegister`]s
+pub const NUM_VECTOR_REGS: usize = 16;
+
+impl fmt::Display for VectorRegister {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::V0 => write!(f, "V0"),
+            Self::V1 => write!(f, "V1"),
+            Self::V2 => write!(f, "V2"),
+            Self::V3 => write!(f, "V3"),
+            Self::V4 => write!(f, "V4"),
+            Self::V5 => write!(f, "V5"),
+            Self::V6 => write!(f, "V6"),
+            Self::V7 => write!(f, "V7"),
+            Self::V8 => write!(f, "V8"),
+            Self::V9 => write!(f, "V9"),
+            Self::V10 => write!(f, "V10"),
+            Self::V11 => write!(f, "V11"),
+            Self::V12 => write!(f, "V12"),
+            Self::V13 => write!(f, "V13"),
+            Self::V14 => write!(f, "V14"),
+            Self::V15 => write!(f, "V15"),
+        }
+    }
+}
+
+/// Represents the set of [`VectorRegister`]s
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VectorRegisters(pub [u128; NUM_VECTOR_REGS]);
+
+impl fmt::Display for VectorRegisters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.iter().enumerate().try_for_each(|(r, v)| {
+            write!(
+                f,
+                "{}={v:032x} ",
+                VectorRegister::from_repr(r as u8).unwrap()
+            )
+        })
+    }
+}
+
+impl VectorRegisters {
+    /// Make an new [`VectorRegisters`] instance with default state
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resets all vector registers to their default state (`0`).
+    pub fn reset(&mut self) {
+        self.0.iter_mut().for_each(|r| *r = 0);
+    }
+
+    /// Reads the value from the specified vector register.
+    pub fn read(&self, reg: VectorRegister) -> u128 {
+        self.0[reg as usize]
+    }
+
+    /// Writes a value to the specified vector register.
+    pub fn write(&mut self, reg: VectorRegister, value: u128) {
+        self.0[reg as usize] = value;
+    }
+}
+
 /// A structure representing the flags stored in the [`Register::RF`].
 ///
 /// This structure encapsulates the [`Register::RF`] register, which holds
@@ -595,8 +1939,8 @@ impl RFlags {
 
     /// Reads the Parity Flag (PF)
     ///
-    /// Indicates whether the number of set bits in the result is even (0) or
-    /// odd (1).
+    /// Indicates whether the number of set bits in the least significant byte
+    /// of the result is even (1) or odd (0).
     pub fn read_pf(&self) -> u64 {
         (self.0 >> 2) & 1
     }
@@ -626,4 +1970,56 @@ impl RFlags {
             self.0 &= !(1 << 4);
         }
     }
+
+    /// Reads the Floating-Point Invalid Operation Flag (IE)
+    ///
+    /// Set when a floating-point operation produces a NaN from non-NaN
+    /// operands, or when a conversion to an integer is out of range. It is
+    /// sticky, i.e. never cleared by floating-point operations.
+    pub fn read_ie(&self) -> u64 {
+        (self.0 >> 32) & 1
+    }
+
+    /// Writes the specified Floating-Point Invalid Operation Flag (IE) value
+    pub fn write_ie(&mut self, value: u64) {
+        if value & 1 == 1 {
+            self.0 |= 1 << 32;
+        } else {
+            self.0 &= !(1 << 32);
+        }
+    }
+
+    /// Reads the Floating-Point Divide-by-Zero Flag (ZE)
+    ///
+    /// Set when a finite non-zero floating-point value is divided by zero. It
+    /// is sticky, i.e. never cleared by floating-point operations.
+    pub fn read_ze(&self) -> u64 {
+        (self.0 >> 33) & 1
+    }
+
+    /// Writes the specified Floating-Point Divide-by-Zero Flag (ZE) value
+    pub fn write_ze(&mut self, value: u64) {
+        if value & 1 == 1 {
+            self.0 |= 1 << 33;
+        } else {
+            self.0 &= !(1 << 33);
+        }
+    }
+
+    /// Reads the Floating-Point Overflow Flag (OE)
+    ///
+    /// Set when a floating-point operation on finite operands produces an
+    /// infinity. It is sticky, i.e. never cleared by floating-point operations.
+    pub fn read_oe(&self) -> u64 {
+        (self.0 >> 34) & 1
+    }
+
+    /// Writes the specified Floating-Point Overflow Flag (OE) value
+    pub fn write_oe(&mut self, value: u64) {
+        if value & 1 == 1 {
+            self.0 |= 1 << 34;
+        } else {
+            self.0 &= !(1 << 34);
+        }
+    }
 }
@@ -7,6 +7,8 @@ use thiserror::Error;
 pub enum Error {
     #[error("I/O error: {0}")]
     Io(#[from] io::Error),
+    #[error("Too many operands ({0}) for a custom instruction")]
+    TooManyOperands(usize),
 }
 
 pub type Result<T> = result::Result<T, Error>;
@@ -8,9 +8,26 @@
 //! ## Exception Types
 //! - [`Exception::Exit`]: Indicates a normal program termination.
 //! - [`Exception::IllegalInstruction`]: Raised when an invalid or unsupported
-//!   instruction is encountered.
+//!   instruction is encountered, including one of a disabled ISA extension and
+//!   a custom opcode without a handler.
 //! - [`Exception::AccessViolation`]: Triggered when an attempt is made to
-//!   access restricted or invalid memory.
+//!   access restricted or invalid memory. It carries the faulting
+//!   [`MemoryAccess`].
+//! - [`Exception::StackOverflow`]: Triggered when a value is pushed onto a full
+//!   stack.
+//! - [`Exception::StackUnderflow`]: Triggered when a value is popped from an
+//!   empty stack.
+//! - [`Exception::DivideByZero`]: Triggered when an integer is divided by zero.
+//! - [`Exception::Halt`]: Raised by `hlt`. Unlike [`Exception::Exit`], the
+//!   execution may be resumed from the next instruction.
+//!
+//! ## Exception Records
+//! Instruction handlers raise a bare [`Exception`], and the emulator wraps it
+//! in an [`ExceptionRecord`] along with the IP and the raw opcode of the
+//! faulting instruction. The kind stays matchable in [`ExceptionRecord::kind`],
+//! while the [`fmt::Display`] of the record renders a message such as
+//! `AccessViolation on a write of 8 bytes to 0x110000 at IP 0x1a (opcode
+//! 0x05)`.
 
 use core::fmt;
 
@@ -25,7 +42,17 @@ pub enum Exception {
     IllegalInstruction,
     /// Indicates a violation of memory access, such as accessing out-of-bounds
     /// memory
-    AccessViolation,
+    AccessViolation(MemoryAccess),
+    /// Indicates that a push exceeded the bottom of the stack region
+    StackOverflow,
+    /// Indicates that a pop exceeded the top of the stack region
+    StackUnderflow,
+    /// Indicates that a division or remainder instruction was executed with
+    /// a zero divisor
+    DivideByZero,
+    /// Indicates that a `hlt` instruction stopped execution, e.g. to wait for
+    /// an event from the host
+    Halt,
 }
 
 impl fmt::Display for Exception {
@@ -33,7 +60,96 @@ impl fmt::Display for Exception {
         match self {
             Self::Exit => write!(f, "Exit"),
             Self::IllegalInstruction => write!(f, "IllegalInstruction"),
-            Self::AccessViolation => write!(f, "AccessViolation"),
+            Self::AccessViolation(access) => write!(f, "AccessViolation on a {access}"),
+            Self::StackOverflow => write!(f, "StackOverflow"),
+            Self::StackUnderflow => write!(f, "StackUnderflow"),
+            Self::DivideByZero => write!(f, "DivideByZero"),
+            Self::Halt => write!(f, "Halt"),
+        }
+    }
+}
+
+/// Represents the kind of a memory access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    /// A read of an operand
+    Read,
+    /// A write of an operand
+    Write,
+    /// A fetch of an instruction
+    Fetch,
+}
+
+impl fmt::Display for AccessKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Write => write!(f, "write"),
+            Self::Fetch => write!(f, "fetch"),
+        }
+    }
+}
+
+/// Represents a memory access, e.g. the one raising an
+/// [`Exception::AccessViolation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryAccess {
+    /// The address of the first byte accessed
+    pub address: u64,
+    /// The kind of the access
+    pub kind: AccessKind,
+    /// The number of bytes accessed
+    pub size: usize,
+}
+
+impl fmt::Display for MemoryAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = if self.size == 1 { "byte" } else { "bytes" };
+        let preposition = match self.kind {
+            AccessKind::Write => "to",
+            AccessKind::Read | AccessKind::Fetch => "from",
+        };
+        write!(
+            f,
+            "{} of {} {unit} {preposition} {:#x}",
+            self.kind, self.size, self.address
+        )
+    }
+}
+
+/// Represents an [`Exception`] raised by an instruction, along with where it
+/// was raised.
+#[derive(Debug, Clone)]
+pub struct ExceptionRecord {
+    /// The exception raised
+    pub kind: Exception,
+    /// The IP of the faulting instruction
+    pub ip: u64,
+    /// The raw opcode of the faulting instruction, with the escape byte in the
+    /// high byte for the second page.
+    ///
+    /// It is `None` if the opcode itself could not be fetched.
+    pub opcode: Option<u16>,
+}
+
+impl ExceptionRecord {
+    /// Returns the faulting memory access of an
+    /// [`Exception::AccessViolation`]
+    pub fn access(&self) -> Option<&MemoryAccess> {
+        match &self.kind {
+            Exception::AccessViolation(access) => Some(access),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ExceptionRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at IP {:#x}", self.kind, self.ip)?;
+        match self.opcode {
+            Some(opcode) if opcode > u8::MAX as u16 => write!(f, " (opcode {opcode:#06x})"),
+            Some(opcode) => write!(f, " (opcode {opcode:#04x})"),
+            None => Ok(()),
         }
     }
 }
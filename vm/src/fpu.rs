@@ -0,0 +1,256 @@
+//! This module implements a sequential Floating-Point Unit (FPU) in software.
+//!
+//! The FPU operates on the [`FloatRegister`] bank, which holds IEEE-754 double
+//! precision values, and rounds to nearest, ties to even, like the host does.
+//!
+//! ## Flags
+//! - `fcmp`: ZF, PF and CF are set following the x86 `ucomisd` rules, so that
+//!   the unsigned conditions (`ja`, `jb`, ...) apply. Equal sets ZF, less than
+//!   sets CF and unordered (either operand is NaN) sets all three. OF, SF and
+//!   AF are cleared.
+//! - `fadd`, `fsub`, `fmul`, `fdiv`, `fsqrt`: The floating-point flags IE, ZE
+//!   and OE of [`RFlags`] are set when the operation is invalid, divides by
+//!   zero or overflows respectively. They are sticky and never cleared by the
+//!   FPU, so a sequence of operations can be checked once at the end.
+//! - `cvtf2si`: IE is set if the value is NaN or out of the range of a signed
+//!   integer of the destination width, and the result is the most negative
+//!   value of that width, e.g. `8000000000000000h` for a 64-bit register.
+//! - `fmov` and `cvtsi2f` leave the flags untouched.
+//!
+//! [`RFlags`]: crate::emulator::RFlags
+//!
+//! ## Memory operands
+//! `fmov` loads and stores a `qword` memory operand as a double precision and
+//! a `dword` memory operand as a single precision value. Other sizes raise an
+//! [`Exception::IllegalInstruction`].
+//!
+//! [`FloatRegister`]: crate::emulator::FloatRegister
+
+use core::cmp::Ordering;
+
+use crate::{
+    alu::{
+        define_handler_trait, filter_special_reg, handle_memop_read, handle_memop_write,
+        memop_size, sign_extend,
+    },
+    emulator::Emulator,
+    exception::Exception,
+    isa::{Instruction, OperandSize},
+};
+
+// Move operators
+define_handler_trait!(FmovFF, handle_fmov_f_f);
+define_handler_trait!(FmovFRM, handle_fmov_f_rm);
+define_handler_trait!(FmovRMF, handle_fmov_rm_f);
+define_handler_trait!(FmovFIMM, handle_fmov_f_imm);
+define_handler_trait!(FmovFR, handle_fmov_f_r);
+define_handler_trait!(FmovRF, handle_fmov_r_f);
+
+// Arithmetic operators
+define_handler_trait!(FaddFF, handle_fadd_f_f);
+define_handler_trait!(FsubFF, handle_fsub_f_f);
+define_handler_trait!(FmulFF, handle_fmul_f_f);
+define_handler_trait!(FdivFF, handle_fdiv_f_f);
+define_handler_trait!(FsqrtFF, handle_fsqrt_f_f);
+
+// Comparison operators
+define_handler_trait!(FcmpFF, handle_fcmp_f_f);
+
+// Conversion operators
+define_handler_trait!(Cvtsi2fFR, handle_cvtsi2f_f_r);
+define_handler_trait!(Cvtf2siRF, handle_cvtf2si_r_f);
+
+/// Updates the sticky floating-point flags according to the result of an
+/// operation on `operands`.
+fn update_float_flags(emulator: &mut Emulator, operands: &[f64], value: f64) {
+    let mut rf = emulator.regs.read_rf();
+    if value.is_nan() && !operands.iter().any(|x| x.is_nan()) {
+        rf.write_ie(1);
+    }
+    if value.is_infinite() && operands.iter().all(|x| x.is_finite()) {
+        // Finite operands only reach an infinity through a zero divisor or an
+        // overflow
+        if operands.contains(&0.0) {
+            rf.write_ze(1);
+        } else {
+            rf.write_oe(1);
+        }
+    }
+    emulator.regs.write_rf(rf);
+}
+
+/// Applies `op` to both floating-point register operands, stores the result
+/// in the first one and updates the floating-point flags.
+fn handle_float_op(
+    emulator: &mut Emulator,
+    insn: &Instruction,
+    op: fn(f64, f64) -> f64,
+) -> Result<(), Exception> {
+    let lhs = emulator.fregs.read(insn.op0_freg());
+    let rhs = emulator.fregs.read(insn.op1_freg());
+    let value = op(lhs, rhs);
+    emulator.fregs.write(insn.op0_freg(), value);
+    update_float_flags(emulator, &[lhs, rhs], value);
+
+    Ok(())
+}
+
+impl FmovFF for Emulator {
+    fn handle_fmov_f_f(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let value = self.fregs.read(insn.op1_freg());
+        self.fregs.write(insn.op0_freg(), value);
+
+        Ok(())
+    }
+}
+
+impl FmovFRM for Emulator {
+    fn handle_fmov_f_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let value = match memop_size(insn, 1)? {
+            OperandSize::DWord => f32::from_bits(handle_memop_read(self, 1, insn)? as u32) as f64,
+            OperandSize::QWord => f64::from_bits(handle_memop_read(self, 1, insn)?),
+            _ => return Err(Exception::IllegalInstruction),
+        };
+        self.fregs.write(insn.op0_freg(), value);
+
+        Ok(())
+    }
+}
+
+impl FmovRMF for Emulator {
+    fn handle_fmov_rm_f(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let value = self.fregs.read(insn.op1_freg());
+        let value = match memop_size(insn, 0)? {
+            OperandSize::DWord => (value as f32).to_bits() as u64,
+            OperandSize::QWord => value.to_bits(),
+            _ => return Err(Exception::IllegalInstruction),
+        };
+        handle_memop_write(self, 0, insn, value)?;
+
+        Ok(())
+    }
+}
+
+impl FmovFIMM for Emulator {
+    fn handle_fmov_f_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let value = f64::from_bits(insn.immediate());
+        self.fregs.write(insn.op0_freg(), value);
+
+        Ok(())
+    }
+}
+
+impl FmovFR for Emulator {
+    fn handle_fmov_f_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let value = f64::from_bits(self.regs.read(insn.op1_reg()));
+        self.fregs.write(insn.op0_freg(), value);
+
+        Ok(())
+    }
+}
+
+impl FmovRF for Emulator {
+    fn handle_fmov_r_f(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+
+        let value = self.fregs.read(insn.op1_freg()).to_bits();
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl FaddFF for Emulator {
+    fn handle_fadd_f_f(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_float_op(self, insn, |lhs, rhs| lhs + rhs)
+    }
+}
+
+impl FsubFF for Emulator {
+    fn handle_fsub_f_f(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_float_op(self, insn, |lhs, rhs| lhs - rhs)
+    }
+}
+
+impl FmulFF for Emulator {
+    fn handle_fmul_f_f(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_float_op(self, insn, |lhs, rhs| lhs * rhs)
+    }
+}
+
+impl FdivFF for Emulator {
+    fn handle_fdiv_f_f(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_float_op(self, insn, |lhs, rhs| lhs / rhs)
+    }
+}
+
+impl FsqrtFF for Emulator {
+    fn handle_fsqrt_f_f(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let src = self.fregs.read(insn.op1_freg());
+        let value = src.sqrt();
+        self.fregs.write(insn.op0_freg(), value);
+        update_float_flags(self, &[src], value);
+
+        Ok(())
+    }
+}
+
+impl FcmpFF for Emulator {
+    fn handle_fcmp_f_f(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let lhs = self.fregs.read(insn.op0_freg());
+        let rhs = self.fregs.read(insn.op1_freg());
+
+        let (zf, pf, cf) = match lhs.partial_cmp(&rhs) {
+            None => (1, 1, 1),
+            Some(Ordering::Less) => (0, 0, 1),
+            Some(Ordering::Equal) => (1, 0, 0),
+            Some(Ordering::Greater) => (0, 0, 0),
+        };
+        let mut rf = self.regs.read_rf();
+        rf.write_zf(zf);
+        rf.write_pf(pf);
+        rf.write_cf(cf);
+        rf.write_of(0);
+        rf.write_sf(0);
+        rf.write_af(0);
+        self.regs.write_rf(rf);
+
+        Ok(())
+    }
+}
+
+impl Cvtsi2fFR for Emulator {
+    fn handle_cvtsi2f_f_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op1_r = insn.op1_reg();
+        let value = sign_extend(op1_r.size(), self.regs.read(op1_r)) as i64 as f64;
+        self.fregs.write(insn.op0_freg(), value);
+
+        Ok(())
+    }
+}
+
+impl Cvtf2siRF for Emulator {
+    fn handle_cvtf2si_r_f(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+
+        // The most negative value of the destination width, whose negation is
+        // the first value past the maximum
+        let min = (i64::MIN >> (64 - op0_r.size().to_size() * 8)) as f64;
+        let value = self.fregs.read(insn.op1_freg()).trunc();
+        let value = if value.is_nan() || value < min || value >= -min {
+            let mut rf = self.regs.read_rf();
+            rf.write_ie(1);
+            self.regs.write_rf(rf);
+            min as i64
+        } else {
+            value as i64
+        };
+        self.regs.write(op0_r, value as u64);
+
+        Ok(())
+    }
+}
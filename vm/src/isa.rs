@@ -14,7 +14,22 @@ use std::io::Write;
 
 use strum_macros::FromRepr;
 
-use crate::{emulator::Register, error};
+use crate::{
+    emulator::{FloatRegister, Register, VectorRegister},
+    error,
+};
+
+/// The opcode byte that escapes to the second page of [`OpCode`]s.
+///
+/// An opcode of the second page is encoded as this byte followed by the low
+/// byte of the opcode, e.g. `FFh 00h` for [`OpCode::Cpuid`].
+pub const ESCAPE: u8 = 0xFF;
+
+/// The version of the ISA, as reported by `cpuid`.
+pub const ISA_VERSION: u64 = 1;
+
+/// The number of [`Extension`]s.
+pub const NUM_EXTENSIONS: usize = 4;
 
 /// Represents the size classes of operands in a instruction.
 #[repr(u8)]
@@ -28,6 +43,8 @@ pub enum OperandSize {
     DWord,
     /// 64-bit operand (quad word).
     QWord,
+    /// 128-bit operand (vector), only valid for vector memory operands.
+    XmmWord,
 }
 
 impl fmt::Display for OperandSize {
@@ -37,6 +54,7 @@ impl fmt::Display for OperandSize {
             Self::Word => write!(f, "Word"),
             Self::DWord => write!(f, "DWord"),
             Self::QWord => write!(f, "QWord"),
+            Self::XmmWord => write!(f, "XmmWord"),
         }
     }
 }
@@ -49,6 +67,7 @@ impl OperandSize {
             "word" | "word ptr" => Some(Self::Word),
             "dword" | "dword ptr" => Some(Self::DWord),
             "qword" | "qword ptr" => Some(Self::QWord),
+            "xmmword" | "xmmword ptr" => Some(Self::XmmWord),
             _ => None,
         }
     }
@@ -60,6 +79,20 @@ impl OperandSize {
             Self::Word => 2,
             Self::DWord => 4,
             Self::QWord => 8,
+            Self::XmmWord => 16,
+        }
+    }
+
+    /// Returns the mask covering all bits of the [`OperandSize`]
+    ///
+    /// [`OperandSize::XmmWord`] is wider than 64 bits, so its mask covers all
+    /// bits of a `u64`.
+    pub fn mask(&self) -> u64 {
+        match self {
+            Self::Byte => u8::MAX as u64,
+            Self::Word => u16::MAX as u64,
+            Self::DWord => u32::MAX as u64,
+            Self::QWord | Self::XmmWord => u64::MAX,
         }
     }
 }
@@ -80,6 +113,14 @@ pub enum Operand {
     /// when the operand is a register, such as in register-to-register
     /// operations.
     Register(Register),
+    /// Indicates a floating-point register operand.
+    ///
+    /// A floating-point register operand takes a single [`FloatRegister`].
+    FloatRegister(FloatRegister),
+    /// Indicates a vector register operand.
+    ///
+    /// A vector register operand takes a single [`VectorRegister`].
+    VectorRegister(VectorRegister),
     /// Indicates a memory operand.
     ///
     /// Memory operands are used when an instruction needs to access memory.
@@ -87,8 +128,8 @@ pub enum Operand {
     ///
     /// - `size`: The size of memory to be read or written, represented as an
     ///   [`OperandSize`].
-    /// - `displacement`: The offset from a base address to calculate the memory
-    ///   location.
+    /// - `displacement`: The signed offset from a base address to calculate the
+    ///   memory location.
     /// - `scale`: A multiplier used for indexed memory access, typically for
     ///   array indexing.
     /// - `index_reg`: An optional register used as an index in indexed
@@ -96,7 +137,7 @@ pub enum Operand {
     /// - `base_reg`: An optional register used as the base address.
     Memory {
         size: OperandSize,
-        displacement: u64,
+        displacement: i64,
         scale: u8,
         index_reg: Option<Register>,
         base_reg: Option<Register>,
@@ -122,7 +163,9 @@ pub enum Operand {
 ///
 /// The operands can be registers, memory locations, immediate values, or branch
 /// addresses, depending on the type of instruction. As per our design
-/// principle, we only accept instructions up to two operands.
+/// principle, we only accept instructions up to three operands, and only the
+/// non-destructive forms of the binary ALU operators and custom instructions
+/// take the third one.
 #[derive(Debug, Clone)]
 pub struct Instruction {
     /// The [`OpCode`] for the instruction.
@@ -134,8 +177,13 @@ pub struct Instruction {
     /// The [`Operand`]s for the instruction.
     ///
     /// The operands are the values or locations on which the instruction
-    /// operates. An instruction can have up to two operands.
-    pub operands: [Operand; 2],
+    /// operates. An instruction can have up to three operands.
+    pub operands: [Operand; 3],
+    /// The custom opcode of an [`OpCode::Custom`] instruction.
+    ///
+    /// It follows the opcode in the encoding and selects the handler
+    /// registered on the emulator, see [`crate::custom`].
+    pub custom: u8,
 }
 
 impl Instruction {
@@ -145,6 +193,7 @@ impl Instruction {
         Self {
             opcode: OpCode::from_repr(0).unwrap(),
             operands: Default::default(),
+            custom: 0,
         }
     }
 
@@ -154,6 +203,7 @@ impl Instruction {
         Self {
             opcode,
             operands: Default::default(),
+            custom: 0,
         }
     }
 
@@ -228,6 +278,84 @@ impl Instruction {
         self.operands[1] = operand;
     }
 
+    /// Returns the [`Register`] of the third operand
+    pub fn op2_reg(&self) -> Register {
+        match self.operands[2] {
+            Operand::Register(reg) => reg,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Sets the [`Register`] of the third operand
+    pub fn set_op2_reg(&mut self, reg: Register) {
+        self.operands[2] = Operand::Register(reg);
+    }
+
+    /// Returns the [`FloatRegister`] of the first operand
+    pub fn op0_freg(&self) -> FloatRegister {
+        match self.operands[0] {
+            Operand::FloatRegister(reg) => reg,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Sets the [`FloatRegister`] of the first operand
+    pub fn set_op0_freg(&mut self, reg: FloatRegister) {
+        self.operands[0] = Operand::FloatRegister(reg);
+    }
+
+    /// Returns the [`FloatRegister`] of the second operand
+    pub fn op1_freg(&self) -> FloatRegister {
+        match self.operands[1] {
+            Operand::FloatRegister(reg) => reg,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Sets the [`FloatRegister`] of the second operand
+    pub fn set_op1_freg(&mut self, reg: FloatRegister) {
+        self.operands[1] = Operand::FloatRegister(reg);
+    }
+
+    /// Returns the [`VectorRegister`] of the first operand
+    pub fn op0_vreg(&self) -> VectorRegister {
+        match self.operands[0] {
+            Operand::VectorRegister(reg) => reg,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Sets the [`VectorRegister`] of the first operand
+    pub fn set_op0_vreg(&mut self, reg: VectorRegister) {
+        self.operands[0] = Operand::VectorRegister(reg);
+    }
+
+    /// Returns the [`VectorRegister`] of the second operand
+    pub fn op1_vreg(&self) -> VectorRegister {
+        match self.operands[1] {
+            Operand::VectorRegister(reg) => reg,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Sets the [`VectorRegister`] of the second operand
+    pub fn set_op1_vreg(&mut self, reg: VectorRegister) {
+        self.operands[1] = Operand::VectorRegister(reg);
+    }
+
+    /// Returns the [`Operand::Immediate64`] of the first operand
+    pub fn op0_immediate(&self) -> u64 {
+        match &self.operands[0] {
+            Operand::Immediate64(imm) => *imm,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Sets the [`Operand::Immediate64`] of the first operand
+    pub fn set_op0_immediate(&mut self, imm: u64) {
+        self.operands[0] = Operand::Immediate64(imm);
+    }
+
     /// Returns the [`Operand::Immediate64`] of the second operand
     pub fn immediate(&self) -> u64 {
         match &self.operands[1] {
@@ -241,6 +369,19 @@ impl Instruction {
         self.operands[1] = Operand::Immediate64(imm.into());
     }
 
+    /// Returns the [`Operand::Immediate64`] of the third operand
+    pub fn op2_immediate(&self) -> u64 {
+        match &self.operands[2] {
+            Operand::Immediate64(imm) => *imm,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Sets the [`Operand::Immediate64`] of the third operand
+    pub fn set_op2_immediate(&mut self, imm: u64) {
+        self.operands[2] = Operand::Immediate64(imm);
+    }
+
     /// Returns the [`OperandSize`] of the specified operand
     pub fn mem_size(&self, op: usize) -> OperandSize {
         match &self.operands[op] {
@@ -297,14 +438,27 @@ impl Instruction {
     ///   returns `Ok(())`. If an error occurs during encoding or writing, it
     ///   returns an [`error::Result`] containing the error.
     pub fn encode<W: Write>(&self, mut writer: W) -> error::Result<()> {
-        writer.write(&[self.opcode as u8])?;
+        let opcode = self.opcode as u16;
+        if opcode > u8::MAX as u16 {
+            writer.write_all(&[ESCAPE])?;
+        }
+        writer.write(&[opcode as u8])?;
+        if self.opcode == OpCode::Custom {
+            writer.write_all(&[self.custom])?;
+        }
 
-        for operand in &self.operands {
+        for (i, operand) in self.operands.iter().enumerate() {
             match operand {
                 Operand::None => break,
                 Operand::Register(reg) => {
                     writer.write(&[*reg as u8])?;
                 }
+                Operand::FloatRegister(reg) => {
+                    writer.write_all(&[*reg as u8])?;
+                }
+                Operand::VectorRegister(reg) => {
+                    writer.write_all(&[*reg as u8])?;
+                }
                 Operand::Memory {
                     size,
                     displacement,
@@ -333,6 +487,9 @@ impl Instruction {
                     };
                 }
                 Operand::Immediate64(imm) => {
+                    if i == 2 && self.opcode.marks_immediate() {
+                        writer.write_all(&[u8::MAX])?;
+                    }
                     writer.write(&imm.to_le_bytes())?;
                 }
                 Operand::Branch(target) => {
@@ -356,6 +513,12 @@ pub enum Mnemonic {
     /// Executes an undefined instruction that typically used to trigger an
     /// exception.
     Ud,
+    /// Does nothing.
+    Nop,
+    /// Halts execution until the host resumes it.
+    Hlt,
+    /// Reports the ISA version in `R0` and the enabled extensions in `R1`.
+    Cpuid,
     /// Moves data from one location to another.
     Mov,
     /// Performs addition.
@@ -372,10 +535,201 @@ pub enum Mnemonic {
     Xchg,
     /// Performs signed integer multiplication.
     Imul,
+    /// Performs addition with the carry flag (CF).
+    Adc,
+    /// Performs subtraction with the carry flag (CF) as borrow.
+    Sbb,
+    /// Performs unsigned integer multiplication into a 128-bit result, storing
+    /// the low half in the first operand and the high half in the second,
+    /// e.g. `mul r0, r1` leaves the low half in `r0` and the high half in `r1`.
+    /// The two operands must be distinct registers.
+    Mul,
+    /// Computes the high 64 bits of an unsigned integer multiplication.
+    Mulh,
+    /// Computes the high 64 bits of a signed integer multiplication.
+    Imulh,
+    /// Performs unsigned integer division.
+    Div,
+    /// Performs signed integer division.
+    Idiv,
+    /// Computes the remainder of unsigned integer division.
+    Rem,
+    /// Computes the remainder of signed integer division.
+    Irem,
+    /// Shifts bits to the left.
+    Shl,
+    /// Shifts bits to the right, filling with zeros.
+    Shr,
+    /// Shifts bits to the right, preserving the sign bit.
+    Sar,
+    /// Rotates bits to the left.
+    Rol,
+    /// Rotates bits to the right.
+    Ror,
+    /// Rotates bits to the left through the carry flag (CF).
+    Rcl,
+    /// Rotates bits to the right through the carry flag (CF).
+    Rcr,
+    /// Moves a value, zero-extending it to 64 bits.
+    Movzx,
+    /// Moves a value, sign-extending it to 64 bits.
+    Movsx,
+    /// Loads the effective address of a memory operand.
+    Lea,
+    /// Moves if the zero flag (ZF) is set (equal to zero).
+    Cmovz,
+    /// Moves if the zero flag (ZF) is not set (not equal to zero).
+    Cmovnz,
+    /// Moves if less than or equal (ZF = 1 or SF ≠ OF).
+    Cmovle,
+    /// Moves if greater (ZF = 0 and SF = OF).
+    Cmovg,
+    /// Moves if greater than or equal (SF = OF).
+    Cmovge,
+    /// Moves if below (CF = 1).
+    Cmovb,
+    /// Moves if less (SF ≠ OF).
+    Cmovl,
+    /// Moves if above (CF = 0 and ZF = 0).
+    Cmova,
+    /// Moves if above or equal (CF = 0).
+    Cmovae,
+    /// Moves if below or equal (CF = 1 or ZF = 1).
+    Cmovbe,
+    /// Moves if sign (SF = 1).
+    Cmovs,
+    /// Moves if not sign (SF = 0).
+    Cmovns,
+    /// Moves if overflow (OF = 1).
+    Cmovo,
+    /// Moves if not overflow (OF = 0).
+    Cmovno,
+    /// Moves if parity (PF = 1).
+    Cmovp,
+    /// Moves if not parity (PF = 0).
+    Cmovnp,
+    /// Sets to 1 if the zero flag (ZF) is set (equal to zero), 0 otherwise.
+    Setz,
+    /// Sets to 1 if the zero flag (ZF) is not set (not equal to zero), 0
+    /// otherwise.
+    Setnz,
+    /// Sets to 1 if less than or equal (ZF = 1 or SF ≠ OF), 0 otherwise.
+    Setle,
+    /// Sets to 1 if greater (ZF = 0 and SF = OF), 0 otherwise.
+    Setg,
+    /// Sets to 1 if greater than or equal (SF = OF), 0 otherwise.
+    Setge,
+    /// Sets to 1 if below (CF = 1), 0 otherwise.
+    Setb,
+    /// Sets to 1 if less (SF ≠ OF), 0 otherwise.
+    Setl,
+    /// Sets to 1 if above (CF = 0 and ZF = 0), 0 otherwise.
+    Seta,
+    /// Sets to 1 if above or equal (CF = 0), 0 otherwise.
+    Setae,
+    /// Sets to 1 if below or equal (CF = 1 or ZF = 1), 0 otherwise.
+    Setbe,
+    /// Sets to 1 if sign (SF = 1), 0 otherwise.
+    Sets,
+    /// Sets to 1 if not sign (SF = 0), 0 otherwise.
+    Setns,
+    /// Sets to 1 if overflow (OF = 1), 0 otherwise.
+    Seto,
+    /// Sets to 1 if not overflow (OF = 0), 0 otherwise.
+    Setno,
+    /// Sets to 1 if parity (PF = 1), 0 otherwise.
+    Setp,
+    /// Sets to 1 if not parity (PF = 0), 0 otherwise.
+    Setnp,
+    /// Counts the number of set bits.
+    Popcnt,
+    /// Counts the number of leading zero bits.
+    Lzcnt,
+    /// Counts the number of trailing zero bits.
+    Tzcnt,
+    /// Reverses the byte order of a register.
+    Bswap,
+    /// Copies the selected bit into CF.
+    Bt,
+    /// Copies the selected bit into CF and sets it.
+    Bts,
+    /// Copies the selected bit into CF and clears it.
+    Btr,
+    /// Copies the selected bit into CF and complements it.
+    Btc,
+    /// Moves a floating-point value.
+    Fmov,
+    /// Performs floating-point addition.
+    Fadd,
+    /// Performs floating-point subtraction.
+    Fsub,
+    /// Performs floating-point multiplication.
+    Fmul,
+    /// Performs floating-point division.
+    Fdiv,
+    /// Computes the floating-point square root.
+    Fsqrt,
+    /// Compares two floating-point values.
+    Fcmp,
+    /// Converts a signed integer to a floating-point value.
+    Cvtsi2f,
+    /// Converts a floating-point value to a signed integer, truncating toward
+    /// zero.
+    Cvtf2si,
+    /// Moves a 128-bit vector value.
+    Movdqu,
+    /// Moves the low 64 bits of a vector register from or to a register.
+    Movq,
+    /// Performs packed addition on 8-bit lanes.
+    Paddb,
+    /// Performs packed addition on 16-bit lanes.
+    Paddw,
+    /// Performs packed addition on 32-bit lanes.
+    Paddd,
+    /// Performs packed addition on 64-bit lanes.
+    Paddq,
+    /// Performs packed subtraction on 8-bit lanes.
+    Psubb,
+    /// Performs packed subtraction on 16-bit lanes.
+    Psubw,
+    /// Performs packed subtraction on 32-bit lanes.
+    Psubd,
+    /// Performs packed subtraction on 64-bit lanes.
+    Psubq,
+    /// Performs a bitwise AND on vector values.
+    Pand,
+    /// Performs a bitwise OR on vector values.
+    Por,
+    /// Performs a bitwise XOR on vector values.
+    Pxor,
+    /// Compares packed 8-bit lanes for equality.
+    Pcmpeqb,
+    /// Compares packed 16-bit lanes for equality.
+    Pcmpeqw,
+    /// Compares packed 32-bit lanes for equality.
+    Pcmpeqd,
+    /// Compares packed 64-bit lanes for equality.
+    Pcmpeqq,
+    /// Shuffles the bytes of a vector value.
+    Pshufb,
+    /// Reads a value from an I/O port.
+    In,
+    /// Writes a value to an I/O port.
+    Out,
     /// Increments the value of an operand.
     Inc,
     /// Decrements the value of an operand.
     Dec,
+    /// Performs a bitwise NOT operation.
+    Not,
+    /// Negates a value (two's complement).
+    Neg,
+    /// Copies `R2` bytes from `[R1]` to `[R0]`.
+    RepMovsb,
+    /// Fills `R2` bytes at `[R0]` with the low byte of `R1`.
+    RepStosb,
+    /// Compares `R2` bytes at `[R0]` and `[R1]` until they differ.
+    RepCmpsb,
     /// Performs a bitwise AND operation but does not store the result.
     Test,
     /// Compares two values by subtracting one from the other but does not store
@@ -395,6 +749,37 @@ pub enum Mnemonic {
     Jge,
     /// Jumps if below (CF = 1).
     Jb,
+    /// Jumps if less (SF ≠ OF).
+    Jl,
+    /// Jumps if above (CF = 0 and ZF = 0).
+    Ja,
+    /// Jumps if above or equal (CF = 0).
+    Jae,
+    /// Jumps if below or equal (CF = 1 or ZF = 1).
+    Jbe,
+    /// Jumps if sign (SF = 1).
+    Js,
+    /// Jumps if not sign (SF = 0).
+    Jns,
+    /// Jumps if overflow (OF = 1).
+    Jo,
+    /// Jumps if not overflow (OF = 0).
+    Jno,
+    /// Jumps if parity (PF = 1).
+    Jp,
+    /// Jumps if not parity (PF = 0).
+    Jnp,
+    /// Pushes a value onto the stack.
+    Push,
+    /// Pops a value from the stack.
+    Pop,
+    /// Pushes the return address onto the stack and jumps to a subroutine.
+    Call,
+    /// Pops the return address from the stack and jumps to it.
+    Ret,
+    /// Executes a custom instruction registered by the host, see
+    /// [`crate::custom`].
+    Custom(u8),
 
     /// Defines a byte (8-bit value).
     Db,
@@ -411,6 +796,9 @@ impl fmt::Display for Mnemonic {
         match self {
             Self::Exit => write!(f, "Exit"),
             Self::Ud => write!(f, "Ud"),
+            Self::Nop => write!(f, "Nop"),
+            Self::Hlt => write!(f, "Hlt"),
+            Self::Cpuid => write!(f, "Cpuid"),
             Self::Mov => write!(f, "Mov"),
             Self::Add => write!(f, "Add"),
             Self::Sub => write!(f, "Sub"),
@@ -419,8 +807,101 @@ impl fmt::Display for Mnemonic {
             Self::Xor => write!(f, "Xor"),
             Self::Xchg => write!(f, "Xchg"),
             Self::Imul => write!(f, "imul"),
+            Self::Adc => write!(f, "Adc"),
+            Self::Sbb => write!(f, "Sbb"),
+            Self::Mul => write!(f, "Mul"),
+            Self::Mulh => write!(f, "Mulh"),
+            Self::Imulh => write!(f, "Imulh"),
+            Self::Div => write!(f, "Div"),
+            Self::Idiv => write!(f, "Idiv"),
+            Self::Rem => write!(f, "Rem"),
+            Self::Irem => write!(f, "Irem"),
+            Self::Shl => write!(f, "Shl"),
+            Self::Shr => write!(f, "Shr"),
+            Self::Sar => write!(f, "Sar"),
+            Self::Rol => write!(f, "Rol"),
+            Self::Ror => write!(f, "Ror"),
+            Self::Rcl => write!(f, "Rcl"),
+            Self::Rcr => write!(f, "Rcr"),
+            Self::Movzx => write!(f, "Movzx"),
+            Self::Movsx => write!(f, "Movsx"),
+            Self::Lea => write!(f, "Lea"),
+            Self::Cmovz => write!(f, "Cmovz"),
+            Self::Cmovnz => write!(f, "Cmovnz"),
+            Self::Cmovle => write!(f, "Cmovle"),
+            Self::Cmovg => write!(f, "Cmovg"),
+            Self::Cmovge => write!(f, "Cmovge"),
+            Self::Cmovb => write!(f, "Cmovb"),
+            Self::Cmovl => write!(f, "Cmovl"),
+            Self::Cmova => write!(f, "Cmova"),
+            Self::Cmovae => write!(f, "Cmovae"),
+            Self::Cmovbe => write!(f, "Cmovbe"),
+            Self::Cmovs => write!(f, "Cmovs"),
+            Self::Cmovns => write!(f, "Cmovns"),
+            Self::Cmovo => write!(f, "Cmovo"),
+            Self::Cmovno => write!(f, "Cmovno"),
+            Self::Cmovp => write!(f, "Cmovp"),
+            Self::Cmovnp => write!(f, "Cmovnp"),
+            Self::Setz => write!(f, "Setz"),
+            Self::Setnz => write!(f, "Setnz"),
+            Self::Setle => write!(f, "Setle"),
+            Self::Setg => write!(f, "Setg"),
+            Self::Setge => write!(f, "Setge"),
+            Self::Setb => write!(f, "Setb"),
+            Self::Setl => write!(f, "Setl"),
+            Self::Seta => write!(f, "Seta"),
+            Self::Setae => write!(f, "Setae"),
+            Self::Setbe => write!(f, "Setbe"),
+            Self::Sets => write!(f, "Sets"),
+            Self::Setns => write!(f, "Setns"),
+            Self::Seto => write!(f, "Seto"),
+            Self::Setno => write!(f, "Setno"),
+            Self::Setp => write!(f, "Setp"),
+            Self::Setnp => write!(f, "Setnp"),
+            Self::Popcnt => write!(f, "Popcnt"),
+            Self::Lzcnt => write!(f, "Lzcnt"),
+            Self::Tzcnt => write!(f, "Tzcnt"),
+            Self::Bswap => write!(f, "Bswap"),
+            Self::Bt => write!(f, "Bt"),
+            Self::Bts => write!(f, "Bts"),
+            Self::Btr => write!(f, "Btr"),
+            Self::Btc => write!(f, "Btc"),
+            Self::Fmov => write!(f, "Fmov"),
+            Self::Fadd => write!(f, "Fadd"),
+            Self::Fsub => write!(f, "Fsub"),
+            Self::Fmul => write!(f, "Fmul"),
+            Self::Fdiv => write!(f, "Fdiv"),
+            Self::Fsqrt => write!(f, "Fsqrt"),
+            Self::Fcmp => write!(f, "Fcmp"),
+            Self::Cvtsi2f => write!(f, "Cvtsi2f"),
+            Self::Cvtf2si => write!(f, "Cvtf2si"),
+            Self::Movdqu => write!(f, "Movdqu"),
+            Self::Movq => write!(f, "Movq"),
+            Self::Paddb => write!(f, "Paddb"),
+            Self::Paddw => write!(f, "Paddw"),
+            Self::Paddd => write!(f, "Paddd"),
+            Self::Paddq => write!(f, "Paddq"),
+            Self::Psubb => write!(f, "Psubb"),
+            Self::Psubw => write!(f, "Psubw"),
+            Self::Psubd => write!(f, "Psubd"),
+            Self::Psubq => write!(f, "Psubq"),
+            Self::Pand => write!(f, "Pand"),
+            Self::Por => write!(f, "Por"),
+            Self::Pxor => write!(f, "Pxor"),
+            Self::Pcmpeqb => write!(f, "Pcmpeqb"),
+            Self::Pcmpeqw => write!(f, "Pcmpeqw"),
+            Self::Pcmpeqd => write!(f, "Pcmpeqd"),
+            Self::Pcmpeqq => write!(f, "Pcmpeqq"),
+            Self::Pshufb => write!(f, "Pshufb"),
+            Self::In => write!(f, "In"),
+            Self::Out => write!(f, "Out"),
             Self::Inc => write!(f, "Inc"),
             Self::Dec => write!(f, "Dec"),
+            Self::Not => write!(f, "Not"),
+            Self::Neg => write!(f, "Neg"),
+            Self::RepMovsb => write!(f, "RepMovsb"),
+            Self::RepStosb => write!(f, "RepStosb"),
+            Self::RepCmpsb => write!(f, "RepCmpsb"),
             Self::Test => write!(f, "Test"),
             Self::Cmp => write!(f, "Cmp"),
             Self::Jmp => write!(f, "Jmp"),
@@ -430,11 +911,26 @@ impl fmt::Display for Mnemonic {
             Self::Jg => write!(f, "Jg"),
             Self::Jge => write!(f, "Jge"),
             Self::Jb => write!(f, "Jb"),
+            Self::Jl => write!(f, "Jl"),
+            Self::Ja => write!(f, "Ja"),
+            Self::Jae => write!(f, "Jae"),
+            Self::Jbe => write!(f, "Jbe"),
+            Self::Js => write!(f, "Js"),
+            Self::Jns => write!(f, "Jns"),
+            Self::Jo => write!(f, "Jo"),
+            Self::Jno => write!(f, "Jno"),
+            Self::Jp => write!(f, "Jp"),
+            Self::Jnp => write!(f, "Jnp"),
+            Self::Push => write!(f, "Push"),
+            Self::Pop => write!(f, "Pop"),
+            Self::Call => write!(f, "Call"),
+            Self::Ret => write!(f, "Ret"),
 
             Self::Db => write!(f, "Db"),
             Self::Dw => write!(f, "Dw"),
             Self::Dd => write!(f, "Dd"),
             Self::Dq => write!(f, "Dq"),
+            Self::Custom(opcode) => write!(f, "Custom({opcode})"),
         }
     }
 }
@@ -445,6 +941,9 @@ impl Mnemonic {
         match &*s.as_ref().to_lowercase() {
             "exit" => Some(Self::Exit),
             "ud" => Some(Self::Ud),
+            "nop" => Some(Self::Nop),
+            "hlt" => Some(Self::Hlt),
+            "cpuid" => Some(Self::Cpuid),
             "mov" => Some(Self::Mov),
             "add" => Some(Self::Add),
             "sub" => Some(Self::Sub),
@@ -453,17 +952,124 @@ impl Mnemonic {
             "xor" => Some(Self::Xor),
             "xchg" => Some(Self::Xchg),
             "imul" => Some(Self::Imul),
+            "adc" => Some(Self::Adc),
+            "sbb" => Some(Self::Sbb),
+            "mul" => Some(Self::Mul),
+            "mulh" => Some(Self::Mulh),
+            "imulh" => Some(Self::Imulh),
+            "div" => Some(Self::Div),
+            "idiv" => Some(Self::Idiv),
+            "rem" => Some(Self::Rem),
+            "irem" => Some(Self::Irem),
+            "shl" | "sal" => Some(Self::Shl),
+            "shr" => Some(Self::Shr),
+            "sar" => Some(Self::Sar),
+            "rol" => Some(Self::Rol),
+            "ror" => Some(Self::Ror),
+            "rcl" => Some(Self::Rcl),
+            "rcr" => Some(Self::Rcr),
+            "movzx" => Some(Self::Movzx),
+            "movsx" | "movsxd" => Some(Self::Movsx),
+            "lea" => Some(Self::Lea),
+            "cmovz" | "cmove" => Some(Self::Cmovz),
+            "cmovnz" | "cmovne" => Some(Self::Cmovnz),
+            "cmovle" | "cmovng" => Some(Self::Cmovle),
+            "cmovg" | "cmovnle" => Some(Self::Cmovg),
+            "cmovge" | "cmovnl" => Some(Self::Cmovge),
+            "cmovb" | "cmovc" | "cmovnae" => Some(Self::Cmovb),
+            "cmovl" | "cmovnge" => Some(Self::Cmovl),
+            "cmova" | "cmovnbe" => Some(Self::Cmova),
+            "cmovae" | "cmovnb" | "cmovnc" => Some(Self::Cmovae),
+            "cmovbe" | "cmovna" => Some(Self::Cmovbe),
+            "cmovs" => Some(Self::Cmovs),
+            "cmovns" => Some(Self::Cmovns),
+            "cmovo" => Some(Self::Cmovo),
+            "cmovno" => Some(Self::Cmovno),
+            "cmovp" | "cmovpe" => Some(Self::Cmovp),
+            "cmovnp" | "cmovpo" => Some(Self::Cmovnp),
+            "setz" | "sete" => Some(Self::Setz),
+            "setnz" | "setne" => Some(Self::Setnz),
+            "setle" | "setng" => Some(Self::Setle),
+            "setg" | "setnle" => Some(Self::Setg),
+            "setge" | "setnl" => Some(Self::Setge),
+            "setb" | "setc" | "setnae" => Some(Self::Setb),
+            "setl" | "setnge" => Some(Self::Setl),
+            "seta" | "setnbe" => Some(Self::Seta),
+            "setae" | "setnb" | "setnc" => Some(Self::Setae),
+            "setbe" | "setna" => Some(Self::Setbe),
+            "sets" => Some(Self::Sets),
+            "setns" => Some(Self::Setns),
+            "seto" => Some(Self::Seto),
+            "setno" => Some(Self::Setno),
+            "setp" | "setpe" => Some(Self::Setp),
+            "setnp" | "setpo" => Some(Self::Setnp),
+            "popcnt" => Some(Self::Popcnt),
+            "lzcnt" => Some(Self::Lzcnt),
+            "tzcnt" => Some(Self::Tzcnt),
+            "bswap" => Some(Self::Bswap),
+            "bt" => Some(Self::Bt),
+            "bts" => Some(Self::Bts),
+            "btr" => Some(Self::Btr),
+            "btc" => Some(Self::Btc),
+            "fmov" => Some(Self::Fmov),
+            "fadd" => Some(Self::Fadd),
+            "fsub" => Some(Self::Fsub),
+            "fmul" => Some(Self::Fmul),
+            "fdiv" => Some(Self::Fdiv),
+            "fsqrt" => Some(Self::Fsqrt),
+            "fcmp" => Some(Self::Fcmp),
+            "cvtsi2f" | "cvtsi2sd" => Some(Self::Cvtsi2f),
+            "cvtf2si" | "cvttsd2si" => Some(Self::Cvtf2si),
+            "movdqu" => Some(Self::Movdqu),
+            "movq" => Some(Self::Movq),
+            "paddb" => Some(Self::Paddb),
+            "paddw" => Some(Self::Paddw),
+            "paddd" => Some(Self::Paddd),
+            "paddq" => Some(Self::Paddq),
+            "psubb" => Some(Self::Psubb),
+            "psubw" => Some(Self::Psubw),
+            "psubd" => Some(Self::Psubd),
+            "psubq" => Some(Self::Psubq),
+            "pand" => Some(Self::Pand),
+            "por" => Some(Self::Por),
+            "pxor" => Some(Self::Pxor),
+            "pcmpeqb" => Some(Self::Pcmpeqb),
+            "pcmpeqw" => Some(Self::Pcmpeqw),
+            "pcmpeqd" => Some(Self::Pcmpeqd),
+            "pcmpeqq" => Some(Self::Pcmpeqq),
+            "pshufb" => Some(Self::Pshufb),
+            "in" => Some(Self::In),
+            "out" => Some(Self::Out),
             "inc" => Some(Self::Inc),
             "dec" => Some(Self::Dec),
+            "not" => Some(Self::Not),
+            "neg" => Some(Self::Neg),
+            "rep movsb" => Some(Self::RepMovsb),
+            "rep stosb" => Some(Self::RepStosb),
+            "rep cmpsb" | "repe cmpsb" | "repz cmpsb" => Some(Self::RepCmpsb),
             "test" => Some(Self::Test),
             "cmp" => Some(Self::Cmp),
             "jmp" => Some(Self::Jmp),
-            "jz" => Some(Self::Jz),
-            "jnz" => Some(Self::Jnz),
-            "jle" => Some(Self::Jle),
-            "jg" => Some(Self::Jg),
-            "jge" => Some(Self::Jge),
-            "jb" => Some(Self::Jb),
+            "jz" | "je" => Some(Self::Jz),
+            "jnz" | "jne" => Some(Self::Jnz),
+            "jle" | "jng" => Some(Self::Jle),
+            "jg" | "jnle" => Some(Self::Jg),
+            "jge" | "jnl" => Some(Self::Jge),
+            "jb" | "jc" | "jnae" => Some(Self::Jb),
+            "jl" | "jnge" => Some(Self::Jl),
+            "ja" | "jnbe" => Some(Self::Ja),
+            "jae" | "jnb" | "jnc" => Some(Self::Jae),
+            "jbe" | "jna" => Some(Self::Jbe),
+            "js" => Some(Self::Js),
+            "jns" => Some(Self::Jns),
+            "jo" => Some(Self::Jo),
+            "jno" => Some(Self::Jno),
+            "jp" | "jpe" => Some(Self::Jp),
+            "jnp" | "jpo" => Some(Self::Jnp),
+            "push" => Some(Self::Push),
+            "pop" => Some(Self::Pop),
+            "call" => Some(Self::Call),
+            "ret" => Some(Self::Ret),
 
             "db" => Some(Self::Db),
             "dw" => Some(Self::Dw),
@@ -479,11 +1085,39 @@ impl Mnemonic {
         matches!(self, Self::Db | Self::Dw | Self::Dd | Self::Dq)
     }
 
+    /// Returns whether the mnemonic takes a branch target as an operand
+    pub fn is_branch(&self) -> bool {
+        matches!(
+            self,
+            Self::Jmp
+                | Self::Jz
+                | Self::Jnz
+                | Self::Jle
+                | Self::Jg
+                | Self::Jge
+                | Self::Jb
+                | Self::Jl
+                | Self::Ja
+                | Self::Jae
+                | Self::Jbe
+                | Self::Js
+                | Self::Jns
+                | Self::Jo
+                | Self::Jno
+                | Self::Jp
+                | Self::Jnp
+                | Self::Call
+        )
+    }
+
     /// Returns the minimum number of operands that this mnemonic supports
     pub fn min_operands(&self) -> usize {
         match self {
             Self::Exit => 0,
             Self::Ud => 0,
+            Self::Nop => 0,
+            Self::Hlt => 0,
+            Self::Cpuid => 0,
             Self::Mov => 2,
             Self::Add => 2,
             Self::Sub => 2,
@@ -492,8 +1126,101 @@ impl Mnemonic {
             Self::Xor => 2,
             Self::Xchg => 2,
             Self::Imul => 2,
+            Self::Adc => 2,
+            Self::Sbb => 2,
+            Self::Mul => 2,
+            Self::Mulh => 2,
+            Self::Imulh => 2,
+            Self::Div => 2,
+            Self::Idiv => 2,
+            Self::Rem => 2,
+            Self::Irem => 2,
+            Self::Shl => 2,
+            Self::Shr => 2,
+            Self::Sar => 2,
+            Self::Rol => 2,
+            Self::Ror => 2,
+            Self::Rcl => 2,
+            Self::Rcr => 2,
+            Self::Movzx => 2,
+            Self::Movsx => 2,
+            Self::Lea => 2,
+            Self::Cmovz => 2,
+            Self::Cmovnz => 2,
+            Self::Cmovle => 2,
+            Self::Cmovg => 2,
+            Self::Cmovge => 2,
+            Self::Cmovb => 2,
+            Self::Cmovl => 2,
+            Self::Cmova => 2,
+            Self::Cmovae => 2,
+            Self::Cmovbe => 2,
+            Self::Cmovs => 2,
+            Self::Cmovns => 2,
+            Self::Cmovo => 2,
+            Self::Cmovno => 2,
+            Self::Cmovp => 2,
+            Self::Cmovnp => 2,
+            Self::Setz => 1,
+            Self::Setnz => 1,
+            Self::Setle => 1,
+            Self::Setg => 1,
+            Self::Setge => 1,
+            Self::Setb => 1,
+            Self::Setl => 1,
+            Self::Seta => 1,
+            Self::Setae => 1,
+            Self::Setbe => 1,
+            Self::Sets => 1,
+            Self::Setns => 1,
+            Self::Seto => 1,
+            Self::Setno => 1,
+            Self::Setp => 1,
+            Self::Setnp => 1,
+            Self::Popcnt => 2,
+            Self::Lzcnt => 2,
+            Self::Tzcnt => 2,
+            Self::Bswap => 1,
+            Self::Bt => 2,
+            Self::Bts => 2,
+            Self::Btr => 2,
+            Self::Btc => 2,
+            Self::Fmov => 2,
+            Self::Fadd => 2,
+            Self::Fsub => 2,
+            Self::Fmul => 2,
+            Self::Fdiv => 2,
+            Self::Fsqrt => 2,
+            Self::Fcmp => 2,
+            Self::Cvtsi2f => 2,
+            Self::Cvtf2si => 2,
+            Self::Movdqu => 2,
+            Self::Movq => 2,
+            Self::Paddb => 2,
+            Self::Paddw => 2,
+            Self::Paddd => 2,
+            Self::Paddq => 2,
+            Self::Psubb => 2,
+            Self::Psubw => 2,
+            Self::Psubd => 2,
+            Self::Psubq => 2,
+            Self::Pand => 2,
+            Self::Por => 2,
+            Self::Pxor => 2,
+            Self::Pcmpeqb => 2,
+            Self::Pcmpeqw => 2,
+            Self::Pcmpeqd => 2,
+            Self::Pcmpeqq => 2,
+            Self::Pshufb => 2,
+            Self::In => 2,
+            Self::Out => 2,
             Self::Inc => 1,
             Self::Dec => 1,
+            Self::Not => 1,
+            Self::Neg => 1,
+            Self::RepMovsb => 0,
+            Self::RepStosb => 0,
+            Self::RepCmpsb => 0,
             Self::Test => 2,
             Self::Cmp => 2,
             Self::Jmp => 1,
@@ -503,11 +1230,26 @@ impl Mnemonic {
             Self::Jg => 1,
             Self::Jge => 1,
             Self::Jb => 1,
+            Self::Jl => 1,
+            Self::Ja => 1,
+            Self::Jae => 1,
+            Self::Jbe => 1,
+            Self::Js => 1,
+            Self::Jns => 1,
+            Self::Jo => 1,
+            Self::Jno => 1,
+            Self::Jp => 1,
+            Self::Jnp => 1,
+            Self::Push => 1,
+            Self::Pop => 1,
+            Self::Call => 1,
+            Self::Ret => 0,
 
             Self::Db => 1,
             Self::Dw => 1,
             Self::Dd => 1,
             Self::Dq => 1,
+            Self::Custom(_) => 0,
         }
     }
 
@@ -516,16 +1258,112 @@ impl Mnemonic {
         match self {
             Self::Exit => 0,
             Self::Ud => 0,
+            Self::Nop => 0,
+            Self::Hlt => 0,
+            Self::Cpuid => 0,
             Self::Mov => 2,
-            Self::Add => 2,
-            Self::Sub => 2,
-            Self::And => 2,
-            Self::Or => 2,
-            Self::Xor => 2,
+            Self::Add => 3,
+            Self::Sub => 3,
+            Self::And => 3,
+            Self::Or => 3,
+            Self::Xor => 3,
             Self::Xchg => 2,
-            Self::Imul => 2,
+            Self::Imul => 3,
+            Self::Adc => 3,
+            Self::Sbb => 3,
+            Self::Mul => 2,
+            Self::Mulh => 3,
+            Self::Imulh => 3,
+            Self::Div => 3,
+            Self::Idiv => 3,
+            Self::Rem => 3,
+            Self::Irem => 3,
+            Self::Shl => 3,
+            Self::Shr => 3,
+            Self::Sar => 3,
+            Self::Rol => 3,
+            Self::Ror => 3,
+            Self::Rcl => 3,
+            Self::Rcr => 3,
+            Self::Movzx => 2,
+            Self::Movsx => 2,
+            Self::Lea => 2,
+            Self::Cmovz => 2,
+            Self::Cmovnz => 2,
+            Self::Cmovle => 2,
+            Self::Cmovg => 2,
+            Self::Cmovge => 2,
+            Self::Cmovb => 2,
+            Self::Cmovl => 2,
+            Self::Cmova => 2,
+            Self::Cmovae => 2,
+            Self::Cmovbe => 2,
+            Self::Cmovs => 2,
+            Self::Cmovns => 2,
+            Self::Cmovo => 2,
+            Self::Cmovno => 2,
+            Self::Cmovp => 2,
+            Self::Cmovnp => 2,
+            Self::Setz => 1,
+            Self::Setnz => 1,
+            Self::Setle => 1,
+            Self::Setg => 1,
+            Self::Setge => 1,
+            Self::Setb => 1,
+            Self::Setl => 1,
+            Self::Seta => 1,
+            Self::Setae => 1,
+            Self::Setbe => 1,
+            Self::Sets => 1,
+            Self::Setns => 1,
+            Self::Seto => 1,
+            Self::Setno => 1,
+            Self::Setp => 1,
+            Self::Setnp => 1,
+            Self::Popcnt => 2,
+            Self::Lzcnt => 2,
+            Self::Tzcnt => 2,
+            Self::Bswap => 1,
+            Self::Bt => 2,
+            Self::Bts => 2,
+            Self::Btr => 2,
+            Self::Btc => 2,
+            Self::Fmov => 2,
+            Self::Fadd => 2,
+            Self::Fsub => 2,
+            Self::Fmul => 2,
+            Self::Fdiv => 2,
+            Self::Fsqrt => 2,
+            Self::Fcmp => 2,
+            Self::Cvtsi2f => 2,
+            Self::Cvtf2si => 2,
+            Self::Movdqu => 2,
+            Self::Movq => 2,
+            Self::Paddb => 2,
+            Self::Paddw => 2,
+            Self::Paddd => 2,
+            Self::Paddq => 2,
+            Self::Psubb => 2,
+            Self::Psubw => 2,
+            Self::Psubd => 2,
+            Self::Psubq => 2,
+            Self::Pand => 2,
+            Self::Por => 2,
+            Self::Pxor => 2,
+            Self::Pcmpeqb => 2,
+            Self::Pcmpeqw => 2,
+            Self::Pcmpeqd => 2,
+            Self::Pcmpeqq => 2,
+            Self::Pshufb => 2,
+            Self::In => 2,
+            Self::Out => 2,
             Self::Inc => 1,
             Self::Dec => 1,
+            Self::Not => 1,
+            Self::Neg => 1,
+            Self::RepMovsb => 0,
+            Self::RepStosb => 0,
+            Self::RepCmpsb => 0,
             Self::Test => 2,
             Self::Cmp => 2,
             Self::Jmp => 1,
@@ -535,17 +1373,36 @@ impl Mnemonic {
             Self::Jg => 1,
             Self::Jge => 1,
             Self::Jb => 1,
+            Self::Jl => 1,
+            Self::Ja => 1,
+            Self::Jae => 1,
+            Self::Jbe => 1,
+            Self::Js => 1,
+            Self::Jns => 1,
+            Self::Jo => 1,
+            Self::Jno => 1,
+            Self::Jp => 1,
+            Self::Jnp => 1,
+            Self::Push => 1,
+            Self::Pop => 1,
+            Self::Call => 1,
+            Self::Ret => 0,
 
             Self::Db => 1,
             Self::Dw => 1,
             Self::Dd => 1,
             Self::Dq => 1,
+            Self::Custom(_) => 3,
         }
     }
 }
 
 /// Represents the raw operation codes for each [`Mnemonic`]
-#[repr(u8)]
+///
+/// Opcodes of the first page are below [`ESCAPE`] and encoded in a single
+/// byte. Opcodes of the second page start at `FF00h` and are encoded with the
+/// [`ESCAPE`] prefix.
+#[repr(u16)]
 #[derive(FromRepr, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OpCode {
     Exit,
@@ -580,6 +1437,302 @@ pub enum OpCode {
     Jg,
     Jge,
     Jb,
+    PushR,
+    PushIMM,
+    PopR,
+    Call,
+    Ret,
+    Jl,
+    Ja,
+    Jae,
+    Jbe,
+    Js,
+    Jns,
+    Jo,
+    Jno,
+    Jp,
+    Jnp,
+    DivRIMM,
+    DivRR,
+    IdivRIMM,
+    IdivRR,
+    RemRIMM,
+    RemRR,
+    IremRIMM,
+    IremRR,
+    ShlRIMM,
+    ShlRR,
+    ShrRIMM,
+    ShrRR,
+    SarRIMM,
+    SarRR,
+    RolRIMM,
+    RolRR,
+    RorRIMM,
+    RorRR,
+    RclRIMM,
+    RclRR,
+    RcrRIMM,
+    RcrRR,
+    AddRRM,
+    AddRMR,
+    AddRMIMM,
+    SubRRM,
+    SubRMR,
+    SubRMIMM,
+    AndRRM,
+    AndRMR,
+    AndRMIMM,
+    OrRRM,
+    OrRMR,
+    OrRMIMM,
+    XorRRM,
+    XorRMR,
+    XorRMIMM,
+    XchgRRM,
+    XchgRMR,
+    ImulRRM,
+    IncRM,
+    DecRM,
+    CmpRRM,
+    CmpRMR,
+    CmpRMIMM,
+    TestRRM,
+    TestRMR,
+    TestRMIMM,
+    MovRMIMM,
+    MovzxRRM,
+    MovsxRRM,
+    MovzxRR8,
+    MovzxRR16,
+    MovzxRR32,
+    MovsxRR8,
+    MovsxRR16,
+    MovsxRR32,
+    LeaRRM,
+    CmovzRR,
+    CmovnzRR,
+    CmovleRR,
+    CmovgRR,
+    CmovgeRR,
+    CmovbRR,
+    CmovlRR,
+    CmovaRR,
+    CmovaeRR,
+    CmovbeRR,
+    CmovsRR,
+    CmovnsRR,
+    CmovoRR,
+    CmovnoRR,
+    CmovpRR,
+    CmovnpRR,
+    SetzR,
+    SetnzR,
+    SetleR,
+    SetgR,
+    SetgeR,
+    SetbR,
+    SetlR,
+    SetaR,
+    SetaeR,
+    SetbeR,
+    SetsR,
+    SetnsR,
+    SetoR,
+    SetnoR,
+    SetpR,
+    SetnpR,
+    JmpR,
+    JmpRM,
+    CallR,
+    CallRM,
+    PopcntRR,
+    PopcntRRM,
+    LzcntRR,
+    LzcntRRM,
+    TzcntRR,
+    TzcntRRM,
+    BswapR,
+    BtRR,
+    BtRIMM,
+    BtRMR,
+    BtRMIMM,
+    BtsRR,
+    BtsRIMM,
+    BtsRMR,
+    BtsRMIMM,
+    BtrRR,
+    BtrRIMM,
+    BtrRMR,
+    BtrRMIMM,
+    BtcRR,
+    BtcRIMM,
+    BtcRMR,
+    BtcRMIMM,
+    AdcRR,
+    AdcRIMM,
+    AdcRRM,
+    AdcRMR,
+    AdcRMIMM,
+    SbbRR,
+    SbbRIMM,
+    SbbRRM,
+    SbbRMR,
+    SbbRMIMM,
+    MulRR,
+    MulhRR,
+    MulhRRM,
+    ImulhRR,
+    ImulhRRM,
+    Nop,
+    Hlt,
+    NotR,
+    NotRM,
+    NegR,
+    NegRM,
+    RepMovsb,
+    RepStosb,
+    RepCmpsb,
+    FmovFF,
+    FmovFRM,
+    FmovRMF,
+    FmovFIMM,
+    FmovFR,
+    FmovRF,
+    FaddFF,
+    FsubFF,
+    FmulFF,
+    FdivFF,
+    FsqrtFF,
+    FcmpFF,
+    Cvtsi2fFR,
+    Cvtf2siRF,
+    MovdquVV,
+    MovdquVRM,
+    MovdquRMV,
+    MovqRV,
+    MovqVR,
+    PaddbVV,
+    PaddwVV,
+    PadddVV,
+    PaddqVV,
+    PsubbVV,
+    PsubwVV,
+    PsubdVV,
+    PsubqVV,
+    PandVV,
+    PorVV,
+    PxorVV,
+    PcmpeqbVV,
+    PcmpeqwVV,
+    PcmpeqdVV,
+    PcmpeqqVV,
+    PshufbVV,
+    // The third operand is a register, or FFh followed by an immediate
+    AddRRRI,
+    SubRRRI,
+    AndRRRI,
+    OrRRRI,
+    XorRRRI,
+    ImulRRRI,
+    ShlRRRI,
+    ShrRRRI,
+    SarRRRI,
+    AdcRRRI,
+    SbbRRRI,
+    MulhRRRI,
+    ImulhRRRI,
+    DivRRRI,
+    IdivRRRI,
+    RemRRRI,
+    IremRRRI,
+    RolRRRI,
+    RorRRRI,
+    RclRRRI,
+    RcrRRRI,
+    InRIMM,
+    InRR,
+    OutIMMR,
+    OutRR,
+    // The second page
+    Cpuid = 0xFF00,
+    Custom,
+}
+
+impl OpCode {
+    /// Returns the [`Extension`] this opcode belongs to, or `None` if it is
+    /// part of the base ISA
+    pub fn extension(&self) -> Option<Extension> {
+        match self {
+            Self::FmovFF
+            | Self::FmovFRM
+            | Self::FmovRMF
+            | Self::FmovFIMM
+            | Self::FmovFR
+            | Self::FmovRF
+            | Self::FaddFF
+            | Self::FsubFF
+            | Self::FmulFF
+            | Self::FdivFF
+            | Self::FsqrtFF
+            | Self::FcmpFF
+            | Self::Cvtsi2fFR
+            | Self::Cvtf2siRF => Some(Extension::Fpu),
+            Self::MovdquVV
+            | Self::MovdquVRM
+            | Self::MovdquRMV
+            | Self::MovqRV
+            | Self::MovqVR
+            | Self::PaddbVV
+            | Self::PaddwVV
+            | Self::PadddVV
+            | Self::PaddqVV
+            | Self::PsubbVV
+            | Self::PsubwVV
+            | Self::PsubdVV
+            | Self::PsubqVV
+            | Self::PandVV
+            | Self::PorVV
+            | Self::PxorVV
+            | Self::PcmpeqbVV
+            | Self::PcmpeqwVV
+            | Self::PcmpeqdVV
+            | Self::PcmpeqqVV
+            | Self::PshufbVV => Some(Extension::Simd),
+            Self::InRIMM | Self::InRR | Self::OutIMMR | Self::OutRR => Some(Extension::PortIo),
+            Self::Custom => Some(Extension::Custom),
+            _ => None,
+        }
+    }
+
+    /// Returns whether an immediate third operand is marked by FFh in place of
+    /// a register, e.g. `add r0, r1, 5`
+    fn marks_immediate(&self) -> bool {
+        matches!(
+            self,
+            Self::AddRRRI
+                | Self::SubRRRI
+                | Self::AndRRRI
+                | Self::OrRRRI
+                | Self::XorRRRI
+                | Self::ImulRRRI
+                | Self::ShlRRRI
+                | Self::ShrRRRI
+                | Self::SarRRRI
+                | Self::AdcRRRI
+                | Self::SbbRRRI
+                | Self::MulhRRRI
+                | Self::ImulhRRRI
+                | Self::DivRRRI
+                | Self::IdivRRRI
+                | Self::RemRRRI
+                | Self::IremRRRI
+                | Self::RolRRRI
+                | Self::RorRRRI
+                | Self::RclRRRI
+                | Self::RcrRRRI
+        )
+    }
 }
 
 impl fmt::Display for OpCode {
@@ -617,6 +1770,303 @@ impl fmt::Display for OpCode {
             Self::Jg => write!(f, "Jg"),
             Self::Jge => write!(f, "Jge"),
             Self::Jb => write!(f, "Jb"),
+            Self::PushR => write!(f, "PushR"),
+            Self::PushIMM => write!(f, "PushIMM"),
+            Self::PopR => write!(f, "PopR"),
+            Self::Call => write!(f, "Call"),
+            Self::Ret => write!(f, "Ret"),
+            Self::Jl => write!(f, "Jl"),
+            Self::Ja => write!(f, "Ja"),
+            Self::Jae => write!(f, "Jae"),
+            Self::Jbe => write!(f, "Jbe"),
+            Self::Js => write!(f, "Js"),
+            Self::Jns => write!(f, "Jns"),
+            Self::Jo => write!(f, "Jo"),
+            Self::Jno => write!(f, "Jno"),
+            Self::Jp => write!(f, "Jp"),
+            Self::Jnp => write!(f, "Jnp"),
+            Self::DivRIMM => write!(f, "DivRIMM"),
+            Self::DivRR => write!(f, "DivRR"),
+            Self::IdivRIMM => write!(f, "IdivRIMM"),
+            Self::IdivRR => write!(f, "IdivRR"),
+            Self::RemRIMM => write!(f, "RemRIMM"),
+            Self::RemRR => write!(f, "RemRR"),
+            Self::IremRIMM => write!(f, "IremRIMM"),
+            Self::IremRR => write!(f, "IremRR"),
+            Self::ShlRIMM => write!(f, "ShlRIMM"),
+            Self::ShlRR => write!(f, "ShlRR"),
+            Self::ShrRIMM => write!(f, "ShrRIMM"),
+            Self::ShrRR => write!(f, "ShrRR"),
+            Self::SarRIMM => write!(f, "SarRIMM"),
+            Self::SarRR => write!(f, "SarRR"),
+            Self::RolRIMM => write!(f, "RolRIMM"),
+            Self::RolRR => write!(f, "RolRR"),
+            Self::RorRIMM => write!(f, "RorRIMM"),
+            Self::RorRR => write!(f, "RorRR"),
+            Self::RclRIMM => write!(f, "RclRIMM"),
+            Self::RclRR => write!(f, "RclRR"),
+            Self::RcrRIMM => write!(f, "RcrRIMM"),
+            Self::RcrRR => write!(f, "RcrRR"),
+            Self::AddRRM => write!(f, "AddRRM"),
+            Self::AddRMR => write!(f, "AddRMR"),
+            Self::AddRMIMM => write!(f, "AddRMIMM"),
+            Self::SubRRM => write!(f, "SubRRM"),
+            Self::SubRMR => write!(f, "SubRMR"),
+            Self::SubRMIMM => write!(f, "SubRMIMM"),
+            Self::AndRRM => write!(f, "AndRRM"),
+            Self::AndRMR => write!(f, "AndRMR"),
+            Self::AndRMIMM => write!(f, "AndRMIMM"),
+            Self::OrRRM => write!(f, "OrRRM"),
+            Self::OrRMR => write!(f, "OrRMR"),
+            Self::OrRMIMM => write!(f, "OrRMIMM"),
+            Self::XorRRM => write!(f, "XorRRM"),
+            Self::XorRMR => write!(f, "XorRMR"),
+            Self::XorRMIMM => write!(f, "XorRMIMM"),
+            Self::XchgRRM => write!(f, "XchgRRM"),
+            Self::XchgRMR => write!(f, "XchgRMR"),
+            Self::ImulRRM => write!(f, "ImulRRM"),
+            Self::IncRM => write!(f, "IncRM"),
+            Self::DecRM => write!(f, "DecRM"),
+            Self::CmpRRM => write!(f, "CmpRRM"),
+            Self::CmpRMR => write!(f, "CmpRMR"),
+            Self::CmpRMIMM => write!(f, "CmpRMIMM"),
+            Self::TestRRM => write!(f, "TestRRM"),
+            Self::TestRMR => write!(f, "TestRMR"),
+            Self::TestRMIMM => write!(f, "TestRMIMM"),
+            Self::MovRMIMM => write!(f, "MovRMIMM"),
+            Self::MovzxRRM => write!(f, "MovzxRRM"),
+            Self::MovsxRRM => write!(f, "MovsxRRM"),
+            Self::MovzxRR8 => write!(f, "MovzxRR8"),
+            Self::MovzxRR16 => write!(f, "MovzxRR16"),
+            Self::MovzxRR32 => write!(f, "MovzxRR32"),
+            Self::MovsxRR8 => write!(f, "MovsxRR8"),
+            Self::MovsxRR16 => write!(f, "MovsxRR16"),
+            Self::MovsxRR32 => write!(f, "MovsxRR32"),
+            Self::LeaRRM => write!(f, "LeaRRM"),
+            Self::CmovzRR => write!(f, "CmovzRR"),
+            Self::CmovnzRR => write!(f, "CmovnzRR"),
+            Self::CmovleRR => write!(f, "CmovleRR"),
+            Self::CmovgRR => write!(f, "CmovgRR"),
+            Self::CmovgeRR => write!(f, "CmovgeRR"),
+            Self::CmovbRR => write!(f, "CmovbRR"),
+            Self::CmovlRR => write!(f, "CmovlRR"),
+            Self::CmovaRR => write!(f, "CmovaRR"),
+            Self::CmovaeRR => write!(f, "CmovaeRR"),
+            Self::CmovbeRR => write!(f, "CmovbeRR"),
+            Self::CmovsRR => write!(f, "CmovsRR"),
+            Self::CmovnsRR => write!(f, "CmovnsRR"),
+            Self::CmovoRR => write!(f, "CmovoRR"),
+            Self::CmovnoRR => write!(f, "CmovnoRR"),
+            Self::CmovpRR => write!(f, "CmovpRR"),
+            Self::CmovnpRR => write!(f, "CmovnpRR"),
+            Self::SetzR => write!(f, "SetzR"),
+            Self::SetnzR => write!(f, "SetnzR"),
+            Self::SetleR => write!(f, "SetleR"),
+            Self::SetgR => write!(f, "SetgR"),
+            Self::SetgeR => write!(f, "SetgeR"),
+            Self::SetbR => write!(f, "SetbR"),
+            Self::SetlR => write!(f, "SetlR"),
+            Self::SetaR => write!(f, "SetaR"),
+            Self::SetaeR => write!(f, "SetaeR"),
+            Self::SetbeR => write!(f, "SetbeR"),
+            Self::SetsR => write!(f, "SetsR"),
+            Self::SetnsR => write!(f, "SetnsR"),
+            Self::SetoR => write!(f, "SetoR"),
+            Self::SetnoR => write!(f, "SetnoR"),
+            Self::SetpR => write!(f, "SetpR"),
+            Self::SetnpR => write!(f, "SetnpR"),
+            Self::JmpR => write!(f, "JmpR"),
+            Self::JmpRM => write!(f, "JmpRM"),
+            Self::CallR => write!(f, "CallR"),
+            Self::CallRM => write!(f, "CallRM"),
+            Self::PopcntRR => write!(f, "PopcntRR"),
+            Self::PopcntRRM => write!(f, "PopcntRRM"),
+            Self::LzcntRR => write!(f, "LzcntRR"),
+            Self::LzcntRRM => write!(f, "LzcntRRM"),
+            Self::TzcntRR => write!(f, "TzcntRR"),
+            Self::TzcntRRM => write!(f, "TzcntRRM"),
+            Self::BswapR => write!(f, "BswapR"),
+            Self::BtRR => write!(f, "BtRR"),
+            Self::BtRIMM => write!(f, "BtRIMM"),
+            Self::BtRMR => write!(f, "BtRMR"),
+            Self::BtRMIMM => write!(f, "BtRMIMM"),
+            Self::BtsRR => write!(f, "BtsRR"),
+            Self::BtsRIMM => write!(f, "BtsRIMM"),
+            Self::BtsRMR => write!(f, "BtsRMR"),
+            Self::BtsRMIMM => write!(f, "BtsRMIMM"),
+            Self::BtrRR => write!(f, "BtrRR"),
+            Self::BtrRIMM => write!(f, "BtrRIMM"),
+            Self::BtrRMR => write!(f, "BtrRMR"),
+            Self::BtrRMIMM => write!(f, "BtrRMIMM"),
+            Self::BtcRR => write!(f, "BtcRR"),
+            Self::BtcRIMM => write!(f, "BtcRIMM"),
+            Self::BtcRMR => write!(f, "BtcRMR"),
+            Self::BtcRMIMM => write!(f, "BtcRMIMM"),
+            Self::AdcRR => write!(f, "AdcRR"),
+            Self::AdcRIMM => write!(f, "AdcRIMM"),
+            Self::AdcRRM => write!(f, "AdcRRM"),
+            Self::AdcRMR => write!(f, "AdcRMR"),
+            Self::AdcRMIMM => write!(f, "AdcRMIMM"),
+            Self::SbbRR => write!(f, "SbbRR"),
+            Self::SbbRIMM => write!(f, "SbbRIMM"),
+            Self::SbbRRM => write!(f, "SbbRRM"),
+            Self::SbbRMR => write!(f, "SbbRMR"),
+            Self::SbbRMIMM => write!(f, "SbbRMIMM"),
+            Self::MulRR => write!(f, "MulRR"),
+            Self::MulhRR => write!(f, "MulhRR"),
+            Self::MulhRRM => write!(f, "MulhRRM"),
+            Self::ImulhRR => write!(f, "ImulhRR"),
+            Self::ImulhRRM => write!(f, "ImulhRRM"),
+            Self::Nop => write!(f, "Nop"),
+            Self::Hlt => write!(f, "Hlt"),
+            Self::Cpuid => write!(f, "Cpuid"),
+            Self::Custom => write!(f, "Custom"),
+            Self::NotR => write!(f, "NotR"),
+            Self::NotRM => write!(f, "NotRM"),
+            Self::NegR => write!(f, "NegR"),
+            Self::NegRM => write!(f, "NegRM"),
+            Self::RepMovsb => write!(f, "RepMovsb"),
+            Self::RepStosb => write!(f, "RepStosb"),
+            Self::RepCmpsb => write!(f, "RepCmpsb"),
+            Self::FmovFF => write!(f, "FmovFF"),
+            Self::FmovFRM => write!(f, "FmovFRM"),
+            Self::FmovRMF => write!(f, "FmovRMF"),
+            Self::FmovFIMM => write!(f, "FmovFIMM"),
+            Self::FmovFR => write!(f, "FmovFR"),
+            Self::FmovRF => write!(f, "FmovRF"),
+            Self::FaddFF => write!(f, "FaddFF"),
+            Self::FsubFF => write!(f, "FsubFF"),
+            Self::FmulFF => write!(f, "FmulFF"),
+            Self::FdivFF => write!(f, "FdivFF"),
+            Self::FsqrtFF => write!(f, "FsqrtFF"),
+            Self::FcmpFF => write!(f, "FcmpFF"),
+            Self::Cvtsi2fFR => write!(f, "Cvtsi2fFR"),
+            Self::Cvtf2siRF => write!(f, "Cvtf2siRF"),
+            Self::MovdquVV => write!(f, "MovdquVV"),
+            Self::MovdquVRM => write!(f, "MovdquVRM"),
+            Self::MovdquRMV => write!(f, "MovdquRMV"),
+            Self::MovqRV => write!(f, "MovqRV"),
+            Self::MovqVR => write!(f, "MovqVR"),
+            Self::PaddbVV => write!(f, "PaddbVV"),
+            Self::PaddwVV => write!(f, "PaddwVV"),
+            Self::PadddVV => write!(f, "PadddVV"),
+            Self::PaddqVV => write!(f, "PaddqVV"),
+            Self::PsubbVV => write!(f, "PsubbVV"),
+            Self::PsubwVV => write!(f, "PsubwVV"),
+            Self::PsubdVV => write!(f, "PsubdVV"),
+            Self::PsubqVV => write!(f, "PsubqVV"),
+            Self::PandVV => write!(f, "PandVV"),
+            Self::PorVV => write!(f, "PorVV"),
+            Self::PxorVV => write!(f, "PxorVV"),
+            Self::PcmpeqbVV => write!(f, "PcmpeqbVV"),
+            Self::PcmpeqwVV => write!(f, "PcmpeqwVV"),
+            Self::PcmpeqdVV => write!(f, "PcmpeqdVV"),
+            Self::PcmpeqqVV => write!(f, "PcmpeqqVV"),
+            Self::PshufbVV => write!(f, "PshufbVV"),
+            Self::AddRRRI => write!(f, "AddRRRI"),
+            Self::SubRRRI => write!(f, "SubRRRI"),
+            Self::AndRRRI => write!(f, "AndRRRI"),
+            Self::OrRRRI => write!(f, "OrRRRI"),
+            Self::XorRRRI => write!(f, "XorRRRI"),
+            Self::ImulRRRI => write!(f, "ImulRRRI"),
+            Self::ShlRRRI => write!(f, "ShlRRRI"),
+            Self::ShrRRRI => write!(f, "ShrRRRI"),
+            Self::SarRRRI => write!(f, "SarRRRI"),
+            Self::AdcRRRI => write!(f, "AdcRRRI"),
+            Self::SbbRRRI => write!(f, "SbbRRRI"),
+            Self::MulhRRRI => write!(f, "MulhRRRI"),
+            Self::ImulhRRRI => write!(f, "ImulhRRRI"),
+            Self::DivRRRI => write!(f, "DivRRRI"),
+            Self::IdivRRRI => write!(f, "IdivRRRI"),
+            Self::RemRRRI => write!(f, "RemRRRI"),
+            Self::IremRRRI => write!(f, "IremRRRI"),
+            Self::RolRRRI => write!(f, "RolRRRI"),
+            Self::RorRRRI => write!(f, "RorRRRI"),
+            Self::RclRRRI => write!(f, "RclRRRI"),
+            Self::RcrRRRI => write!(f, "RcrRRRI"),
+            Self::InRIMM => write!(f, "InRIMM"),
+            Self::InRR => write!(f, "InRR"),
+            Self::OutIMMR => write!(f, "OutIMMR"),
+            Self::OutRR => write!(f, "OutRR"),
         }
     }
 }
+
+/// Represents an optional extension of the ISA.
+///
+/// `cpuid` reports the enabled extensions as a bitmask of [`Extension::bit`],
+/// and the [`Emulator`] raises an [`Exception::IllegalInstruction`] on the
+/// opcodes of a disabled one.
+///
+/// [`Emulator`]: crate::emulator::Emulator
+/// [`Exception::IllegalInstruction`]: crate::exception::Exception::IllegalInstruction
+#[repr(u8)]
+#[derive(FromRepr, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Extension {
+    /// The floating-point instructions, see [`crate::fpu`].
+    Fpu,
+    /// The vector instructions, see [`crate::simd`].
+    Simd,
+    /// The port-mapped I/O instructions, see [`crate::port`].
+    PortIo,
+    /// The custom instructions, see [`crate::custom`].
+    Custom,
+}
+
+impl fmt::Display for Extension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fpu => write!(f, "Fpu"),
+            Self::Simd => write!(f, "Simd"),
+            Self::PortIo => write!(f, "PortIo"),
+            Self::Custom => write!(f, "Custom"),
+        }
+    }
+}
+
+impl Extension {
+    /// Returns the bit of this extension in [`Extensions`]
+    #[must_use]
+    pub fn bit(&self) -> u64 {
+        1 << *self as u8
+    }
+}
+
+/// Represents a set of [`Extension`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extensions(pub u64);
+
+impl Default for Extensions {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl Extensions {
+    /// Returns the set of all the extensions
+    #[must_use]
+    pub fn all() -> Self {
+        Self((1 << NUM_EXTENSIONS) - 1)
+    }
+
+    /// Returns the empty set, i.e. the base ISA only
+    #[must_use]
+    pub fn none() -> Self {
+        Self(0)
+    }
+
+    /// Returns `true` if `ext` is in this set
+    pub fn contains(&self, ext: Extension) -> bool {
+        self.0 & ext.bit() != 0
+    }
+
+    /// Adds `ext` to this set
+    pub fn insert(&mut self, ext: Extension) {
+        self.0 |= ext.bit();
+    }
+
+    /// Removes `ext` from this set
+    pub fn remove(&mut self, ext: Extension) {
+        self.0 &= !ext.bit();
+    }
+}
@@ -1,6 +1,10 @@
 pub mod alu;
+pub mod custom;
 pub mod emulator;
 pub mod error;
 pub mod exception;
+pub mod fpu;
 pub mod isa;
+pub mod port;
 pub mod ram;
+pub mod simd;
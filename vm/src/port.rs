@@ -0,0 +1,163 @@
+//! This module implements a port bus for port-mapped I/O.
+//!
+//! Ports form a 16-bit address space separate from the [`Dram`], so that guest
+//! programs can talk to peripherals of the host without reserving a range of
+//! memory for them. The host maps a [`PortDevice`] at each port it serves, and
+//! the `in` and `out` instructions are dispatched to the device mapped at the
+//! accessed port.
+//!
+//! ## Operators
+//! - `in`: Reads a value from a port into a register. The port is an immediate
+//!   or the low 16 bits of a register.
+//! - `out`: Writes a register to a port. The port is an immediate or the low 16
+//!   bits of a register.
+//!
+//! The width of the access is the width of the register operand, so `out 0E9h,
+//! r0b` writes a single byte. Like an ISA bus without a device, reading an
+//! unmapped port returns all ones and writing it is a no-op. An error returned
+//! by a device is raised as is, and neither operator updates the flags.
+//!
+//! [`Dram`]: crate::ram::Dram
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{
+    alu::{define_handler_trait, filter_special_reg},
+    emulator::Emulator,
+    exception::Exception,
+    isa::{Instruction, OperandSize},
+};
+
+define_handler_trait!(InRIMM, handle_in_r_imm);
+define_handler_trait!(InRR, handle_in_r_r);
+define_handler_trait!(OutIMMR, handle_out_imm_r);
+define_handler_trait!(OutRR, handle_out_r_r);
+
+/// Represents a peripheral of the host that serves one or more ports.
+pub trait PortDevice: Send {
+    /// Reads a `size`-wide value from `port` for an `in` instruction.
+    ///
+    /// The value is truncated to `size` before it is written to the register.
+    fn read(&mut self, port: u16, size: OperandSize) -> Result<u64, Exception>;
+
+    /// Writes a `size`-wide `value` to `port` for an `out` instruction.
+    fn write(&mut self, port: u16, size: OperandSize, value: u64) -> Result<(), Exception>;
+}
+
+/// A device shared between the [`PortBus`] and the host.
+pub type SharedPortDevice = Arc<Mutex<dyn PortDevice>>;
+
+/// The port bus, which dispatches port accesses to the mapped devices.
+///
+/// Cloning the bus, e.g. along with the [`Emulator`], shares the devices.
+#[derive(Clone, Default)]
+pub struct PortBus(BTreeMap<u16, SharedPortDevice>);
+
+impl fmt::Debug for PortBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+impl PortBus {
+    /// Make an new instance of [`PortBus`] without any device
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps `device` at `port`, replacing the device previously mapped there.
+    ///
+    /// The same device may be mapped at several ports. The host keeps a clone
+    /// of the [`Arc`] to observe the device.
+    pub fn map(&mut self, port: u16, device: SharedPortDevice) -> Option<SharedPortDevice> {
+        self.0.insert(port, device)
+    }
+
+    /// Unmaps the device at `port` and returns it.
+    pub fn unmap(&mut self, port: u16) -> Option<SharedPortDevice> {
+        self.0.remove(&port)
+    }
+
+    /// Reads a `size`-wide value from `port`.
+    pub fn read(&self, port: u16, size: OperandSize) -> Result<u64, Exception> {
+        match self.0.get(&port) {
+            Some(device) => Ok(lock(device).read(port, size)? & size.mask()),
+            None => Ok(size.mask()),
+        }
+    }
+
+    /// Writes a `size`-wide `value` to `port`.
+    pub fn write(&self, port: u16, size: OperandSize, value: u64) -> Result<(), Exception> {
+        match self.0.get(&port) {
+            Some(device) => lock(device).write(port, size, value & size.mask()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Locks `device`, even if a previous access panicked while holding it.
+fn lock(device: &SharedPortDevice) -> MutexGuard<'_, dyn PortDevice + 'static> {
+    device.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Converts an immediate port number, which must fit in 16 bits.
+fn imm_port(imm: u64) -> Result<u16, Exception> {
+    u16::try_from(imm).map_err(|_| Exception::IllegalInstruction)
+}
+
+impl InRIMM for Emulator {
+    fn handle_in_r_imm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let port = imm_port(insn.immediate())?;
+
+        let value = self.ports.read(port, r.size())?;
+        self.regs.write(r, value);
+
+        Ok(())
+    }
+}
+
+impl InRR for Emulator {
+    fn handle_in_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+        let port = self.regs.read(insn.op1_reg()) as u16;
+
+        let value = self.ports.read(port, op0_r.size())?;
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl OutIMMR for Emulator {
+    fn handle_out_imm_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let port = imm_port(insn.op0_immediate())?;
+        let op1_r = insn.op1_reg();
+
+        let value = self.regs.read(op1_r);
+        self.ports.write(port, op1_r.size(), value)?;
+
+        Ok(())
+    }
+}
+
+impl OutRR for Emulator {
+    fn handle_out_r_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let port = self.regs.read(insn.op0_reg()) as u16;
+        let op1_r = insn.op1_reg();
+
+        let value = self.regs.read(op1_r);
+        self.ports.write(port, op1_r.size(), value)?;
+
+        Ok(())
+    }
+}
@@ -9,16 +9,17 @@
 //! Instead, this DRAM emulation directly maps
 //! offsets to the underlying memory (backed by a `Vec<u8>`), making it a
 //! simplified model for memory storage. The memory operations (reading and
-//! writing 8-bit, 16-bit, 32-bit, and 64-bit values) occur directly at specific
-//! offsets without any translation between virtual and physical addresses. This
-//! lack of page table management means that any access beyond the allocated
-//! memory space will result in an exception, such as an `AccessViolation`.
+//! writing 8-bit, 16-bit, 32-bit, 64-bit and 128-bit values) occur directly at
+//! specific offsets without any translation between virtual and physical
+//! addresses. This lack of page table management means that any access beyond
+//! the allocated memory space will result in an exception, such as an
+//! `AccessViolation`.
 //!
 //! This DRAM is not thread-safe, meaning that concurrent accesses to the memory
 //! may lead to data races and undefined behavior unless proper synchronization
 //! is applied.
 
-use crate::exception::Exception;
+use crate::exception::{AccessKind, Exception, MemoryAccess};
 
 /// The default DRAM size is set to 1Mib.
 pub const DEFAULT_SIZE: usize = 1024 * 1024;
@@ -58,8 +59,8 @@ impl Dram {
     /// - `Err(Exception::AccessViolation)`: If the offset is out of bounds.
     pub fn read_u8(&self, offset: usize) -> Result<u8, Exception> {
         const SIZE: usize = core::mem::size_of::<u8>();
-        if offset + SIZE > self.0.len() {
-            return Err(Exception::AccessViolation);
+        if offset >= self.0.len() || self.0.len() - offset < SIZE {
+            return Err(violation(offset, SIZE, AccessKind::Read));
         }
         Ok(self.0[offset])
     }
@@ -74,8 +75,8 @@ impl Dram {
     /// - `Err(Exception::AccessViolation)`: If the offset is out of bounds.
     pub fn read_u16le(&self, offset: usize) -> Result<u16, Exception> {
         const SIZE: usize = core::mem::size_of::<u16>();
-        if offset + SIZE > self.0.len() {
-            return Err(Exception::AccessViolation);
+        if offset >= self.0.len() || self.0.len() - offset < SIZE {
+            return Err(violation(offset, SIZE, AccessKind::Read));
         }
         let bytes: [u8; SIZE] = self.0[offset..offset + SIZE].try_into().unwrap();
         Ok(u16::from_le_bytes(bytes).into())
@@ -91,8 +92,8 @@ impl Dram {
     /// - `Err(Exception::AccessViolation)`: If the offset is out of bounds.
     pub fn read_u32le(&self, offset: usize) -> Result<u32, Exception> {
         const SIZE: usize = core::mem::size_of::<u32>();
-        if offset + SIZE > self.0.len() {
-            return Err(Exception::AccessViolation);
+        if offset >= self.0.len() || self.0.len() - offset < SIZE {
+            return Err(violation(offset, SIZE, AccessKind::Read));
         }
         let bytes: [u8; SIZE] = self.0[offset..offset + SIZE].try_into().unwrap();
         Ok(u32::from_le_bytes(bytes).into())
@@ -108,13 +109,30 @@ impl Dram {
     /// - `Err(Exception::AccessViolation)`: If the offset is out of bounds.
     pub fn read_u64le(&self, offset: usize) -> Result<u64, Exception> {
         const SIZE: usize = core::mem::size_of::<u64>();
-        if offset + SIZE > self.0.len() {
-            return Err(Exception::AccessViolation);
+        if offset >= self.0.len() || self.0.len() - offset < SIZE {
+            return Err(violation(offset, SIZE, AccessKind::Read));
         }
         let bytes: [u8; SIZE] = self.0[offset..offset + SIZE].try_into().unwrap();
         Ok(u64::from_le_bytes(bytes).into())
     }
 
+    /// Reads a 128-bit unsigned integer at the specified offset.
+    ///
+    /// # Arguments
+    /// - `offset`: The memory offset from which to read the 128-bit value.
+    ///
+    /// # Returns
+    /// - `Ok(u128)`: The 128-bit value read from the specified offset.
+    /// - `Err(Exception::AccessViolation)`: If the offset is out of bounds.
+    pub fn read_u128le(&self, offset: usize) -> Result<u128, Exception> {
+        const SIZE: usize = core::mem::size_of::<u128>();
+        if offset >= self.0.len() || self.0.len() - offset < SIZE {
+            return Err(violation(offset, SIZE, AccessKind::Read));
+        }
+        let bytes: [u8; SIZE] = self.0[offset..offset + SIZE].try_into().unwrap();
+        Ok(u128::from_le_bytes(bytes))
+    }
+
     /// Writes an 8-bit unsigned integer to the specified offset.
     ///
     /// # Arguments
@@ -127,7 +145,7 @@ impl Dram {
     /// - `Err(Exception::AccessViolation)`: If the offset is out of bounds.
     pub fn write_u8(&mut self, offset: usize, value: u8) -> Result<(), Exception> {
         if offset >= self.0.len() {
-            return Err(Exception::AccessViolation);
+            return Err(violation(offset, 1, AccessKind::Write));
         }
         self.0[offset] = value;
         Ok(())
@@ -145,8 +163,8 @@ impl Dram {
     /// - `Err(Exception::AccessViolation)`: If the offset is out of bounds.
     pub fn write_u16le(&mut self, offset: usize, value: u16) -> Result<(), Exception> {
         const SIZE: usize = core::mem::size_of::<u16>();
-        if offset + SIZE > self.0.len() {
-            return Err(Exception::AccessViolation);
+        if offset >= self.0.len() || self.0.len() - offset < SIZE {
+            return Err(violation(offset, SIZE, AccessKind::Write));
         }
         self.0[offset..offset + SIZE].copy_from_slice(&value.to_le_bytes());
         Ok(())
@@ -164,8 +182,8 @@ impl Dram {
     /// - `Err(Exception::AccessViolation)`: If the offset is out of bounds.
     pub fn write_u32le(&mut self, offset: usize, value: u32) -> Result<(), Exception> {
         const SIZE: usize = core::mem::size_of::<u32>();
-        if offset + SIZE > self.0.len() {
-            return Err(Exception::AccessViolation);
+        if offset >= self.0.len() || self.0.len() - offset < SIZE {
+            return Err(violation(offset, SIZE, AccessKind::Write));
         }
         self.0[offset..offset + SIZE].copy_from_slice(&value.to_le_bytes());
         Ok(())
@@ -183,10 +201,90 @@ impl Dram {
     /// - `Err(Exception::AccessViolation)`: If the offset is out of bounds.
     pub fn write_u64le(&mut self, offset: usize, value: u64) -> Result<(), Exception> {
         const SIZE: usize = core::mem::size_of::<u64>();
-        if offset + SIZE > self.0.len() {
-            return Err(Exception::AccessViolation);
+        if offset >= self.0.len() || self.0.len() - offset < SIZE {
+            return Err(violation(offset, SIZE, AccessKind::Write));
+        }
+        self.0[offset..offset + SIZE].copy_from_slice(&value.to_le_bytes());
+        Ok(())
+    }
+
+    /// Writes a 128-bit unsigned integer to the specified offset.
+    ///
+    /// # Arguments
+    /// - `offset`: The memory offset where the value should be written.
+    /// - `value`: The value to write to memory.
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the value is successfully written to the specified
+    ///   offset.
+    /// - `Err(Exception::AccessViolation)`: If the offset is out of bounds.
+    pub fn write_u128le(&mut self, offset: usize, value: u128) -> Result<(), Exception> {
+        const SIZE: usize = core::mem::size_of::<u128>();
+        if offset >= self.0.len() || self.0.len() - offset < SIZE {
+            return Err(violation(offset, SIZE, AccessKind::Write));
         }
         self.0[offset..offset + SIZE].copy_from_slice(&value.to_le_bytes());
         Ok(())
     }
+
+    /// Returns a slice of the specified length at the specified offset.
+    ///
+    /// # Arguments
+    /// - `offset`: The memory offset of the first byte.
+    /// - `len`: The number of bytes.
+    ///
+    /// # Returns
+    /// - `Ok(&[u8])`: The bytes in the specified range.
+    /// - `Err(Exception::AccessViolation)`: If any part of the range is out of
+    ///   bounds.
+    pub fn slice(&self, offset: usize, len: usize) -> Result<&[u8], Exception> {
+        let violation = || violation(offset, len, AccessKind::Read);
+        let end = offset.checked_add(len).ok_or_else(violation)?;
+        self.0.get(offset..end).ok_or_else(violation)
+    }
+
+    /// Returns a mutable slice of the specified length at the specified
+    /// offset.
+    ///
+    /// # Arguments
+    /// - `offset`: The memory offset of the first byte.
+    /// - `len`: The number of bytes.
+    ///
+    /// # Returns
+    /// - `Ok(&mut [u8])`: The bytes in the specified range.
+    /// - `Err(Exception::AccessViolation)`: If any part of the range is out of
+    ///   bounds.
+    pub fn slice_mut(&mut self, offset: usize, len: usize) -> Result<&mut [u8], Exception> {
+        let violation = || violation(offset, len, AccessKind::Write);
+        let end = offset.checked_add(len).ok_or_else(violation)?;
+        self.0.get_mut(offset..end).ok_or_else(violation)
+    }
+
+    /// Copies bytes from one range to another, the ranges may overlap.
+    ///
+    /// # Arguments
+    /// - `src`: The memory offset of the first byte to copy.
+    /// - `dst`: The memory offset where the bytes should be copied to.
+    /// - `len`: The number of bytes.
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the bytes are successfully copied.
+    /// - `Err(Exception::AccessViolation)`: If any part of either range is out
+    ///   of bounds. Nothing is copied in this case.
+    pub fn copy_within(&mut self, src: usize, dst: usize, len: usize) -> Result<(), Exception> {
+        self.slice(src, len)?;
+        self.slice_mut(dst, len)?;
+        self.0.copy_within(src..src + len, dst);
+        Ok(())
+    }
+}
+
+/// Makes an [`Exception::AccessViolation`] for the `size`-byte access at
+/// `offset`.
+fn violation(offset: usize, size: usize, kind: AccessKind) -> Exception {
+    Exception::AccessViolation(MemoryAccess {
+        address: offset as u64,
+        kind,
+        size,
+    })
 }
@@ -0,0 +1,273 @@
+//! This module implements a sequential Single Instruction, Multiple Data
+//! (SIMD) unit in software.
+//!
+//! The SIMD unit operates on the [`VectorRegister`] bank, whose 128-bit values
+//! packed operators split into 8, 16, 32 or 64-bit lanes as selected by the
+//! `b`, `w`, `d` or `q` suffix of the mnemonic. Lanes are numbered from the
+//! least significant bits, matching the byte order of memory.
+//!
+//! ## Flags
+//! Vector operators never update the [`RFlags`].
+//!
+//! [`RFlags`]: crate::emulator::RFlags
+//!
+//! ## Operators
+//! - `padd`, `psub`: Add or subtract each pair of lanes, wrapping around on
+//!   overflow.
+//! - `pand`, `por`, `pxor`: Bitwise operators on the whole 128-bit values.
+//! - `pcmpeq`: Sets each lane to all ones if the pair of lanes is equal, or to
+//!   zero otherwise.
+//! - `pshufb`: Selects each destination byte from the original destination by
+//!   the low 4 bits of the matching source byte, or zeroes it if the source
+//!   byte has its MSB set, like x86 `pshufb`.
+//! - `movq`: Moves the low 64 bits of a vector register to a register, or a
+//!   register to a vector register zeroing the upper 64 bits.
+//!
+//! ## Memory operands
+//! `movdqu` loads and stores an `xmmword` memory operand, without alignment
+//! requirements. Other sizes raise an [`Exception::IllegalInstruction`].
+//!
+//! [`VectorRegister`]: crate::emulator::VectorRegister
+
+use crate::{
+    alu::{define_handler_trait, filter_special_reg, memop_address, memop_size},
+    emulator::Emulator,
+    exception::{AccessKind, Exception},
+    isa::{Instruction, OperandSize},
+};
+
+// Move operators
+define_handler_trait!(MovdquVV, handle_movdqu_v_v);
+define_handler_trait!(MovdquVRM, handle_movdqu_v_rm);
+define_handler_trait!(MovdquRMV, handle_movdqu_rm_v);
+define_handler_trait!(MovqRV, handle_movq_r_v);
+define_handler_trait!(MovqVR, handle_movq_v_r);
+
+// Arithmetic operators
+define_handler_trait!(PaddbVV, handle_paddb_v_v);
+define_handler_trait!(PaddwVV, handle_paddw_v_v);
+define_handler_trait!(PadddVV, handle_paddd_v_v);
+define_handler_trait!(PaddqVV, handle_paddq_v_v);
+define_handler_trait!(PsubbVV, handle_psubb_v_v);
+define_handler_trait!(PsubwVV, handle_psubw_v_v);
+define_handler_trait!(PsubdVV, handle_psubd_v_v);
+define_handler_trait!(PsubqVV, handle_psubq_v_v);
+
+// Logical operators
+define_handler_trait!(PandVV, handle_pand_v_v);
+define_handler_trait!(PorVV, handle_por_v_v);
+define_handler_trait!(PxorVV, handle_pxor_v_v);
+
+// Comparison operators
+define_handler_trait!(PcmpeqbVV, handle_pcmpeqb_v_v);
+define_handler_trait!(PcmpeqwVV, handle_pcmpeqw_v_v);
+define_handler_trait!(PcmpeqdVV, handle_pcmpeqd_v_v);
+define_handler_trait!(PcmpeqqVV, handle_pcmpeqq_v_v);
+
+// Shuffle operators
+define_handler_trait!(PshufbVV, handle_pshufb_v_v);
+
+/// Applies `op` to each pair of `size`-wide lanes of `lhs` and `rhs`.
+fn lanewise(size: OperandSize, lhs: u128, rhs: u128, op: fn(u64, u64) -> u64) -> u128 {
+    let bits = size.to_size() * 8;
+    (0..128).step_by(bits).fold(0, |value, shift| {
+        let x = (lhs >> shift) as u64 & size.mask();
+        let y = (rhs >> shift) as u64 & size.mask();
+        value | (((op(x, y) & size.mask()) as u128) << shift)
+    })
+}
+
+/// Applies `op` lane-wise to both vector register operands and stores the
+/// result in the first one.
+fn handle_packed_op(
+    emulator: &mut Emulator,
+    insn: &Instruction,
+    size: OperandSize,
+    op: fn(u64, u64) -> u64,
+) -> Result<(), Exception> {
+    let lhs = emulator.vregs.read(insn.op0_vreg());
+    let rhs = emulator.vregs.read(insn.op1_vreg());
+    let value = lanewise(size, lhs, rhs, op);
+    emulator.vregs.write(insn.op0_vreg(), value);
+
+    Ok(())
+}
+
+/// Returns the address of the specified `xmmword` memory operand, which is
+/// accessed as `kind`.
+fn vector_address(
+    emulator: &Emulator,
+    op: usize,
+    insn: &Instruction,
+    kind: AccessKind,
+) -> Result<usize, Exception> {
+    match memop_size(insn, op)? {
+        OperandSize::XmmWord => memop_address(emulator, op, insn, kind),
+        _ => Err(Exception::IllegalInstruction),
+    }
+}
+
+impl MovdquVV for Emulator {
+    fn handle_movdqu_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let value = self.vregs.read(insn.op1_vreg());
+        self.vregs.write(insn.op0_vreg(), value);
+
+        Ok(())
+    }
+}
+
+impl MovdquVRM for Emulator {
+    fn handle_movdqu_v_rm(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let address = vector_address(self, 1, insn, AccessKind::Read)?;
+        let value = self.dram.read_u128le(address)?;
+        self.vregs.write(insn.op0_vreg(), value);
+
+        Ok(())
+    }
+}
+
+impl MovdquRMV for Emulator {
+    fn handle_movdqu_rm_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let address = vector_address(self, 0, insn, AccessKind::Write)?;
+        let value = self.vregs.read(insn.op1_vreg());
+        self.dram.write_u128le(address, value)?;
+
+        Ok(())
+    }
+}
+
+impl MovqRV for Emulator {
+    fn handle_movq_r_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let op0_r = Some(insn.op0_reg())
+            .and_then(filter_special_reg)
+            .ok_or(Exception::IllegalInstruction)?;
+
+        let value = self.vregs.read(insn.op1_vreg()) as u64;
+        self.regs.write(op0_r, value);
+
+        Ok(())
+    }
+}
+
+impl MovqVR for Emulator {
+    fn handle_movq_v_r(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let value = self.regs.read(insn.op1_reg()) as u128;
+        self.vregs.write(insn.op0_vreg(), value);
+
+        Ok(())
+    }
+}
+
+impl PaddbVV for Emulator {
+    fn handle_paddb_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::Byte, |x, y| x.wrapping_add(y))
+    }
+}
+
+impl PaddwVV for Emulator {
+    fn handle_paddw_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::Word, |x, y| x.wrapping_add(y))
+    }
+}
+
+impl PadddVV for Emulator {
+    fn handle_paddd_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::DWord, |x, y| x.wrapping_add(y))
+    }
+}
+
+impl PaddqVV for Emulator {
+    fn handle_paddq_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::QWord, |x, y| x.wrapping_add(y))
+    }
+}
+
+impl PsubbVV for Emulator {
+    fn handle_psubb_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::Byte, |x, y| x.wrapping_sub(y))
+    }
+}
+
+impl PsubwVV for Emulator {
+    fn handle_psubw_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::Word, |x, y| x.wrapping_sub(y))
+    }
+}
+
+impl PsubdVV for Emulator {
+    fn handle_psubd_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::DWord, |x, y| x.wrapping_sub(y))
+    }
+}
+
+impl PsubqVV for Emulator {
+    fn handle_psubq_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::QWord, |x, y| x.wrapping_sub(y))
+    }
+}
+
+impl PandVV for Emulator {
+    fn handle_pand_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::QWord, |x, y| x & y)
+    }
+}
+
+impl PorVV for Emulator {
+    fn handle_por_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::QWord, |x, y| x | y)
+    }
+}
+
+impl PxorVV for Emulator {
+    fn handle_pxor_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::QWord, |x, y| x ^ y)
+    }
+}
+
+impl PcmpeqbVV for Emulator {
+    fn handle_pcmpeqb_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::Byte, |x, y| {
+            if x == y { u64::MAX } else { 0 }
+        })
+    }
+}
+
+impl PcmpeqwVV for Emulator {
+    fn handle_pcmpeqw_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::Word, |x, y| {
+            if x == y { u64::MAX } else { 0 }
+        })
+    }
+}
+
+impl PcmpeqdVV for Emulator {
+    fn handle_pcmpeqd_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::DWord, |x, y| {
+            if x == y { u64::MAX } else { 0 }
+        })
+    }
+}
+
+impl PcmpeqqVV for Emulator {
+    fn handle_pcmpeqq_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        handle_packed_op(self, insn, OperandSize::QWord, |x, y| {
+            if x == y { u64::MAX } else { 0 }
+        })
+    }
+}
+
+impl PshufbVV for Emulator {
+    fn handle_pshufb_v_v(&mut self, insn: &Instruction) -> Result<(), Exception> {
+        let src = self.vregs.read(insn.op0_vreg()).to_le_bytes();
+        let selectors = self.vregs.read(insn.op1_vreg()).to_le_bytes();
+
+        let bytes = selectors.map(|sel| match sel & 0x80 {
+            0 => src[(sel & 0x0F) as usize],
+            _ => 0,
+        });
+        self.vregs
+            .write(insn.op0_vreg(), u128::from_le_bytes(bytes));
+
+        Ok(())
+    }
+}
@@ -32,8 +32,9 @@ function App() {
     'R13',
     'R14',
     'R15',
+    'SP',
   ];
-  const specialRegs = registers.slice(0, 2);
+  const specialRegs = [...registers.slice(0, 2), registers[18]];
   const gpRegs = registers.slice(2, 18);
 
   const [code, setCode] = React.useState(`; Fibonacci(n)
@@ -2,11 +2,22 @@ use std::sync::Mutex;
 
 use compiler::builder::{build_bytecode_s, Builder};
 use once_cell::sync::Lazy;
-use vm::emulator::{Emulator, Register};
+use vm::{
+    emulator::{Emulator, Register},
+    exception::{Exception, ExceptionRecord},
+};
 use wasm_bindgen::prelude::*;
 
 static EMULATOR: Lazy<Mutex<Emulator>> = Lazy::new(|| Mutex::new(Emulator::new()));
 
+/// Formats an exception for the frontend, which checks for a bare `Exit`
+fn format_exception(e: ExceptionRecord) -> String {
+    match e.kind {
+        Exception::Exit => format!("{:?}", e.kind),
+        _ => e.to_string(),
+    }
+}
+
 #[wasm_bindgen]
 pub fn compile(source: &str) -> Result<Vec<u8>, String> {
     let mut builder = Builder::new();
@@ -37,11 +48,11 @@ pub fn get_cycle() -> u64 {
 #[wasm_bindgen]
 pub fn execute() -> Result<(), String> {
     let mut emulator = EMULATOR.lock().unwrap();
-    emulator.execute().map_err(|e| format!("{e:?}"))
+    emulator.execute().map_err(format_exception)
 }
 
 #[wasm_bindgen]
 pub fn single_step() -> Result<(), String> {
     let mut emulator = EMULATOR.lock().unwrap();
-    emulator.single_step().map_err(|e| format!("{e:?}"))
+    emulator.single_step().map_err(format_exception)
 }